@@ -862,7 +862,7 @@ async function syncBetFromChain(betId: bigint): Promise<boolean> {
     return true;
   }
 
-  // Chain uses "timeoutclaimed" (no underscore), DB uses "timeout_claimed"
+  // Chain uses "timeout_claimed"; older contract versions returned "timeoutclaimed"
   const isResolved = chainStatus === 'revealed'
     || chainStatus === 'timeout_claimed' || chainStatus === 'timeoutclaimed'
     || chainStatus === 'canceled';
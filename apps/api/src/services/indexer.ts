@@ -9,8 +9,11 @@
  *   - coinflip.bet_canceled    (bet_id)
  *   - coinflip.bet_accepted    (bet_id, acceptor, guess)
  *   - coinflip.bet_revealed    (bet_id, side, winner)
- *   - coinflip.bet_timeout_claimed (bet_id, winner)
- *   - coinflip.commission_paid (bet_id, treasury, amount)
+ *   - coinflip.bet_timeout_claimed (bet_id, winner, payout, treasury, commission)
+ *   - coinflip.commission_paid (bet_id, treasury, amount) — older contract versions only
+ *
+ * The contracts also emit typed `wasm-coinflip_*` events (bet lifecycle and
+ * before/after balances); this indexer still keys on the legacy `action` attribute.
  */
 
 import { StargateClient } from '@cosmjs/stargate';
@@ -360,11 +363,12 @@ export class IndexerService {
 
     // Record commission in treasury_ledger
     // Native contract: commission is in accept_and_reveal event
-    // CW20 contract: separate commission_paid event
+    // Timeout claims carry it on bet_timeout_claimed; older versions emitted commission_paid
     if (
       event.type === 'coinflip.commission_paid' ||
       event.attributes.action === 'commission_paid' ||
-      (event.type === 'coinflip.accept_and_reveal' && event.attributes.commission)
+      (event.type === 'coinflip.accept_and_reveal' && event.attributes.commission) ||
+      (event.type === 'coinflip.bet_timeout_claimed' && event.attributes.commission)
     ) {
       const amount = event.attributes.commission ?? event.attributes.amount;
       if (amount && BigInt(amount) > 0n) {
@@ -1,13 +1,15 @@
 use cosmwasm_std::{
     entry_point, to_json_binary, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response,
-    StdResult, Uint128, WasmMsg, BankMsg, Coin,
+    StdResult, Uint128, WasmMsg, BankMsg, Coin, Event,
 };
 use cw2::set_contract_version;
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 
 use crate::error::ContractError;
-use crate::msg::{ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg, ReceiveMsg, StatusResponse};
-use crate::state::{Config, CONFIG};
+use crate::msg::{
+    ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg, ReceiveMsg, StatusResponse, SudoMsg,
+};
+use crate::state::{Config, CONFIG, PAUSED};
 
 const CONTRACT_NAME: &str = "crates.io:coin-presale";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -54,6 +56,10 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    if PAUSED.may_load(deps.storage)?.unwrap_or(false) {
+        return Err(ContractError::Paused);
+    }
+
     match msg {
         ExecuteMsg::Buy {} => execute_buy(deps, env, info),
         ExecuteMsg::Receive(cw20_msg) => execute_receive(deps, info, cw20_msg),
@@ -181,11 +187,29 @@ fn execute_update_config(
     enabled: Option<bool>,
     max_per_tx: Option<Uint128>,
 ) -> Result<Response, ContractError> {
-    let mut config = CONFIG.load(deps.storage)?;
+    let config = CONFIG.load(deps.storage)?;
     if info.sender != config.admin {
         return Err(ContractError::Unauthorized);
     }
 
+    let config = update_config(deps, rate_num, rate_denom, enabled, max_per_tx)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_config")
+        .add_attribute("rate", format!("{}/{}", config.rate_num, config.rate_denom))
+        .add_attribute("enabled", config.enabled.to_string()))
+}
+
+/// Validate and save config changes. Shared by the admin and governance (sudo) paths.
+fn update_config(
+    deps: DepsMut,
+    rate_num: Option<u64>,
+    rate_denom: Option<u64>,
+    enabled: Option<bool>,
+    max_per_tx: Option<Uint128>,
+) -> Result<Config, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
     if let Some(n) = rate_num {
         if n == 0 {
             return Err(ContractError::InvalidRate);
@@ -206,11 +230,7 @@ fn execute_update_config(
     }
 
     CONFIG.save(deps.storage, &config)?;
-
-    Ok(Response::new()
-        .add_attribute("action", "update_config")
-        .add_attribute("rate", format!("{}/{}", config.rate_num, config.rate_denom))
-        .add_attribute("enabled", config.enabled.to_string()))
+    Ok(config)
 }
 
 /// Admin: withdraw accumulated native AXM.
@@ -276,6 +296,60 @@ fn execute_withdraw_coin(
         .add_attribute("amount", amount.to_string()))
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn sudo(deps: DepsMut, _env: Env, msg: SudoMsg) -> Result<Response, ContractError> {
+    match msg {
+        SudoMsg::ReplaceAdmin { new_admin } => sudo_replace_admin(deps, new_admin),
+        SudoMsg::Pause {} => sudo_set_paused(deps, true),
+        SudoMsg::Unpause {} => sudo_set_paused(deps, false),
+        SudoMsg::UpdateConfig {
+            rate_num,
+            rate_denom,
+            enabled,
+            max_per_tx,
+        } => {
+            let config = update_config(deps, rate_num, rate_denom, enabled, max_per_tx)?;
+            Ok(Response::new()
+                .add_attribute("action", "sudo_update_config")
+                .add_event(
+                    Event::new("sudo_config_updated")
+                        .add_attribute("rate", format!("{}/{}", config.rate_num, config.rate_denom))
+                        .add_attribute("enabled", config.enabled.to_string()),
+                ))
+        }
+    }
+}
+
+/// Governance: replace the admin.
+fn sudo_replace_admin(deps: DepsMut, new_admin: String) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    let old_admin = config.admin;
+    config.admin = deps.api.addr_validate(&new_admin)?;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "sudo_replace_admin")
+        .add_event(
+            Event::new("sudo_admin_replaced")
+                .add_attribute("old_admin", old_admin.to_string())
+                .add_attribute("new_admin", config.admin.to_string()),
+        ))
+}
+
+/// Governance: pause or resume all execute messages.
+fn sudo_set_paused(deps: DepsMut, paused: bool) -> Result<Response, ContractError> {
+    PAUSED.save(deps.storage, &paused)?;
+
+    let (action, event) = if paused {
+        ("sudo_pause", "sudo_paused")
+    } else {
+        ("sudo_unpause", "sudo_unpaused")
+    };
+    Ok(Response::new()
+        .add_attribute("action", action)
+        .add_event(Event::new(event)))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
@@ -295,6 +369,7 @@ fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
         max_per_tx: config.max_per_tx,
         total_axm_received: config.total_axm_received,
         total_coin_sold: config.total_coin_sold,
+        paused: PAUSED.may_load(deps.storage)?.unwrap_or(false),
     })
 }
 
@@ -531,4 +606,117 @@ mod tests {
         .unwrap_err();
         assert!(matches!(err, ContractError::Unauthorized));
     }
+
+    fn setup_app() -> (cw_multi_test::App, Addr) {
+        use cw_multi_test::Executor;
+        let mut app = cw_multi_test::App::default();
+        let code = cw_multi_test::ContractWrapper::new(execute, instantiate, query).with_sudo(sudo);
+        let code_id = app.store_code(Box::new(code));
+        let msg = InstantiateMsg {
+            coin_cw20: "coin_cw20_addr".to_string(),
+            rate_num: 1,
+            rate_denom: 1,
+            enabled: true,
+            max_per_tx: Uint128::zero(),
+        };
+        let presale = app
+            .instantiate_contract(code_id, Addr::unchecked("admin"), &msg, &[], "presale", None)
+            .unwrap();
+        (app, presale)
+    }
+
+    #[test]
+    fn sudo_replace_admin() {
+        use cw_multi_test::Executor;
+        let (mut app, presale) = setup_app();
+
+        let res = app
+            .wasm_sudo(
+                presale.clone(),
+                &SudoMsg::ReplaceAdmin {
+                    new_admin: "new_admin".to_string(),
+                },
+            )
+            .unwrap();
+        let event = res
+            .events
+            .iter()
+            .find(|e| e.ty == "wasm-sudo_admin_replaced")
+            .unwrap();
+        assert!(event.attributes.iter().any(|a| a.key == "old_admin" && a.value == "admin"));
+        assert!(event.attributes.iter().any(|a| a.key == "new_admin" && a.value == "new_admin"));
+
+        // Old admin lost access, new admin has it
+        let update = ExecuteMsg::UpdateConfig {
+            rate_num: Some(2),
+            rate_denom: None,
+            enabled: None,
+            max_per_tx: None,
+        };
+        let err = app
+            .execute_contract(Addr::unchecked("admin"), presale.clone(), &update, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast_ref::<ContractError>(), Some(ContractError::Unauthorized)));
+        app.execute_contract(Addr::unchecked("new_admin"), presale, &update, &[])
+            .unwrap();
+    }
+
+    #[test]
+    fn sudo_pause_and_unpause() {
+        use cw_multi_test::Executor;
+        let (mut app, presale) = setup_app();
+        let withdraw = ExecuteMsg::WithdrawAxm {
+            amount: Uint128::new(100),
+        };
+
+        let res = app.wasm_sudo(presale.clone(), &SudoMsg::Pause {}).unwrap();
+        assert!(res.events.iter().any(|e| e.ty == "wasm-sudo_paused"));
+
+        // Admin messages are blocked too
+        let err = app
+            .execute_contract(Addr::unchecked("admin"), presale.clone(), &withdraw, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast_ref::<ContractError>(), Some(ContractError::Paused)));
+
+        let config: ConfigResponse = app
+            .wrap()
+            .query_wasm_smart(presale.clone(), &QueryMsg::Config {})
+            .unwrap();
+        assert!(config.paused);
+
+        let res = app.wasm_sudo(presale.clone(), &SudoMsg::Unpause {}).unwrap();
+        assert!(res.events.iter().any(|e| e.ty == "wasm-sudo_unpaused"));
+
+        // Back to normal validation
+        let err = app
+            .execute_contract(Addr::unchecked("random_user"), presale, &withdraw, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast_ref::<ContractError>(), Some(ContractError::Unauthorized)));
+    }
+
+    #[test]
+    fn sudo_update_config() {
+        let (mut app, presale) = setup_app();
+        let update = |rate_num| SudoMsg::UpdateConfig {
+            rate_num: Some(rate_num),
+            rate_denom: None,
+            enabled: Some(false),
+            max_per_tx: None,
+        };
+
+        // Same checks as the admin path
+        let err = app.wasm_sudo(presale.clone(), &update(0)).unwrap_err();
+        assert!(matches!(err.downcast_ref::<ContractError>(), Some(ContractError::InvalidRate)));
+
+        let res = app.wasm_sudo(presale.clone(), &update(3)).unwrap();
+        assert!(res.events.iter().any(|e| e.ty == "wasm-sudo_config_updated"));
+
+        let config: ConfigResponse = app
+            .wrap()
+            .query_wasm_smart(presale, &QueryMsg::Config {})
+            .unwrap();
+        assert_eq!(config.rate_num, 3);
+        assert!(!config.enabled);
+        assert_eq!(config.admin, Addr::unchecked("admin"));
+    }
 }
@@ -15,6 +15,9 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized,
 
+    #[error("Contract is paused by governance")]
+    Paused,
+
     #[error("Presale is currently disabled")]
     PresaleDisabled,
 
@@ -42,6 +42,28 @@ pub enum ExecuteMsg {
     WithdrawCoin { amount: Uint128 },
 }
 
+/// Chain governance messages, handled by the `sudo` entry point without an admin check.
+#[cw_serde]
+pub enum SudoMsg {
+    /// Replace the admin (e.g. lost or compromised key)
+    ReplaceAdmin { new_admin: String },
+
+    /// Block every execute message until `Unpause`. Buyers get their COIN in the same
+    /// transaction, so the presale holds no user funds for a pause to lock in.
+    Pause {},
+
+    /// Resume normal operation after `Pause`
+    Unpause {},
+
+    /// Update configuration without the admin; same fields and checks as `ExecuteMsg::UpdateConfig`
+    UpdateConfig {
+        rate_num: Option<u64>,
+        rate_denom: Option<u64>,
+        enabled: Option<bool>,
+        max_per_tx: Option<Uint128>,
+    },
+}
+
 /// CW20 receive sub-message
 #[cw_serde]
 pub enum ReceiveMsg {
@@ -73,6 +95,8 @@ pub struct ConfigResponse {
     pub max_per_tx: Uint128,
     pub total_axm_received: Uint128,
     pub total_coin_sold: Uint128,
+    /// Set by governance through `SudoMsg::Pause`
+    pub paused: bool,
 }
 
 #[cw_serde]
@@ -22,3 +22,6 @@ pub struct Config {
 }
 
 pub const CONFIG: Item<Config> = Item::new("config");
+
+/// Governance pause flag (see `SudoMsg::Pause`); absent means not paused.
+pub const PAUSED: Item<bool> = Item::new("paused");
@@ -1,12 +1,21 @@
 use cosmwasm_std::{
-    coins, BankMsg, CosmosMsg, DepsMut, Env, MessageInfo, Order, Response, Uint128,
+    coins, BankMsg, CosmosMsg, Deps, DepsMut, Env, Event, MessageInfo, Response, StdResult,
+    Uint128,
 };
 
 use crate::error::ContractError;
-use crate::state::{CONFIG, VAULT_BALANCES};
+use crate::state::{Config, CONFIG, VAULT_TOTALS};
+
+/// Native balance held by this contract.
+pub fn contract_balance(deps: Deps, env: &Env, config: &Config) -> StdResult<Uint128> {
+    Ok(deps
+        .querier
+        .query_balance(&env.contract.address, &config.accepted_denom)?
+        .amount)
+}
 
 /// Admin-only: sweep orphaned native tokens from the contract.
-/// Calculates: contract native balance - sum(all vault available + locked).
+/// Calculates: contract native balance - total owed to users (available + locked + pending).
 /// Sends the difference to `recipient` (defaults to admin).
 pub fn execute_admin_sweep(
     deps: DepsMut,
@@ -19,19 +28,8 @@ pub fn execute_admin_sweep(
         return Err(ContractError::Unauthorized);
     }
 
-    // Query the native balance held by this contract
-    let contract_balance = deps
-        .querier
-        .query_balance(&env.contract.address, &config.accepted_denom)?
-        .amount;
-
-    // Sum all vault balances (available + locked)
-    let mut total_vault = Uint128::zero();
-    for item in VAULT_BALANCES.range(deps.storage, None, None, Order::Ascending) {
-        let (_, balance) = item?;
-        total_vault += balance.available;
-        total_vault += balance.locked;
-    }
+    let contract_balance = contract_balance(deps.as_ref(), &env, &config)?;
+    let total_vault = VAULT_TOTALS.load(deps.storage)?.total_owed();
 
     // Orphaned = native balance on contract - total tracked in vaults
     let orphaned = contract_balance
@@ -60,5 +58,12 @@ pub fn execute_admin_sweep(
         .add_attribute("orphaned_amount", orphaned.to_string())
         .add_attribute("recipient", to.to_string())
         .add_attribute("contract_balance", contract_balance.to_string())
-        .add_attribute("total_vault", total_vault.to_string()))
+        .add_attribute("total_vault", total_vault.to_string())
+        .add_event(
+            Event::new("coinflip_swept")
+                .add_attribute("recipient", to.to_string())
+                .add_attribute("amount", orphaned.to_string())
+                .add_attribute("contract_balance", contract_balance.to_string())
+                .add_attribute("total_owed", total_vault.to_string()),
+        ))
 }
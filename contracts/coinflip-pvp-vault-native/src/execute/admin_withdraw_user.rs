@@ -1,7 +1,8 @@
 use cosmwasm_std::{coins, BankMsg, CosmosMsg, DepsMut, Env, MessageInfo, Response, Uint128};
 
 use crate::error::ContractError;
-use crate::state::{CONFIG, VAULT_BALANCES};
+use crate::events;
+use crate::state::{save_balance, CONFIG, VAULT_BALANCES};
 
 /// Admin-only: withdraw from a user's vault directly to the treasury wallet.
 /// Used by the sweep service to collect offchain-spent tokens (VIP, pins, etc.)
@@ -33,7 +34,7 @@ pub fn execute_admin_withdraw_user(
     }
 
     balance.available -= amount;
-    VAULT_BALANCES.save(deps.storage, &user_addr, &balance)?;
+    let balance_event = save_balance(deps.storage, &user_addr, &balance)?;
 
     // Send native tokens directly to admin (sender)
     let transfer_msg = CosmosMsg::Bank(BankMsg::Send {
@@ -47,5 +48,10 @@ pub fn execute_admin_withdraw_user(
         .add_attribute("user", user_addr.to_string())
         .add_attribute("amount", amount.to_string())
         .add_attribute("recipient", info.sender.to_string())
-        .add_attribute("new_available", balance.available.to_string()))
+        .add_attribute("new_available", balance.available.to_string())
+        .add_event(
+            events::transfer("coinflip_admin_withdraw_user", &user_addr, amount)
+                .add_attribute("recipient", info.sender.to_string()),
+        )
+        .add_event(balance_event))
 }
@@ -1,7 +1,10 @@
-use cosmwasm_std::{DepsMut, Env, MessageInfo, Response, Uint128};
+use cosmwasm_std::{to_json_binary, DepsMut, Env, MessageInfo, Response};
 
 use crate::error::ContractError;
-use crate::state::{BetStatus, BETS, CONFIG, USER_OPEN_BET_COUNT, VAULT_BALANCES};
+use crate::events;
+use crate::execute::settle::settle_bet;
+use crate::msg::BetOutcomeResponse;
+use crate::state::{BetStatus, BETS, CONFIG, VAULT_BALANCES};
 
 pub fn execute_claim_timeout(
     deps: DepsMut,
@@ -34,48 +37,26 @@ pub fn execute_claim_timeout(
     }
 
     // Acceptor wins by default
-    let pot = bet.amount * Uint128::new(2);
-    let commission = pot * Uint128::from(config.commission_bps) / Uint128::new(10_000);
-    let payout = pot - commission;
-
-    // Update balances
-    let mut maker_bal = VAULT_BALANCES.load(deps.storage, &bet.maker)?;
-    let mut acceptor_bal = VAULT_BALANCES.load(deps.storage, &acceptor)?;
-
-    maker_bal.locked -= bet.amount;
-    acceptor_bal.locked -= bet.amount;
-    acceptor_bal.available += payout;
-
-    VAULT_BALANCES.save(deps.storage, &bet.maker, &maker_bal)?;
-    VAULT_BALANCES.save(deps.storage, &acceptor, &acceptor_bal)?;
-
-    // Credit treasury
-    let mut treasury_bal = VAULT_BALANCES
-        .may_load(deps.storage, &config.treasury)?
-        .unwrap_or_default();
-    treasury_bal.available += commission;
-    VAULT_BALANCES.save(deps.storage, &config.treasury, &treasury_bal)?;
-
-    // Decrement maker's open bet count
-    let open_count = USER_OPEN_BET_COUNT
-        .may_load(deps.storage, &bet.maker)?
-        .unwrap_or(0);
-    USER_OPEN_BET_COUNT.save(deps.storage, &bet.maker, &open_count.saturating_sub(1))?;
-
-    // Update bet
-    bet.status = BetStatus::TimeoutClaimed;
-    bet.resolved_at_height = Some(env.block.height);
-    bet.payout_winner = Some(acceptor.clone());
-    bet.commission_paid = commission;
-    bet.payout_amount = payout;
-    BETS.save(deps.storage, bet_id, &bet)?;
+    let acceptor_bal = VAULT_BALANCES.load(deps.storage, &acceptor)?;
+    let settlement = settle_bet(
+        deps,
+        &env,
+        &config,
+        &mut bet,
+        acceptor_bal,
+        &acceptor,
+        BetStatus::TimeoutClaimed,
+    )?;
 
     Ok(Response::new()
         .add_attribute("action", "coinflip.bet_timeout_claimed")
         .add_attribute("bet_id", bet_id.to_string())
         .add_attribute("winner", acceptor.to_string())
-        .add_attribute("payout", payout.to_string())
-        .add_attribute("action", "coinflip.commission_paid")
+        .add_attribute("payout", settlement.payout.to_string())
         .add_attribute("treasury", config.treasury.to_string())
-        .add_attribute("commission", commission.to_string()))
+        .add_attribute("commission", settlement.commission.to_string())
+        .add_event(events::bet_timeout_claimed(&bet, &acceptor, &config.treasury))
+        .add_events(settlement.events)
+        .add_submessages(settlement.hooks)
+        .set_data(to_json_binary(&BetOutcomeResponse::from_bet(&bet))?))
 }
@@ -2,7 +2,8 @@ use cosmwasm_std::{DepsMut, Env, MessageInfo, Response};
 use cw_utils::must_pay;
 
 use crate::error::ContractError;
-use crate::state::{CONFIG, VAULT_BALANCES};
+use crate::events;
+use crate::state::{save_balance, CONFIG, VAULT_BALANCES};
 
 pub fn execute_deposit(
     deps: DepsMut,
@@ -21,11 +22,13 @@ pub fn execute_deposit(
         .may_load(deps.storage, &depositor)?
         .unwrap_or_default();
     balance.available += amount;
-    VAULT_BALANCES.save(deps.storage, &depositor, &balance)?;
+    let balance_event = save_balance(deps.storage, &depositor, &balance)?;
 
     Ok(Response::new()
         .add_attribute("action", "deposit")
         .add_attribute("depositor", depositor.to_string())
         .add_attribute("amount", amount.to_string())
-        .add_attribute("new_available", balance.available.to_string()))
+        .add_attribute("new_available", balance.available.to_string())
+        .add_event(events::transfer("coinflip_deposit", &depositor, amount))
+        .add_event(balance_event))
 }
@@ -0,0 +1,142 @@
+use cosmwasm_std::{
+    to_json_binary, DepsMut, Event, MessageInfo, Order, Reply, Response, StdResult, Storage,
+    SubMsg, SubMsgResult, WasmMsg,
+};
+
+use crate::error::ContractError;
+use crate::msg::HookMsg;
+use crate::state::{Bet, CONFIG, HOOKS, HOOK_CALLS};
+
+/// Reply id of the first hook call: the i-th registered hook replies with
+/// `HOOK_REPLY_ID + i`. Replies only arrive for failed calls.
+pub const HOOK_REPLY_ID: u64 = 1;
+
+/// Bounds on hooks, so a resolution's total gas stays predictable.
+pub const MAX_HOOKS: u32 = 10;
+pub const MIN_HOOK_GAS_LIMIT: u64 = 50_000;
+pub const MAX_HOOK_GAS_LIMIT: u64 = 1_000_000;
+
+/// Admin-only: register a hook contract, or change the gas limit of a registered one.
+pub fn execute_register_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    contract: String,
+    gas_limit: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized);
+    }
+
+    if !(MIN_HOOK_GAS_LIMIT..=MAX_HOOK_GAS_LIMIT).contains(&gas_limit) {
+        return Err(ContractError::InvalidHookGasLimit {
+            min: MIN_HOOK_GAS_LIMIT,
+            max: MAX_HOOK_GAS_LIMIT,
+        });
+    }
+
+    let contract = deps.api.addr_validate(&contract)?;
+    if !HOOKS.has(deps.storage, &contract) {
+        let count = HOOKS.keys(deps.storage, None, None, Order::Ascending).count();
+        if count >= MAX_HOOKS as usize {
+            return Err(ContractError::TooManyHooks { max: MAX_HOOKS });
+        }
+    }
+    HOOKS.save(deps.storage, &contract, &gas_limit)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "register_hook")
+        .add_attribute("contract", contract.to_string())
+        .add_attribute("gas_limit", gas_limit.to_string())
+        .add_event(
+            Event::new("coinflip_hook_registered")
+                .add_attribute("contract", contract.to_string())
+                .add_attribute("gas_limit", gas_limit.to_string()),
+        ))
+}
+
+/// Admin-only: unregister a hook contract.
+pub fn execute_remove_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    contract: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized);
+    }
+
+    let contract = deps.api.addr_validate(&contract)?;
+    if !HOOKS.has(deps.storage, &contract) {
+        return Err(ContractError::HookNotFound {
+            contract: contract.to_string(),
+        });
+    }
+    HOOKS.remove(deps.storage, &contract);
+
+    Ok(Response::new()
+        .add_attribute("action", "remove_hook")
+        .add_attribute("contract", contract.to_string())
+        .add_event(
+            Event::new("coinflip_hook_removed").add_attribute("contract", contract.to_string()),
+        ))
+}
+
+/// One `BetResolvedHook` call per registered hook for a played bet; none for a
+/// non-qualifying one.
+///
+/// Each call is gas-limited and replies only on error, so a failing or
+/// gas-hungry hook is reverted on its own and never blocks the payout.
+/// The contract behind each reply id is saved so a failure names its hook.
+pub fn bet_resolved_hooks(storage: &mut dyn Storage, bet: &Bet) -> StdResult<Vec<SubMsg>> {
+    if bet.non_qualifying {
+        return Ok(vec![]);
+    }
+    let msg = to_json_binary(&HookMsg::BetResolvedHook {
+        bet_id: bet.id,
+        maker: bet.maker.to_string(),
+        acceptor: bet.acceptor.as_ref().map(|a| a.to_string()).unwrap_or_default(),
+        winner: bet.payout_winner.as_ref().map(|w| w.to_string()).unwrap_or_default(),
+        amount: bet.amount,
+        commission: bet.commission_paid,
+    })?;
+
+    let hooks = HOOKS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    hooks
+        .into_iter()
+        .zip(HOOK_REPLY_ID..)
+        .map(|((contract, gas_limit), reply_id)| {
+            HOOK_CALLS.save(storage, reply_id, &contract)?;
+            let call = WasmMsg::Execute {
+                contract_addr: contract.to_string(),
+                msg: msg.clone(),
+                funds: vec![],
+            };
+            Ok(SubMsg::reply_on_error(call, reply_id).with_gas_limit(gas_limit))
+        })
+        .collect()
+}
+
+/// Whether `id` is the reply id of a hook call.
+pub fn is_hook_reply(id: u64) -> bool {
+    (HOOK_REPLY_ID..HOOK_REPLY_ID + MAX_HOOKS as u64).contains(&id)
+}
+
+/// A hook call failed: its changes are reverted, record the error and carry on.
+pub fn reply_hook_failed(deps: DepsMut, reply: Reply) -> Result<Response, ContractError> {
+    let contract = HOOK_CALLS.load(deps.storage, reply.id)?;
+    let error = match reply.result {
+        SubMsgResult::Err(error) => error,
+        SubMsgResult::Ok(_) => String::new(),
+    };
+    Ok(Response::new()
+        .add_attribute("action", "hook_failed")
+        .add_attribute("contract", contract.to_string())
+        .add_event(
+            Event::new("coinflip_hook_failed")
+                .add_attribute("contract", contract.to_string())
+                .add_attribute("error", error),
+        ))
+}
@@ -0,0 +1,158 @@
+use cosmwasm_std::{DepsMut, Env, Event, MessageInfo, Response, Uint128};
+
+use crate::error::ContractError;
+use crate::msg::{
+    balance_entries_checksum, BalanceEntry, ExportStateResponse, QueryMsg, ShutdownStatusResponse,
+};
+use crate::state::{
+    save_balance, ImportSource, CONFIG, IMPORTED_BALANCES, IMPORT_SOURCES, VAULT_BALANCES,
+};
+
+/// Admin-only: register a source vault and the rate used to convert its balances.
+/// Re-configuring keeps the running totals, so only the rate changes.
+pub fn execute_configure_import(
+    deps: DepsMut,
+    info: MessageInfo,
+    source_contract: String,
+    rate_num: u64,
+    rate_denom: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized);
+    }
+    if rate_num == 0 || rate_denom == 0 {
+        return Err(ContractError::InvalidRate);
+    }
+
+    let source = deps.api.addr_validate(&source_contract)?;
+    let mut import = IMPORT_SOURCES
+        .may_load(deps.storage, &source)?
+        .unwrap_or(ImportSource {
+            rate_num,
+            rate_denom,
+            imported_entries: 0,
+            total_source_amount: Uint128::zero(),
+            total_credited: Uint128::zero(),
+        });
+    import.rate_num = rate_num;
+    import.rate_denom = rate_denom;
+    IMPORT_SOURCES.save(deps.storage, &source, &import)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "configure_import")
+        .add_attribute("source_contract", source.to_string())
+        .add_attribute("rate", format!("{}/{}", rate_num, rate_denom))
+        .add_event(
+            Event::new("coinflip_import_configured")
+                .add_attribute("source_contract", source.to_string())
+                .add_attribute("rate", format!("{}/{}", rate_num, rate_denom)),
+        ))
+}
+
+/// Admin-only: credit a page of balances exported by another vault.
+///
+/// The source must have finalized its export after its emergency shutdown, so
+/// neither bets nor withdrawals can still move its balances. The page must match
+/// its export checksum and, entry by entry, the page the source exports after
+/// `start_after` now; every address is imported at most once per source. Amounts
+/// are converted with the configured rate and credited to `available`. The imported
+/// tokens must be funded separately.
+pub fn execute_import_balances(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    source_contract: String,
+    start_after: Option<String>,
+    entries: Vec<BalanceEntry>,
+    checksum: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized);
+    }
+
+    let source = deps.api.addr_validate(&source_contract)?;
+    let mut import = IMPORT_SOURCES
+        .may_load(deps.storage, &source)?
+        .ok_or(ContractError::ImportSourceNotConfigured {
+            source_contract: source.to_string(),
+        })?;
+
+    let computed = balance_entries_checksum(&entries);
+    if computed != checksum.to_lowercase() {
+        return Err(ContractError::ChecksumMismatch {
+            expected: checksum,
+            computed,
+        });
+    }
+
+    let status: ShutdownStatusResponse = deps
+        .querier
+        .query_wasm_smart(&source, &QueryMsg::ShutdownStatus {})?;
+    if !status.export_finalized {
+        return Err(ContractError::ImportSourceNotFinalized {
+            source_contract: source.to_string(),
+        });
+    }
+    let page: ExportStateResponse = deps.querier.query_wasm_smart(
+        &source,
+        &QueryMsg::ExportState {
+            start_after,
+            limit: Some(entries.len() as u32),
+        },
+    )?;
+    let mismatch = (0..entries.len().max(page.entries.len()))
+        .find(|i| entries.get(*i) != page.entries.get(*i));
+    if let Some(index) = mismatch {
+        return Err(ContractError::ImportEntryMismatch { index: index as u64 });
+    }
+
+    let mut batch_source = Uint128::zero();
+    let mut batch_credited = Uint128::zero();
+    let mut balance_events = vec![];
+    for entry in &entries {
+        let user = deps.api.addr_validate(&entry.address)?;
+        if IMPORTED_BALANCES.has(deps.storage, (&source, &user)) {
+            return Err(ContractError::AlreadyImported {
+                address: user.to_string(),
+            });
+        }
+        IMPORTED_BALANCES.save(deps.storage, (&source, &user), &entry.amount)?;
+
+        let credited = entry
+            .amount
+            .multiply_ratio(import.rate_num, import.rate_denom);
+        let mut balance = VAULT_BALANCES
+            .may_load(deps.storage, &user)?
+            .unwrap_or_default();
+        balance.available += credited;
+        balance_events.push(save_balance(deps.storage, &user, &balance)?);
+
+        batch_source += entry.amount;
+        batch_credited += credited;
+    }
+
+    import.imported_entries += entries.len() as u64;
+    import.total_source_amount += batch_source;
+    import.total_credited += batch_credited;
+    IMPORT_SOURCES.save(deps.storage, &source, &import)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "import_balances")
+        .add_attribute("source_contract", source.to_string())
+        .add_attribute("entries", entries.len().to_string())
+        .add_attribute("checksum", computed.clone())
+        .add_attribute("source_amount", batch_source.to_string())
+        .add_attribute("credited", batch_credited.to_string())
+        .add_attribute("total_imported_entries", import.imported_entries.to_string())
+        .add_event(
+            Event::new("coinflip_balances_imported")
+                .add_attribute("source_contract", source.to_string())
+                .add_attribute("entries", entries.len().to_string())
+                .add_attribute("checksum", computed)
+                .add_attribute("source_amount", batch_source.to_string())
+                .add_attribute("credited", batch_credited.to_string()),
+        )
+        .add_events(balance_events))
+}
@@ -0,0 +1,168 @@
+//! Limits players set on their own play: a daily loss limit, a max bet and a cooldown.
+//!
+//! A limit that is at least as strict as the current one applies at once. A looser one
+//! waits `LOOSEN_DELAY_SECS`, so a player cannot lift a limit on impulse. Each
+//! `SetPlayerLimits` replaces any change still waiting.
+
+use cosmwasm_std::{Addr, DepsMut, Env, Event, MessageInfo, Response, StdResult, Storage, Uint128};
+
+use crate::error::ContractError;
+use crate::leaderboard::DAY_SECS;
+use crate::state::{
+    PendingLimits, PlayerLimits, DAILY_LOSS, PENDING_LIMITS, PLAYER_LIMITS, VAULT_BALANCES,
+};
+
+pub const LOOSEN_DELAY_SECS: u64 = DAY_SECS;
+
+/// Set the sender's limits. `None` removes a limit, which counts as loosening it.
+pub fn execute_set_player_limits(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    daily_loss_limit: Option<Uint128>,
+    max_bet: Option<Uint128>,
+    cooldown_until: Option<u64>,
+) -> Result<Response, ContractError> {
+    let now = env.block.time.seconds();
+    let current = effective_limits(deps.storage, &info.sender, now)?;
+    let requested = PlayerLimits {
+        daily_loss_limit,
+        max_bet,
+        cooldown_until,
+    };
+
+    let mut applied = current.clone();
+    let mut loosened = false;
+    if at_most(requested.daily_loss_limit, current.daily_loss_limit) {
+        applied.daily_loss_limit = requested.daily_loss_limit;
+    } else {
+        loosened = true;
+    }
+    if at_most(requested.max_bet, current.max_bet) {
+        applied.max_bet = requested.max_bet;
+    } else {
+        loosened = true;
+    }
+    // Dropping a cooldown that has run out loosens nothing
+    let current_until = current.cooldown_until.unwrap_or(0);
+    if requested.cooldown_until.unwrap_or(0) >= current_until || current_until <= now {
+        applied.cooldown_until = requested.cooldown_until;
+    } else {
+        loosened = true;
+    }
+
+    PLAYER_LIMITS.save(deps.storage, &info.sender, &applied)?;
+    let effective_at = if loosened {
+        let effective_at = now + LOOSEN_DELAY_SECS;
+        let pending = PendingLimits {
+            limits: requested,
+            effective_at,
+        };
+        PENDING_LIMITS.save(deps.storage, &info.sender, &pending)?;
+        effective_at.to_string()
+    } else {
+        PENDING_LIMITS.remove(deps.storage, &info.sender);
+        String::new()
+    };
+
+    let limit = |value: Option<String>| value.unwrap_or_default();
+    Ok(Response::new()
+        .add_attribute("action", "set_player_limits")
+        .add_attribute("user", info.sender.to_string())
+        .add_event(
+            Event::new("coinflip_player_limits_set")
+                .add_attribute("user", info.sender.to_string())
+                .add_attribute(
+                    "daily_loss_limit",
+                    limit(applied.daily_loss_limit.map(|v| v.to_string())),
+                )
+                .add_attribute("max_bet", limit(applied.max_bet.map(|v| v.to_string())))
+                .add_attribute(
+                    "cooldown_until",
+                    limit(applied.cooldown_until.map(|v| v.to_string())),
+                )
+                .add_attribute("pending_effective_at", effective_at),
+        ))
+}
+
+/// The limits in force for `player` at `now`, with a due pending change applied.
+pub fn effective_limits(storage: &dyn Storage, player: &Addr, now: u64) -> StdResult<PlayerLimits> {
+    match PENDING_LIMITS.may_load(storage, player)? {
+        Some(pending) if pending.effective_at <= now => Ok(pending.limits),
+        _ => Ok(PLAYER_LIMITS.may_load(storage, player)?.unwrap_or_default()),
+    }
+}
+
+/// Fail if `player`'s limits rule out staking `stake` on a bet now. `stake_locked` is
+/// set when the stake is already in their locked balance, as for a maker whose bet is
+/// being accepted.
+pub fn check_limits(
+    storage: &dyn Storage,
+    player: &Addr,
+    now: u64,
+    stake: Uint128,
+    stake_locked: bool,
+) -> Result<(), ContractError> {
+    let limits = effective_limits(storage, player, now)?;
+    if let Some(until) = limits.cooldown_until {
+        if now < until {
+            return Err(ContractError::PlayerCooldown {
+                player: player.to_string(),
+                until,
+            });
+        }
+    }
+    if let Some(max) = limits.max_bet {
+        if stake > max {
+            return Err(ContractError::AboveMaxBet {
+                player: player.to_string(),
+                max: max.to_string(),
+            });
+        }
+    }
+    if let Some(limit) = limits.daily_loss_limit {
+        let lost = DAILY_LOSS
+            .may_load(storage, (player, now / DAY_SECS))?
+            .unwrap_or_default()
+            .net();
+        let locked = VAULT_BALANCES.may_load(storage, player)?.unwrap_or_default().locked;
+        let at_stake = if stake_locked { locked } else { locked + stake };
+        if lost + at_stake > limit {
+            return Err(ContractError::DailyLossLimit {
+                player: player.to_string(),
+                limit: limit.to_string(),
+                lost: lost.to_string(),
+                at_stake: at_stake.to_string(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Add a played bet to `player`'s results for the day of `time`. `returned` is the
+/// payout they collected, zero for a lost bet.
+pub fn record_result(
+    storage: &mut dyn Storage,
+    player: &Addr,
+    time: u64,
+    stake: Uint128,
+    returned: Uint128,
+) -> StdResult<()> {
+    let key = (player, time / DAY_SECS);
+    let mut day = DAILY_LOSS.may_load(storage, key)?.unwrap_or_default();
+    if returned.is_zero() {
+        day.lost += stake;
+    } else {
+        day.won += returned.saturating_sub(stake);
+    }
+    DAILY_LOSS.save(storage, key, &day)
+}
+
+/// Whether `new` is at least as strict as `old`, where `None` is no limit.
+fn at_most(new: Option<Uint128>, old: Option<Uint128>) -> bool {
+    match (new, old) {
+        (_, None) => true,
+        (Some(new), Some(old)) => new <= old,
+        (None, Some(_)) => false,
+    }
+}
@@ -2,9 +2,27 @@ pub mod accept_and_reveal;
 pub mod accept_bet;
 pub mod admin_sweep;
 pub mod admin_withdraw_user;
+pub mod bonus;
 pub mod cancel_bet;
 pub mod claim_timeout;
+pub mod continue_migration;
 pub mod create_bet;
 pub mod deposit;
+pub mod fees;
+pub mod hooks;
+pub mod import_balances;
+pub mod jackpot;
+pub mod limits;
+pub mod loyalty;
+pub mod pairs;
+pub mod pending_withdrawal;
+pub mod promo;
+pub mod prune_bets;
+pub mod prune_stats;
+pub mod rakeback;
+pub mod referrals;
 pub mod reveal;
+pub mod settle;
+pub mod shutdown;
+pub mod stake_discounts;
 pub mod withdraw;
@@ -0,0 +1,127 @@
+//! Daily caps on play between the same two players, against wallets farming stats
+//! and rewards by flipping against each other.
+//!
+//! Each accepted bet counts one game and both stakes toward the pair's total for the
+//! day, whichever of the two made the bet. Days older than `PAIR_DAYS_KEPT` can be
+//! pruned.
+
+use cosmwasm_std::{
+    Addr, DepsMut, Event, MessageInfo, Order, Response, StdResult, Storage, Uint128,
+};
+
+use crate::error::ContractError;
+use crate::leaderboard::DAY_SECS;
+use crate::state::{Bet, PairCapMode, PairCaps, CONFIG, PAIR_STATS};
+
+/// Days of pair totals kept before the current one
+pub const PAIR_DAYS_KEPT: u64 = 30;
+
+/// Admin-only: set or clear the daily caps on play between the same two players.
+pub fn execute_update_pair_caps(
+    deps: DepsMut,
+    info: MessageInfo,
+    caps: Option<PairCaps>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized);
+    }
+    if let Some(caps) = &caps {
+        if caps.max_games == 0 && caps.max_volume.is_zero() {
+            return Err(ContractError::InvalidPairCaps {
+                reason: "set max_games, max_volume or both".to_string(),
+            });
+        }
+    }
+
+    config.pair_caps = caps;
+    CONFIG.save(deps.storage, &config)?;
+
+    let (max_games, max_volume, mode) = match &config.pair_caps {
+        Some(caps) => (
+            caps.max_games.to_string(),
+            caps.max_volume.to_string(),
+            format!("{:?}", caps.mode),
+        ),
+        None => Default::default(),
+    };
+    Ok(Response::new()
+        .add_attribute("action", "update_pair_caps")
+        .add_attribute("max_games", &max_games)
+        .add_attribute("max_volume", &max_volume)
+        .add_attribute("mode", &mode)
+        .add_event(
+            Event::new("coinflip_pair_caps_updated")
+                .add_attribute("max_games", max_games)
+                .add_attribute("max_volume", max_volume)
+                .add_attribute("mode", mode),
+        ))
+}
+
+/// Count `bet`, being accepted by `acceptor` at `now`, toward the pair's daily totals.
+///
+/// Over a cap, fails in `Reject` mode and marks the bet non-qualifying otherwise.
+pub fn record_pair_game(
+    storage: &mut dyn Storage,
+    caps: Option<&PairCaps>,
+    bet: &mut Bet,
+    acceptor: &Addr,
+    now: u64,
+) -> Result<Vec<Event>, ContractError> {
+    let day = now / DAY_SECS;
+    let mut stats = PAIR_STATS
+        .may_load(storage, (day, &bet.maker, acceptor))?
+        .unwrap_or_default();
+    stats.games += 1;
+    stats.volume += bet.amount + bet.amount;
+
+    let mut events = vec![];
+    if let Some(caps) = caps.filter(|caps| over_cap(caps, stats.games, stats.volume)) {
+        if caps.mode == PairCapMode::Reject {
+            return Err(ContractError::PairCapReached {
+                maker: bet.maker.to_string(),
+                acceptor: acceptor.to_string(),
+            });
+        }
+        bet.non_qualifying = true;
+        events.push(
+            Event::new("coinflip_bet_non_qualifying")
+                .add_attribute("bet_id", bet.id.to_string())
+                .add_attribute("maker", bet.maker.to_string())
+                .add_attribute("acceptor", acceptor.to_string())
+                .add_attribute("games", stats.games.to_string())
+                .add_attribute("volume", stats.volume.to_string()),
+        );
+    }
+
+    PAIR_STATS.save(storage, (day, &bet.maker, acceptor), &stats)?;
+    PAIR_STATS.save(storage, (day, acceptor, &bet.maker), &stats)?;
+    Ok(events)
+}
+
+/// Whether a pair's day with `games` games and `volume` volume goes over `caps`.
+pub fn over_cap(caps: &PairCaps, games: u32, volume: Uint128) -> bool {
+    (caps.max_games > 0 && games > caps.max_games)
+        || (!caps.max_volume.is_zero() && volume > caps.max_volume)
+}
+
+/// Delete up to `limit` pair totals of days past `PAIR_DAYS_KEPT`, oldest first.
+/// Returns how many were deleted.
+pub fn prune_expired(storage: &mut dyn Storage, now: u64, limit: usize) -> StdResult<usize> {
+    let expired = (now / DAY_SECS).saturating_sub(PAIR_DAYS_KEPT);
+    let mut keys = vec![];
+    for key in PAIR_STATS
+        .keys(storage, None, None, Order::Ascending)
+        .take(limit)
+    {
+        let key = key?;
+        if key.0 >= expired {
+            break;
+        }
+        keys.push(key);
+    }
+    for (day, player, opponent) in &keys {
+        PAIR_STATS.remove(storage, (*day, player, opponent));
+    }
+    Ok(keys.len())
+}
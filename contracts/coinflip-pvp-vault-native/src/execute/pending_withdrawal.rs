@@ -0,0 +1,176 @@
+use cosmwasm_std::{DepsMut, Env, Event, MessageInfo, Response, Uint128};
+
+use crate::error::ContractError;
+use crate::events;
+use crate::execute::withdraw::payout_msg;
+use crate::state::{save_balance, CONFIG, PENDING_WITHDRAWALS, VAULT_BALANCES};
+
+/// Owner-only: pay out a pending withdrawal once its delay window has passed.
+pub fn execute_claim_withdrawal(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    withdrawal_id: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let pending = PENDING_WITHDRAWALS
+        .load(deps.storage, withdrawal_id)
+        .map_err(|_| ContractError::WithdrawalNotFound { id: withdrawal_id })?;
+
+    if pending.user != info.sender {
+        return Err(ContractError::Unauthorized);
+    }
+    if pending.frozen {
+        return Err(ContractError::WithdrawalFrozen { id: withdrawal_id });
+    }
+    if env.block.time.seconds() < pending.unlocks_at {
+        return Err(ContractError::WithdrawalLocked {
+            id: withdrawal_id,
+            unlocks_at: pending.unlocks_at,
+        });
+    }
+
+    let mut balance = VAULT_BALANCES.load(deps.storage, &pending.user)?;
+    balance.pending_withdrawal -= pending.amount;
+    let balance_event = save_balance(deps.storage, &pending.user, &balance)?;
+    PENDING_WITHDRAWALS.remove(deps.storage, withdrawal_id);
+
+    Ok(Response::new()
+        .add_message(payout_msg(&config, &pending.user, pending.amount)?)
+        .add_attribute("action", "withdraw")
+        .add_attribute("user", pending.user.to_string())
+        .add_attribute("withdrawal_id", withdrawal_id.to_string())
+        .add_attribute("amount", pending.amount.to_string())
+        .add_attribute("new_available", balance.available.to_string())
+        .add_event(events::withdrawal(
+            "coinflip_withdrawal_claimed",
+            withdrawal_id,
+            &pending.user,
+            pending.amount,
+        ))
+        .add_event(balance_event))
+}
+
+/// Owner-only: cancel a pending withdrawal and return the funds to `available`.
+/// Not while frozen: the funds could then leave in instant withdrawals.
+pub fn execute_cancel_withdrawal(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    withdrawal_id: u64,
+) -> Result<Response, ContractError> {
+    let pending = PENDING_WITHDRAWALS
+        .load(deps.storage, withdrawal_id)
+        .map_err(|_| ContractError::WithdrawalNotFound { id: withdrawal_id })?;
+
+    if pending.user != info.sender {
+        return Err(ContractError::Unauthorized);
+    }
+    if pending.frozen {
+        return Err(ContractError::WithdrawalFrozen { id: withdrawal_id });
+    }
+
+    let mut balance = VAULT_BALANCES.load(deps.storage, &pending.user)?;
+    balance.pending_withdrawal -= pending.amount;
+    balance.available += pending.amount;
+    let balance_event = save_balance(deps.storage, &pending.user, &balance)?;
+    PENDING_WITHDRAWALS.remove(deps.storage, withdrawal_id);
+
+    Ok(Response::new()
+        .add_attribute("action", "withdraw_canceled")
+        .add_attribute("user", pending.user.to_string())
+        .add_attribute("withdrawal_id", withdrawal_id.to_string())
+        .add_attribute("amount", pending.amount.to_string())
+        .add_attribute("new_available", balance.available.to_string())
+        .add_event(events::withdrawal(
+            "coinflip_withdrawal_canceled",
+            withdrawal_id,
+            &pending.user,
+            pending.amount,
+        ))
+        .add_event(balance_event))
+}
+
+/// Guardian or admin: freeze (`frozen = true`) or unfreeze a pending withdrawal.
+/// Only the admin may unfreeze. The guardian can only freeze during the delay window,
+/// before the withdrawal unlocks.
+pub fn execute_set_withdrawal_frozen(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    withdrawal_id: u64,
+    frozen: bool,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let is_guardian = config.guardian.as_ref() == Some(&info.sender);
+    if info.sender != config.admin && !(frozen && is_guardian) {
+        return Err(ContractError::Unauthorized);
+    }
+
+    let mut pending = PENDING_WITHDRAWALS
+        .load(deps.storage, withdrawal_id)
+        .map_err(|_| ContractError::WithdrawalNotFound { id: withdrawal_id })?;
+    if frozen && info.sender != config.admin && env.block.time.seconds() >= pending.unlocks_at {
+        return Err(ContractError::WithdrawalUnlocked {
+            id: withdrawal_id,
+            unlocks_at: pending.unlocks_at,
+        });
+    }
+    pending.frozen = frozen;
+    PENDING_WITHDRAWALS.save(deps.storage, withdrawal_id, &pending)?;
+
+    let (action, event) = if frozen {
+        ("withdraw_frozen", "coinflip_withdrawal_frozen")
+    } else {
+        ("withdraw_unfrozen", "coinflip_withdrawal_unfrozen")
+    };
+    Ok(Response::new()
+        .add_attribute("action", action)
+        .add_attribute("withdrawal_id", withdrawal_id.to_string())
+        .add_attribute("user", pending.user.to_string())
+        .add_attribute("by", info.sender.to_string())
+        .add_event(
+            events::withdrawal(event, withdrawal_id, &pending.user, pending.amount)
+                .add_attribute("by", info.sender.to_string()),
+        ))
+}
+
+/// Admin-only: configure the delayed-withdrawal policy.
+pub fn execute_update_withdraw_policy(
+    deps: DepsMut,
+    info: MessageInfo,
+    threshold: Uint128,
+    delay_secs: u64,
+    guardian: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized);
+    }
+
+    // Delay window must be 5 min to 7 days
+    if !(300..=604800).contains(&delay_secs) {
+        return Err(ContractError::InvalidTimeout { min: 300, max: 604800 });
+    }
+
+    config.withdraw_delay_threshold = threshold;
+    config.withdraw_delay_secs = delay_secs;
+    config.guardian = guardian.map(|g| deps.api.addr_validate(&g)).transpose()?;
+    CONFIG.save(deps.storage, &config)?;
+
+    let guardian = config
+        .guardian
+        .map(|g| g.to_string())
+        .unwrap_or_else(|| "none".to_string());
+    Ok(Response::new()
+        .add_attribute("action", "update_withdraw_policy")
+        .add_attribute("threshold", threshold.to_string())
+        .add_attribute("delay_secs", delay_secs.to_string())
+        .add_attribute("guardian", guardian.clone())
+        .add_event(
+            Event::new("coinflip_withdraw_policy_updated")
+                .add_attribute("threshold", threshold.to_string())
+                .add_attribute("delay_secs", delay_secs.to_string())
+                .add_attribute("guardian", guardian),
+        ))
+}
@@ -0,0 +1,163 @@
+use cosmwasm_std::{
+    to_json_vec, Addr, Api, Binary, DepsMut, Env, Event, MessageInfo, Response, StdResult,
+    Storage,
+};
+use sha2::{Digest, Sha256};
+
+use crate::error::ContractError;
+use crate::execute::bonus::credit_bonus;
+use crate::msg::{PromoGrant, PromoVoucher, PromoVoucherPayload};
+use crate::state::{
+    Bet, Config, PromoDiscount, CONFIG, PROMO_REDEEMERS, PROMO_REDEMPTIONS, VAULT_BALANCES,
+};
+
+/// Admin-only: set or clear the public key that signs promo vouchers.
+pub fn execute_update_promo_key(
+    deps: DepsMut,
+    info: MessageInfo,
+    pubkey: Option<Binary>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized);
+    }
+    if let Some(key) = &pubkey {
+        if key.len() != 33 && key.len() != 65 {
+            return Err(ContractError::InvalidPromoKey { len: key.len() });
+        }
+    }
+
+    config.promo_pubkey = pubkey;
+    CONFIG.save(deps.storage, &config)?;
+
+    let pubkey = config.promo_pubkey.map(|key| key.to_base64()).unwrap_or_default();
+    Ok(Response::new()
+        .add_attribute("action", "update_promo_key")
+        .add_attribute("pubkey", &pubkey)
+        .add_event(Event::new("coinflip_promo_key_updated").add_attribute("pubkey", pubkey)))
+}
+
+/// Verify a voucher and redeem it for `player` on bet `bet_id`.
+///
+/// A commission discount is returned to be stored on the bet. A bonus moves from the
+/// treasury's vault balance to the player's bonus right away, so redeem before loading
+/// the player's balance. A voucher signed for one player cannot be redeemed by another.
+pub fn redeem_voucher(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    env: &Env,
+    config: &Config,
+    player: &Addr,
+    bet_id: u64,
+    voucher: PromoVoucher,
+) -> Result<(Option<PromoDiscount>, Vec<Event>), ContractError> {
+    let Some(pubkey) = &config.promo_pubkey else {
+        return Err(ContractError::PromosDisabled);
+    };
+    let invalid = |reason: &str| ContractError::InvalidVoucher {
+        reason: reason.to_string(),
+    };
+    let payload = PromoVoucherPayload {
+        contract: env.contract.address.to_string(),
+        id: voucher.id.clone(),
+        grant: voucher.grant.clone(),
+        expires_at: voucher.expires_at,
+        max_uses: voucher.max_uses,
+        player: voucher.player.clone(),
+    };
+    let hash = Sha256::digest(to_json_vec(&payload)?);
+    if !api
+        .secp256k1_verify(&hash, &voucher.signature, pubkey)
+        .unwrap_or(false)
+    {
+        return Err(invalid("signature does not match the promo key"));
+    }
+    match voucher.grant {
+        PromoGrant::CommissionDiscount { bps } if bps == 0 || bps > 10_000 => {
+            return Err(invalid("discount must be 1..=10000 bps"));
+        }
+        PromoGrant::Bonus { amount } if amount.is_zero() => {
+            return Err(invalid("bonus of 0"));
+        }
+        _ => {}
+    }
+
+    let id = voucher.id;
+    if env.block.time.seconds() > voucher.expires_at {
+        return Err(ContractError::VoucherExpired { id });
+    }
+    if voucher.player.is_some_and(|only| only != player.as_str()) {
+        return Err(ContractError::VoucherNotForPlayer { id });
+    }
+    if PROMO_REDEEMERS.has(storage, (&id, player)) {
+        return Err(ContractError::VoucherAlreadyRedeemed { id });
+    }
+    let redemptions = PROMO_REDEMPTIONS.may_load(storage, &id)?.unwrap_or(0);
+    if redemptions >= voucher.max_uses {
+        return Err(ContractError::VoucherExhausted { id });
+    }
+    if let PromoGrant::Bonus { amount } = voucher.grant {
+        let treasury_bal = VAULT_BALANCES
+            .may_load(storage, &config.treasury)?
+            .unwrap_or_default();
+        if treasury_bal.available < amount {
+            return Err(ContractError::PromoBonusUnfunded {
+                need: amount.to_string(),
+                have: treasury_bal.available.to_string(),
+            });
+        }
+    }
+    PROMO_REDEMPTIONS.save(storage, &id, &(redemptions + 1))?;
+    PROMO_REDEEMERS.save(storage, (&id, player), &bet_id)?;
+
+    let mut event = Event::new("coinflip_promo_redeemed")
+        .add_attribute("voucher_id", &id)
+        .add_attribute("user", player.to_string())
+        .add_attribute("bet_id", bet_id.to_string())
+        .add_attribute("redemptions", (redemptions + 1).to_string());
+    match voucher.grant {
+        PromoGrant::CommissionDiscount { bps } => {
+            event = event.add_attribute("discount_bps", bps.to_string());
+            let discount = PromoDiscount {
+                player: player.clone(),
+                voucher_id: id,
+                bps,
+            };
+            Ok((Some(discount), vec![event]))
+        }
+        PromoGrant::Bonus { amount } => {
+            event = event.add_attribute("bonus", amount.to_string());
+            let mut events = vec![event];
+            events.extend(credit_bonus(storage, config, player, amount)?);
+            Ok((None, events))
+        }
+    }
+}
+
+/// Give back the uses of the discount vouchers redeemed on a bet canceled before it
+/// was played, so the players can redeem them on another bet.
+///
+/// Bonus vouchers are not given back: the bonus stays in the player's bonus balance.
+pub fn refund_vouchers(storage: &mut dyn Storage, bet: &Bet) -> StdResult<Vec<Event>> {
+    let mut events = vec![];
+    for promo in &bet.promo_discounts {
+        let id = &promo.voucher_id;
+        if PROMO_REDEEMERS.may_load(storage, (id, &promo.player))? != Some(bet.id) {
+            continue;
+        }
+        PROMO_REDEEMERS.remove(storage, (id, &promo.player));
+        let redemptions = PROMO_REDEMPTIONS
+            .may_load(storage, id)?
+            .unwrap_or(0)
+            .saturating_sub(1);
+        PROMO_REDEMPTIONS.save(storage, id, &redemptions)?;
+        events.push(
+            Event::new("coinflip_promo_refunded")
+                .add_attribute("voucher_id", id)
+                .add_attribute("user", promo.player.to_string())
+                .add_attribute("bet_id", bet.id.to_string())
+                .add_attribute("redemptions", redemptions.to_string()),
+        );
+    }
+    Ok(events)
+}
@@ -0,0 +1,160 @@
+use cosmwasm_std::{DepsMut, Env, Event, MessageInfo, Order, Response, StdResult, Storage};
+use cw_storage_plus::Bound;
+
+use crate::error::ContractError;
+use crate::state::{Bet, BetStatus, BETS, CONFIG, DAILY_STATS};
+use crate::stats;
+
+/// Permissionless: delete resolved bets with id below `before_id` whose retention
+/// period has passed.
+///
+/// Scans at most `limit` bets, newest first, and skips bets that are still live or
+/// too recent. Each pruned bet is added to the per-day totals and emitted in full
+/// as a `coinflip_bet_pruned` event. Pass the returned `next_before_id` as
+/// `before_id` to continue.
+pub fn execute_prune_bets(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    before_id: u64,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let now = env.block.time.seconds();
+    let limit = limit.unwrap_or(30).clamp(1, 100) as usize;
+
+    let bets = BETS
+        .range(
+            deps.storage,
+            None,
+            Some(Bound::exclusive(before_id)),
+            Order::Descending,
+        )
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut events = vec![];
+    for (bet_id, bet) in &bets {
+        let resolved = matches!(
+            bet.status,
+            BetStatus::Revealed
+                | BetStatus::Canceled
+                | BetStatus::TimeoutClaimed
+                | BetStatus::Refunded
+        );
+        if !resolved || now < bet.created_at_time + config.bet_retention_secs {
+            continue;
+        }
+
+        archive_bet(deps.storage, bet)?;
+        BETS.remove(deps.storage, *bet_id);
+        events.push(bet_pruned_event(bet));
+    }
+
+    // Lowest scanned id; 0 once the start of the map was reached
+    let next_before_id = if bets.len() < limit {
+        0
+    } else {
+        bets.last().map(|(id, _)| *id).unwrap_or(0)
+    };
+
+    Ok(Response::new()
+        .add_attribute("action", "prune_bets")
+        .add_attribute("scanned", bets.len().to_string())
+        .add_attribute("pruned", events.len().to_string())
+        .add_attribute("next_before_id", next_before_id.to_string())
+        .add_events(events))
+}
+
+/// Admin-only: set how long resolved bets are kept before they can be pruned.
+pub fn execute_update_bet_retention(
+    deps: DepsMut,
+    info: MessageInfo,
+    bet_retention_secs: u64,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized);
+    }
+
+    // Keep resolved bets for at least 1 day
+    if bet_retention_secs < 86400 {
+        return Err(ContractError::InvalidRetention { min: 86400 });
+    }
+
+    config.bet_retention_secs = bet_retention_secs;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_bet_retention")
+        .add_attribute("bet_retention_secs", bet_retention_secs.to_string())
+        .add_event(
+            Event::new("coinflip_bet_retention_updated")
+                .add_attribute("bet_retention_secs", bet_retention_secs.to_string()),
+        ))
+}
+
+/// Add a resolved bet to the daily totals, leaving out non-qualifying bets like the
+/// live stats do, then pass it to `stats::record_bet`, which skips bets whose stats
+/// were already recorded when they resolved.
+fn archive_bet(storage: &mut dyn Storage, bet: &Bet) -> StdResult<()> {
+    if bet.non_qualifying {
+        return stats::record_bet(storage, &mut bet.clone(), None);
+    }
+    let played = matches!(bet.status, BetStatus::Revealed | BetStatus::TimeoutClaimed);
+
+    let day = bet.created_at_time / 86400;
+    let mut daily = DAILY_STATS.may_load(storage, day)?.unwrap_or_default();
+    daily.bets += 1;
+    if played {
+        daily.games += 1;
+        daily.volume += bet.amount + bet.amount;
+        daily.commission += bet.commission_paid;
+        daily.payout += bet.payout_amount;
+    } else {
+        daily.canceled += 1;
+    }
+    DAILY_STATS.save(storage, day, &daily)?;
+
+    stats::record_bet(storage, &mut bet.clone(), None)
+}
+
+/// Full bet record for off-chain indexers; unset optional fields are omitted.
+fn bet_pruned_event(bet: &Bet) -> Event {
+    let mut event = Event::new("coinflip_bet_pruned")
+        .add_attribute("bet_id", bet.id.to_string())
+        .add_attribute("maker", bet.maker.to_string())
+        .add_attribute("amount", bet.amount.to_string())
+        .add_attribute("commitment", bet.commitment.to_base64())
+        .add_attribute("status", format!("{:?}", bet.status).to_lowercase())
+        .add_attribute("created_at_height", bet.created_at_height.to_string())
+        .add_attribute("created_at_time", bet.created_at_time.to_string())
+        .add_attribute("commission_paid", bet.commission_paid.to_string())
+        .add_attribute("payout_amount", bet.payout_amount.to_string());
+
+    if let Some(acceptor) = &bet.acceptor {
+        event = event.add_attribute("acceptor", acceptor.to_string());
+    }
+    if let Some(guess) = &bet.acceptor_guess {
+        event = event.add_attribute("acceptor_guess", format!("{:?}", guess).to_lowercase());
+    }
+    if let Some(height) = bet.accepted_at_height {
+        event = event.add_attribute("accepted_at_height", height.to_string());
+    }
+    if let Some(time) = bet.accepted_at_time {
+        event = event.add_attribute("accepted_at_time", time.to_string());
+    }
+    if let Some(secret) = &bet.reveal_secret {
+        event = event.add_attribute("reveal_secret", secret.to_base64());
+    }
+    if let Some(side) = &bet.reveal_side {
+        event = event.add_attribute("reveal_side", format!("{:?}", side).to_lowercase());
+    }
+    if let Some(height) = bet.resolved_at_height {
+        event = event.add_attribute("resolved_at_height", height.to_string());
+    }
+    if let Some(winner) = &bet.payout_winner {
+        event = event.add_attribute("winner", winner.to_string());
+    }
+    event
+}
@@ -0,0 +1,40 @@
+use cosmwasm_std::{DepsMut, Env, Response};
+
+use crate::error::ContractError;
+use crate::execute::pairs;
+use crate::leaderboard;
+
+/// Permissionless: delete leaderboard counters of day and week buckets that fell out
+/// of retention, with their board entries.
+///
+/// Deletes at most `limit` counters per call; `completed` is true once none are left.
+pub fn execute_prune_period_stats(
+    deps: DepsMut,
+    env: Env,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let limit = limit.unwrap_or(100).clamp(1, 500) as usize;
+    let pruned = leaderboard::prune_expired(deps.storage, env.block.time.seconds(), limit)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "prune_period_stats")
+        .add_attribute("pruned", pruned.to_string())
+        .add_attribute("completed", (pruned < limit).to_string()))
+}
+
+/// Permissionless: delete pair totals of days that fell out of retention.
+///
+/// Deletes at most `limit` entries per call; `completed` is true once none are left.
+pub fn execute_prune_pair_stats(
+    deps: DepsMut,
+    env: Env,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let limit = limit.unwrap_or(100).clamp(1, 500) as usize;
+    let pruned = pairs::prune_expired(deps.storage, env.block.time.seconds(), limit)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "prune_pair_stats")
+        .add_attribute("pruned", pruned.to_string())
+        .add_attribute("completed", (pruned < limit).to_string()))
+}
@@ -0,0 +1,155 @@
+use cosmwasm_std::{Addr, DepsMut, Event, MessageInfo, Response, StdResult, Storage, Uint128};
+
+use crate::error::ContractError;
+use crate::state::{
+    save_balance, Bet, Config, CONFIG, REFERRAL_EARNINGS, REFERRAL_TREES, REFERRERS, VAULT_BALANCES,
+};
+
+/// Referrers rewarded per resolved bet: the winner's referrer and two levels above it.
+pub const MAX_REFERRAL_LEVELS: usize = 3;
+
+/// Bind the sender to `referrer`. A player's referrer is set once and cannot change.
+pub fn execute_set_referrer(
+    deps: DepsMut,
+    info: MessageInfo,
+    referrer: String,
+) -> Result<Response, ContractError> {
+    let referrer = deps.api.addr_validate(&referrer)?;
+    let player = info.sender;
+    if REFERRERS.has(deps.storage, &player) {
+        return Err(ContractError::ReferrerAlreadySet);
+    }
+
+    // Walk up the whole chain of the referrer: the player must not be in it
+    let mut ancestor = Some(referrer.clone());
+    while let Some(addr) = ancestor {
+        if addr == player {
+            return Err(ContractError::ReferralCycle);
+        }
+        ancestor = REFERRERS.may_load(deps.storage, &addr)?;
+    }
+    REFERRERS.save(deps.storage, &player, &referrer)?;
+
+    // The player and its own referees join the trees of up to three ancestors
+    let own = REFERRAL_TREES.may_load(deps.storage, &player)?.unwrap_or_default();
+    let mut ancestor = Some(referrer.clone());
+    for level in 0..MAX_REFERRAL_LEVELS {
+        let Some(addr) = ancestor else {
+            break;
+        };
+        let mut tree = REFERRAL_TREES.may_load(deps.storage, &addr)?.unwrap_or_default();
+        tree[level] += 1;
+        for below in level + 1..MAX_REFERRAL_LEVELS {
+            tree[below] += own[below - level - 1];
+        }
+        REFERRAL_TREES.save(deps.storage, &addr, &tree)?;
+        ancestor = REFERRERS.may_load(deps.storage, &addr)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "set_referrer")
+        .add_attribute("player", player.to_string())
+        .add_attribute("referrer", referrer.to_string())
+        .add_event(
+            Event::new("coinflip_referrer_set")
+                .add_attribute("player", player.to_string())
+                .add_attribute("referrer", referrer.to_string()),
+        ))
+}
+
+/// Admin-only: set the referral reward of each level, in bps of the pot.
+///
+/// Rewards come out of the commission, so together with the jackpot tiers they cannot
+/// exceed `commission_bps`.
+pub fn execute_update_referral_bps(
+    deps: DepsMut,
+    info: MessageInfo,
+    bps: Vec<u16>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized);
+    }
+
+    if bps.len() > MAX_REFERRAL_LEVELS {
+        return Err(ContractError::InvalidReferralBps {
+            reason: format!("at most {MAX_REFERRAL_LEVELS} levels"),
+        });
+    }
+    let referral_bps: u32 = bps.iter().map(|level| u32::from(*level)).sum();
+    let jackpot_bps: u32 = config.jackpot_tiers.iter().map(|tier| u32::from(tier.bps)).sum();
+    if referral_bps + jackpot_bps > u32::from(config.commission_bps) {
+        return Err(ContractError::InvalidReferralBps {
+            reason: format!(
+                "referral and jackpot bps add up to more than commission_bps {}",
+                config.commission_bps
+            ),
+        });
+    }
+
+    config.referral_bps = bps;
+    CONFIG.save(deps.storage, &config)?;
+
+    let levels = config
+        .referral_bps
+        .iter()
+        .map(|level| level.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    Ok(Response::new()
+        .add_attribute("action", "update_referral_bps")
+        .add_attribute("referral_bps", &levels)
+        .add_event(
+            Event::new("coinflip_referral_bps_updated").add_attribute("referral_bps", levels),
+        ))
+}
+
+/// Credit the winner's referrers, level by level, with their cut of the pot.
+///
+/// Rewards go to the referrers' vault balances and come out of `commission`; what is
+/// left of it is returned for the treasury. A reward never exceeds what is left, in
+/// case `commission_bps` was lowered below the sum of the levels. A non-qualifying bet
+/// pays no rewards.
+pub fn credit_referral_rewards(
+    storage: &mut dyn Storage,
+    config: &Config,
+    bet: &Bet,
+    winner: &Addr,
+    pot: Uint128,
+    commission: Uint128,
+) -> StdResult<(Uint128, Vec<Event>)> {
+    if bet.non_qualifying {
+        return Ok((commission, vec![]));
+    }
+
+    let mut left = commission;
+    let mut events = vec![];
+    let mut player = winner.clone();
+    for (level, bps) in config.referral_bps.iter().enumerate() {
+        let Some(referrer) = REFERRERS.may_load(storage, &player)? else {
+            break;
+        };
+        let reward = pot.multiply_ratio(*bps, 10_000u128).min(left);
+        if !reward.is_zero() {
+            left -= reward;
+            let mut balance = VAULT_BALANCES.may_load(storage, &referrer)?.unwrap_or_default();
+            balance.available += reward;
+            events.push(save_balance(storage, &referrer, &balance)?);
+
+            let mut earnings = REFERRAL_EARNINGS.may_load(storage, &referrer)?.unwrap_or_default();
+            earnings[level] += reward;
+            REFERRAL_EARNINGS.save(storage, &referrer, &earnings)?;
+
+            events.push(
+                Event::new("coinflip_referral_reward")
+                    .add_attribute("bet_id", bet.id.to_string())
+                    .add_attribute("referrer", referrer.to_string())
+                    .add_attribute("winner", winner.to_string())
+                    .add_attribute("level", (level + 1).to_string())
+                    .add_attribute("amount", reward.to_string()),
+            );
+        }
+        player = referrer;
+    }
+    Ok((left, events))
+}
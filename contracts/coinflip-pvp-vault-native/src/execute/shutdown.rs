@@ -0,0 +1,188 @@
+use cosmwasm_std::{DepsMut, Env, Event, MessageInfo, Order, Response, StdResult};
+use cw_storage_plus::Bound;
+
+use crate::error::ContractError;
+use crate::events;
+use crate::execute::bonus::release_stake;
+use crate::execute::promo::refund_vouchers;
+use crate::state::{
+    save_balance, BetStatus, ShutdownState, BETS, CONFIG, NEXT_BET_ID, SHUTDOWN,
+    USER_OPEN_BET_COUNT, VAULT_BALANCES,
+};
+use crate::stats;
+
+/// Admin-only: enter emergency shutdown mode.
+/// New bets are blocked immediately; live bets are refunded by `ProcessShutdown`.
+pub fn execute_emergency_shutdown(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized);
+    }
+    if SHUTDOWN.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::ShutdownAlreadyActive);
+    }
+
+    // Every bet that can still be live has an id below next_bet_id
+    let last_bet_id = NEXT_BET_ID.load(deps.storage)?.saturating_sub(1);
+    let state = ShutdownState {
+        started_at_time: env.block.time.seconds(),
+        cursor: 0,
+        last_bet_id,
+        refunded_bets: 0,
+        completed: last_bet_id == 0,
+        export_finalized: false,
+    };
+    SHUTDOWN.save(deps.storage, &state)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "emergency_shutdown")
+        .add_attribute("admin", info.sender.to_string())
+        .add_attribute("last_bet_id", last_bet_id.to_string())
+        .add_event(
+            Event::new("coinflip_shutdown_started")
+                .add_attribute("admin", info.sender.to_string())
+                .add_attribute("last_bet_id", last_bet_id.to_string()),
+        ))
+}
+
+/// Permissionless: refund up to `limit` bets (Open and Accepted) back to `available`,
+/// and to the bonus for stakes taken from it.
+/// Walks bet ids in order and persists a cursor, so it can be called repeatedly
+/// until `completed` is set.
+pub fn execute_process_shutdown(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let mut state = SHUTDOWN
+        .may_load(deps.storage)?
+        .ok_or(ContractError::ShutdownNotActive)?;
+    if state.completed {
+        return Err(ContractError::ShutdownAlreadyCompleted);
+    }
+
+    let limit = limit.unwrap_or(30).clamp(1, 100) as usize;
+    let bets = BETS
+        .range(
+            deps.storage,
+            Some(Bound::exclusive(state.cursor)),
+            Some(Bound::inclusive(state.last_bet_id)),
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut refunded: u64 = 0;
+    let mut refund_events = vec![];
+    for (bet_id, mut bet) in bets.iter().cloned() {
+        state.cursor = bet_id;
+
+        let was_accepted = match bet.status {
+            BetStatus::Open => false,
+            BetStatus::Accepted => true,
+            _ => continue,
+        };
+
+        // Unlock maker stake; bonus stake goes back to the bonus
+        let mut maker_bal = VAULT_BALANCES.load(deps.storage, &bet.maker)?;
+        let mut bonus_events = release_stake(
+            deps.storage,
+            &bet.maker,
+            &mut maker_bal,
+            bet.amount,
+            bet.maker_bonus,
+            bet.amount,
+            false,
+        )?;
+        let maker_event = save_balance(deps.storage, &bet.maker, &maker_bal)?;
+
+        // Unlock acceptor stake
+        let acceptor_event = if was_accepted {
+            let acceptor = bet.acceptor.clone().unwrap();
+            let mut acceptor_bal = VAULT_BALANCES.load(deps.storage, &acceptor)?;
+            bonus_events.extend(release_stake(
+                deps.storage,
+                &acceptor,
+                &mut acceptor_bal,
+                bet.amount,
+                bet.acceptor_bonus,
+                bet.amount,
+                false,
+            )?);
+            Some(save_balance(deps.storage, &acceptor, &acceptor_bal)?)
+        } else {
+            None
+        };
+
+        // Decrement maker's open bet count
+        let open_count = USER_OPEN_BET_COUNT
+            .may_load(deps.storage, &bet.maker)?
+            .unwrap_or(0);
+        USER_OPEN_BET_COUNT.save(deps.storage, &bet.maker, &open_count.saturating_sub(1))?;
+
+        // The bet was never played: its discount vouchers can be redeemed again
+        let promo_events = refund_vouchers(deps.storage, &bet)?;
+
+        bet.status = BetStatus::Refunded;
+        bet.resolved_at_height = Some(env.block.height);
+        stats::record_bet(deps.storage, &mut bet, Some(env.block.time.seconds()))?;
+        BETS.save(deps.storage, bet_id, &bet)?;
+        refunded += 1;
+        refund_events.push(events::bet_refunded(&bet));
+        refund_events.push(maker_event);
+        refund_events.extend(acceptor_event);
+        refund_events.extend(bonus_events);
+        refund_events.extend(promo_events);
+    }
+
+    // Fewer bets than requested means the range is exhausted
+    if bets.len() < limit || state.cursor >= state.last_bet_id {
+        state.completed = true;
+        state.cursor = state.last_bet_id;
+    }
+    state.refunded_bets += refunded;
+    SHUTDOWN.save(deps.storage, &state)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "process_shutdown")
+        .add_attribute("processed", bets.len().to_string())
+        .add_attribute("refunded", refunded.to_string())
+        .add_attribute("cursor", state.cursor.to_string())
+        .add_attribute("completed", state.completed.to_string())
+        .add_events(refund_events))
+}
+
+/// Admin-only: once the shutdown has refunded every live bet, freeze the balances so
+/// another vault can import them (see `ImportBalances`). Withdrawals, jackpot draws and
+/// rakeback claims are rejected from then on, so the exported amounts cannot be paid
+/// out here as well.
+pub fn execute_finalize_export(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized);
+    }
+    let mut state = SHUTDOWN
+        .may_load(deps.storage)?
+        .ok_or(ContractError::ShutdownNotActive)?;
+    if !state.completed {
+        return Err(ContractError::ShutdownNotCompleted);
+    }
+    state.export_finalized = true;
+    SHUTDOWN.save(deps.storage, &state)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "finalize_export")
+        .add_attribute("admin", info.sender.to_string())
+        .add_event(
+            Event::new("coinflip_export_finalized")
+                .add_attribute("admin", info.sender.to_string()),
+        ))
+}
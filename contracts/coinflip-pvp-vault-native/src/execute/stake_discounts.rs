@@ -0,0 +1,95 @@
+use cosmwasm_std::{Addr, DepsMut, Event, MessageInfo, QuerierWrapper, Response, Uint128};
+
+use crate::error::ContractError;
+use crate::msg::{StakingQueryMsg, StakingState, StakingStakerInfo};
+use crate::state::{Config, StakeDiscount, CONFIG};
+
+pub const MAX_STAKE_DISCOUNTS: usize = 8;
+
+/// Admin-only: set the launch-staking contract and the stake thresholds that lower
+/// the commission of the pots a staker wins.
+pub fn execute_update_stake_discounts(
+    deps: DepsMut,
+    info: MessageInfo,
+    staking_contract: Option<String>,
+    discounts: Vec<StakeDiscount>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized);
+    }
+
+    let invalid = |reason: &str| ContractError::InvalidStakeDiscounts {
+        reason: reason.to_string(),
+    };
+    if discounts.len() > MAX_STAKE_DISCOUNTS {
+        return Err(invalid("too many discounts"));
+    }
+    if discounts.iter().any(|d| d.min_stake.is_zero() || d.bps == 0 || d.bps > 10_000) {
+        return Err(invalid("discounts need a non-zero min_stake and 1..=10000 bps"));
+    }
+    if discounts.windows(2).any(|pair| pair[0].min_stake >= pair[1].min_stake) {
+        return Err(invalid("min_stake must be strictly ascending"));
+    }
+    let staking_contract = staking_contract
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+    match &staking_contract {
+        Some(staking) => {
+            // Fail now rather than silently granting no discount at resolution
+            deps.querier
+                .query_wasm_smart::<StakingState>(staking, &StakingQueryMsg::State {})
+                .map_err(|_| invalid("staking_contract is not a staking contract"))?;
+        }
+        None if !discounts.is_empty() => return Err(invalid("discounts need a staking_contract")),
+        None => {}
+    }
+
+    config.staking_contract = staking_contract;
+    config.stake_discounts = discounts;
+    CONFIG.save(deps.storage, &config)?;
+
+    let staking = config.staking_contract.map(|a| a.to_string()).unwrap_or_default();
+    let discounts = config
+        .stake_discounts
+        .iter()
+        .map(|d| format!("{}:{}", d.min_stake, d.bps))
+        .collect::<Vec<_>>()
+        .join(",");
+    Ok(Response::new()
+        .add_attribute("action", "update_stake_discounts")
+        .add_attribute("staking_contract", &staking)
+        .add_attribute("discounts", &discounts)
+        .add_event(
+            Event::new("coinflip_stake_discounts_updated")
+                .add_attribute("staking_contract", staking)
+                .add_attribute("discounts", discounts),
+        ))
+}
+
+/// A player's LAUNCH stake and the commission bps it takes off.
+///
+/// A failing query counts as no stake, so a misbehaving staking contract never
+/// blocks a resolution.
+pub fn stake_discount(querier: QuerierWrapper, config: &Config, player: &Addr) -> (Uint128, u16) {
+    let Some(staking) = &config.staking_contract else {
+        return (Uint128::zero(), 0);
+    };
+    if config.stake_discounts.is_empty() {
+        return (Uint128::zero(), 0);
+    }
+    let msg = StakingQueryMsg::StakerInfo {
+        address: player.to_string(),
+    };
+    let staked = querier
+        .query_wasm_smart::<StakingStakerInfo>(staking, &msg)
+        .map(|info| info.staked)
+        .unwrap_or_default();
+    let bps = config
+        .stake_discounts
+        .iter()
+        .rev()
+        .find(|d| staked >= d.min_stake)
+        .map_or(0, |d| d.bps);
+    (staked, bps)
+}
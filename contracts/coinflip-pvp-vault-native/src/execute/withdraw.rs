@@ -1,11 +1,17 @@
-use cosmwasm_std::{coins, BankMsg, CosmosMsg, DepsMut, Env, MessageInfo, Response, Uint128};
+use cosmwasm_std::{
+    coins, Addr, BankMsg, CosmosMsg, DepsMut, Env, MessageInfo, Response, StdResult, Uint128,
+};
 
 use crate::error::ContractError;
-use crate::state::{CONFIG, VAULT_BALANCES};
+use crate::events;
+use crate::state::{
+    save_balance, Config, PendingWithdrawal, RecentWithdrawal, CONFIG, NEXT_WITHDRAWAL_ID,
+    PENDING_WITHDRAWALS, RECENT_WITHDRAWALS, VAULT_BALANCES,
+};
 
 pub fn execute_withdraw(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     amount: Uint128,
 ) -> Result<Response, ContractError> {
@@ -22,18 +28,78 @@ pub fn execute_withdraw(
     }
 
     balance.available -= amount;
-    VAULT_BALANCES.save(deps.storage, &info.sender, &balance)?;
 
-    // Send native tokens back to user
-    let transfer_msg = CosmosMsg::Bank(BankMsg::Send {
-        to_address: info.sender.to_string(),
-        amount: coins(amount.u128(), &config.accepted_denom),
-    });
+    // Instant withdrawals add up over the delay window, so splitting a large withdrawal
+    // does not skip the delay
+    let now = env.block.time.seconds();
+    let mut recent = RECENT_WITHDRAWALS
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    recent.retain(|w| w.at + config.withdraw_delay_secs > now);
+    let recent_total: Uint128 = recent.iter().map(|w| w.amount).sum();
+
+    // Large withdrawal: reserve the funds and let them unlock after the delay window
+    let threshold = config.withdraw_delay_threshold;
+    if !threshold.is_zero() && recent_total + amount > threshold {
+        balance.pending_withdrawal += amount;
+        let balance_event = save_balance(deps.storage, &info.sender, &balance)?;
+
+        let withdrawal_id = NEXT_WITHDRAWAL_ID.load(deps.storage)?;
+        NEXT_WITHDRAWAL_ID.save(deps.storage, &(withdrawal_id + 1))?;
+
+        let pending = PendingWithdrawal {
+            id: withdrawal_id,
+            user: info.sender.clone(),
+            amount,
+            created_at_time: now,
+            unlocks_at: now + config.withdraw_delay_secs,
+            frozen: false,
+        };
+        PENDING_WITHDRAWALS.save(deps.storage, withdrawal_id, &pending)?;
+
+        return Ok(Response::new()
+            .add_attribute("action", "withdraw_requested")
+            .add_attribute("user", info.sender.to_string())
+            .add_attribute("withdrawal_id", withdrawal_id.to_string())
+            .add_attribute("amount", amount.to_string())
+            .add_attribute("unlocks_at", pending.unlocks_at.to_string())
+            .add_attribute("new_available", balance.available.to_string())
+            .add_event(
+                events::withdrawal(
+                    "coinflip_withdrawal_requested",
+                    withdrawal_id,
+                    &info.sender,
+                    amount,
+                )
+                .add_attribute("unlocks_at", pending.unlocks_at.to_string()),
+            )
+            .add_event(balance_event));
+    }
+
+    let balance_event = save_balance(deps.storage, &info.sender, &balance)?;
+    if !threshold.is_zero() && !amount.is_zero() {
+        recent.push(RecentWithdrawal { amount, at: now });
+    }
+    if recent.is_empty() {
+        RECENT_WITHDRAWALS.remove(deps.storage, &info.sender);
+    } else {
+        RECENT_WITHDRAWALS.save(deps.storage, &info.sender, &recent)?;
+    }
 
     Ok(Response::new()
-        .add_message(transfer_msg)
+        .add_message(payout_msg(&config, &info.sender, amount)?)
         .add_attribute("action", "withdraw")
         .add_attribute("user", info.sender.to_string())
         .add_attribute("amount", amount.to_string())
-        .add_attribute("new_available", balance.available.to_string()))
+        .add_attribute("new_available", balance.available.to_string())
+        .add_event(events::transfer("coinflip_withdraw", &info.sender, amount))
+        .add_event(balance_event))
+}
+
+/// Build the message that sends `amount` of the vault token out of the contract.
+pub fn payout_msg(config: &Config, recipient: &Addr, amount: Uint128) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Bank(BankMsg::Send {
+        to_address: recipient.to_string(),
+        amount: coins(amount.u128(), &config.accepted_denom),
+    }))
 }
@@ -0,0 +1,147 @@
+//! Sorted indexes behind the `Leaderboard` query.
+//!
+//! A board ranks one metric over all time or over one day or week bucket.
+//! `LEADERBOARD` holds a (board, score, player) key per ranked player, so a
+//! descending range over a board is its ranking. All-time scores are derived from
+//! `PLAYER_STATS`, period scores from the counters in `PERIOD_STATS`.
+//!
+//! Period buckets older than `DAYS_KEPT` days or `WEEKS_KEPT` weeks can be pruned
+//! together with their board keys.
+
+use cosmwasm_std::{Addr, Order, StdError, StdResult, Storage};
+use cw_storage_plus::PrefixBound;
+
+use crate::msg::{LeaderboardMetric, LeaderboardPeriod};
+use crate::state::{PeriodStats, PlayerStats, LEADERBOARD, PERIOD_STATS, PLAYER_STATS};
+
+pub const DAY_SECS: u64 = 86_400;
+pub const WEEK_SECS: u64 = 604_800;
+/// Day buckets kept before the current one
+pub const DAYS_KEPT: u64 = 30;
+/// Week buckets kept before the current one
+pub const WEEKS_KEPT: u64 = 12;
+
+const METRICS: [LeaderboardMetric; 3] =
+    [LeaderboardMetric::Volume, LeaderboardMetric::Wins, LeaderboardMetric::Pnl];
+
+/// A period bucket: ("day" | "week", bucket). `None` is all time.
+pub type Scope = Option<(&'static str, u64)>;
+
+/// Resolve a requested period against the current block time.
+pub fn scope(period: &LeaderboardPeriod, now: u64) -> Scope {
+    match period {
+        LeaderboardPeriod::AllTime => None,
+        LeaderboardPeriod::Day { day } => Some(("day", day.unwrap_or(now / DAY_SECS))),
+        LeaderboardPeriod::Week { week } => Some(("week", week.unwrap_or(now / WEEK_SECS))),
+    }
+}
+
+/// Board key: the metric alone for all time, else `<metric>:<period>:<bucket>`.
+pub fn board(metric: &LeaderboardMetric, scope: Scope) -> String {
+    let metric = match metric {
+        LeaderboardMetric::Volume => "volume",
+        LeaderboardMetric::Wins => "wins",
+        LeaderboardMetric::Pnl => "pnl",
+    };
+    match scope {
+        None => metric.to_string(),
+        Some((period, bucket)) => format!("{metric}:{period}:{bucket}"),
+    }
+}
+
+pub fn score(metric: &LeaderboardMetric, stats: &PeriodStats) -> StdResult<i128> {
+    let wagered = to_i128(stats.wagered.u128())?;
+    Ok(match metric {
+        LeaderboardMetric::Volume => wagered,
+        LeaderboardMetric::Wins => stats.wins.into(),
+        LeaderboardMetric::Pnl => to_i128(stats.won.u128())? - wagered,
+    })
+}
+
+/// Totals of `player` within `scope`; errors if the player has none there.
+pub fn load_stats(storage: &dyn Storage, scope: Scope, player: &Addr) -> StdResult<PeriodStats> {
+    match scope {
+        None => Ok(PeriodStats::from(&PLAYER_STATS.load(storage, player)?)),
+        Some((period, bucket)) => PERIOD_STATS.load(storage, (period, bucket, player)),
+    }
+}
+
+/// Move `player` on the all-time boards, and on the current day and week boards when
+/// the resolution time is known, from `before` to `after` (one played game apart).
+pub fn record_game(
+    storage: &mut dyn Storage,
+    player: &Addr,
+    before: &PlayerStats,
+    after: &PlayerStats,
+    resolved_at: Option<u64>,
+) -> StdResult<()> {
+    let before = PeriodStats::from(before);
+    let after = PeriodStats::from(after);
+    rerank(storage, None, player, &before, &after)?;
+
+    let Some(time) = resolved_at else {
+        return Ok(());
+    };
+    for (period, secs) in [("day", DAY_SECS), ("week", WEEK_SECS)] {
+        let key = (period, time / secs, player);
+        let old = PERIOD_STATS.may_load(storage, key)?.unwrap_or_default();
+        let new = PeriodStats {
+            games: old.games + after.games - before.games,
+            wins: old.wins + after.wins - before.wins,
+            wagered: old.wagered + after.wagered - before.wagered,
+            won: old.won + after.won - before.won,
+        };
+        PERIOD_STATS.save(storage, key, &new)?;
+        rerank(storage, Some((period, time / secs)), player, &old, &new)?;
+    }
+    Ok(())
+}
+
+/// Replace the index keys of `player` on every board of `scope`.
+fn rerank(
+    storage: &mut dyn Storage,
+    scope: Scope,
+    player: &Addr,
+    old: &PeriodStats,
+    new: &PeriodStats,
+) -> StdResult<()> {
+    for metric in &METRICS {
+        let board = board(metric, scope);
+        if old.games > 0 {
+            LEADERBOARD.remove(storage, (&board, score(metric, old)?, player));
+        }
+        LEADERBOARD.save(storage, (&board, score(metric, new)?, player), &())?;
+    }
+    Ok(())
+}
+
+/// Delete up to `limit` expired period counters, oldest day buckets first, and drop
+/// their players from the boards of those buckets. Returns how many were deleted.
+pub fn prune_expired(storage: &mut dyn Storage, now: u64, limit: usize) -> StdResult<usize> {
+    let mut pruned = 0;
+    for (period, secs, kept) in [("day", DAY_SECS, DAYS_KEPT), ("week", WEEK_SECS, WEEKS_KEPT)] {
+        let expired = (now / secs).saturating_sub(kept);
+        let counters = PERIOD_STATS
+            .prefix_range(
+                storage,
+                Some(PrefixBound::inclusive((period, 0))),
+                Some(PrefixBound::exclusive((period, expired))),
+                Order::Ascending,
+            )
+            .take(limit - pruned)
+            .collect::<StdResult<Vec<_>>>()?;
+        for ((_, bucket, player), stats) in &counters {
+            for metric in &METRICS {
+                let board = board(metric, Some((period, *bucket)));
+                LEADERBOARD.remove(storage, (&board, score(metric, stats)?, player));
+            }
+            PERIOD_STATS.remove(storage, (period, *bucket, player));
+        }
+        pruned += counters.len();
+    }
+    Ok(pruned)
+}
+
+fn to_i128(amount: u128) -> StdResult<i128> {
+    i128::try_from(amount).map_err(|_| StdError::generic_err("leaderboard score overflow"))
+}
@@ -0,0 +1,67 @@
+use cosmwasm_std::{Env, StdError, Storage};
+use semver::Version;
+
+use crate::error::ContractError;
+use crate::state::{TotalsBackfill, CONFIG, NEXT_WITHDRAWAL_ID, TOTALS_BACKFILL, VAULT_TOTALS};
+
+/// A storage upgrade that brings state written by older versions up to `version`.
+///
+/// Steps run inside the `migrate` transaction, so they must touch a bounded number of
+/// keys. Rewriting whole maps belongs in a batched job (see `ExecuteMsg::ContinueMigration`).
+/// Steps must also be idempotent: a step can run on state that a newer code path
+/// already wrote in the new shape.
+pub struct Migration {
+    pub version: &'static str,
+    pub description: &'static str,
+    pub run: fn(&mut dyn Storage, &Env) -> Result<(), ContractError>,
+}
+
+/// Upgrade steps in ascending version order.
+pub const MIGRATIONS: &[Migration] = &[Migration {
+    version: "0.3.0",
+    description: "store Config in its full shape, initialize next_withdrawal_id, count vault totals",
+    run: v0_3_0,
+}];
+
+/// Run every step newer than the stored version, in order.
+/// Returns the versions of the applied steps.
+pub fn run_migrations(
+    storage: &mut dyn Storage,
+    env: &Env,
+    from: &Version,
+) -> Result<Vec<&'static str>, ContractError> {
+    let mut applied = vec![];
+    for step in MIGRATIONS {
+        let version: Version = step
+            .version
+            .parse()
+            .map_err(|e| StdError::generic_err(format!("Semver: {e}")))?;
+        if version > *from {
+            (step.run)(storage, env)?;
+            applied.push(step.version);
+        }
+    }
+    Ok(applied)
+}
+
+/// v0.3.0: Config gained `bet_ttl_secs` and the delayed-withdrawal policy, which older
+/// versions only read through serde defaults. Writing Config back persists the defaults,
+/// so later versions can drop them. Deployments that predate delayed withdrawals
+/// also have no withdrawal id counter yet.
+///
+/// The running vault totals did not exist either. Counting them walks every balance,
+/// so the step only starts a batched backfill that `ContinueMigration` runs; other
+/// messages are blocked until it completes.
+fn v0_3_0(storage: &mut dyn Storage, _env: &Env) -> Result<(), ContractError> {
+    let config = CONFIG.load(storage)?;
+    CONFIG.save(storage, &config)?;
+
+    if NEXT_WITHDRAWAL_ID.may_load(storage)?.is_none() {
+        NEXT_WITHDRAWAL_ID.save(storage, &1u64)?;
+    }
+
+    if VAULT_TOTALS.may_load(storage)?.is_none() {
+        TOTALS_BACKFILL.save(storage, &TotalsBackfill::default())?;
+    }
+    Ok(())
+}
@@ -1,5 +1,11 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Binary, Uint128};
+use cosmwasm_std::{Addr, Binary, Int128, Uint128};
+use sha2::{Digest, Sha256};
+
+use crate::state::{
+    Bet, BetStatus, CommissionShare, FeePromo, FeeTier, JackpotTier, PairCaps, PendingLimits,
+    PlayerLimits, ResetPhase, StakeDiscount,
+};
 
 #[cw_serde]
 pub struct InstantiateMsg {
@@ -20,13 +26,28 @@ pub enum ExecuteMsg {
     /// Deposit native tokens into vault (send funds with this message)
     Deposit {},
 
-    /// Withdraw available balance
+    /// Withdraw available balance.
+    /// A withdrawal that takes the sender's instant withdrawals over the last
+    /// `withdraw_delay_secs` above `withdraw_delay_threshold` becomes a pending withdrawal.
     Withdraw { amount: Uint128 },
 
+    /// Claim a pending withdrawal once its delay has passed (owner only)
+    ClaimWithdrawal { withdrawal_id: u64 },
+
+    /// Cancel a pending withdrawal that is not frozen — funds return to available (owner only)
+    CancelWithdrawal { withdrawal_id: u64 },
+
+    /// Guardian or admin: freeze a pending withdrawal so it cannot be claimed
+    FreezeWithdrawal { withdrawal_id: u64 },
+
+    /// Admin: unfreeze a previously frozen pending withdrawal
+    UnfreezeWithdrawal { withdrawal_id: u64 },
+
     /// Create a new bet with a commitment hash
     CreateBet {
         amount: Uint128,
         commitment: Binary,
+        voucher: Option<PromoVoucher>,
     },
 
     /// Cancel an open (unaccepted) bet
@@ -36,6 +57,7 @@ pub enum ExecuteMsg {
     AcceptBet {
         bet_id: u64,
         guess: Side,
+        voucher: Option<PromoVoucher>,
     },
 
     /// Accept + reveal in one atomic tx — instant result, no intermediate state
@@ -44,6 +66,7 @@ pub enum ExecuteMsg {
         guess: Side,
         side: Side,
         secret: Binary,
+        voucher: Option<PromoVoucher>,
     },
 
     /// Reveal commitment — resolves the bet (legacy, kept for compatibility)
@@ -67,6 +90,169 @@ pub enum ExecuteMsg {
         bet_ttl_secs: Option<u64>,
     },
 
+    /// Admin: configure delayed withdrawals.
+    /// `threshold` = 0 disables the delay; `guardian: None` removes the guardian.
+    UpdateWithdrawPolicy {
+        threshold: Uint128,
+        delay_secs: u64,
+        guardian: Option<String>,
+    },
+
+    /// Admin: enter emergency shutdown — blocks new bets, then refunds every live bet.
+    /// Once processing completes, only withdrawals remain allowed.
+    EmergencyShutdown {},
+
+    /// Refund the next batch of Open/Accepted bets during shutdown (anyone can call)
+    ProcessShutdown { limit: Option<u32> },
+
+    /// Admin: after the shutdown has refunded every live bet, freeze all balances for
+    /// export. Withdrawals, jackpot draws and rakeback claims are rejected from then on;
+    /// `ImportBalances` on another vault requires this.
+    FinalizeExport {},
+
+    /// Delete resolved bets with id below `before_id` once their retention period has
+    /// passed, folding them into player and daily stats (anyone can call)
+    PruneBets { before_id: u64, limit: Option<u32> },
+
+    /// Delete leaderboard counters of day and week buckets past their retention
+    /// (anyone can call)
+    PrunePeriodStats { limit: Option<u32> },
+
+    /// Delete per-pair daily totals older than 30 days (anyone can call)
+    PrunePairStats { limit: Option<u32> },
+
+    /// Admin: set how long resolved bets are kept before they can be pruned
+    UpdateBetRetention { bet_retention_secs: u64 },
+
+    /// Clear the next batch of entries of a state reset started by `migrate`, or count the
+    /// next batch of the vault totals backfill (anyone can call)
+    ContinueMigration { limit: Option<u32> },
+
+    /// Admin: register (or update) a vault whose exported balances may be imported,
+    /// with the conversion rate `credited = amount * rate_num / rate_denom`.
+    ConfigureImport {
+        source_contract: String,
+        rate_num: u64,
+        rate_denom: u64,
+    },
+
+    /// Admin: credit balances exported by `source_contract` (see `QueryMsg::ExportState`).
+    /// The source must have finalized its export (`FinalizeExport`), and `entries` must equal
+    /// its export page after `start_after`, with `checksum`; each address is imported once.
+    ImportBalances {
+        source_contract: String,
+        start_after: Option<String>,
+        entries: Vec<BalanceEntry>,
+        checksum: String,
+    },
+
+    /// Admin: register (or update) a contract that receives `HookMsg::BetResolvedHook`
+    /// on every resolved bet. The call runs with `gas_limit` and may fail without
+    /// affecting the resolution.
+    RegisterHook { contract: String, gas_limit: u64 },
+
+    /// Admin: stop calling a registered hook contract
+    RemoveHook { contract: String },
+
+    /// Admin: replace the commission split table. Shares must add up to 10000 bps;
+    /// an empty list credits all commission to the treasury's vault balance again.
+    UpdateCommissionSplit { shares: Vec<CommissionShareMsg> },
+
+    /// Pay out every accrued commission share (anyone can call)
+    FlushFees {},
+
+    /// Bind the sender to `referrer`. Can be done once; a referrer cannot be one of the
+    /// sender's own referees.
+    SetReferrer { referrer: String },
+
+    /// Admin: set the referral reward of levels 1..=3 in bps of the pot, paid out of the
+    /// commission. An empty list disables referral rewards.
+    UpdateReferralBps { bps: Vec<u16> },
+
+    /// Admin: replace the jackpot tiers (at most 5; tier id = position). Tiers are funded
+    /// out of the commission; a tier can only be removed once its pool is empty.
+    UpdateJackpotTiers { tiers: Vec<JackpotTier> },
+
+    /// Admin: commit sha256(seed) for the current cycle of a jackpot tier. A cycle is
+    /// only funded once its seed is committed.
+    CommitJackpotSeed { tier: u8, commitment: Binary },
+
+    /// Admin: reveal the committed seed of a filled cycle and pay a winner drawn from
+    /// its eligible players, weighted by stake. Accepted from the fill's
+    /// `draw_from_height` until its `reveal_deadline`.
+    DrawJackpot { tier: u8, seed: Binary },
+
+    /// Pay a winner of a filled cycle whose reveal deadline has passed, drawn from the
+    /// same entries with the fill's entropy alone (anyone can call)
+    FallbackDrawJackpot { tier: u8 },
+
+    /// Delete up to `limit` entries of a jackpot cycle that was drawn (anyone can call)
+    PruneJackpotEntries { tier: u8, cycle: u64, limit: Option<u32> },
+
+    /// Admin: reserve `bps` of each bet's commission as rakeback for its two players,
+    /// claimable for `claim_epochs` epochs (weeks) after the epoch ends. 0 bps disables it.
+    UpdateRakeback { bps: u16, claim_epochs: u64 },
+
+    /// Credit the sender's rakeback of a finished epoch to their vault balance
+    ClaimRakeback { epoch: u64 },
+
+    /// Return the unclaimed rakeback of an epoch to the treasury once its claim window
+    /// has closed (anyone can call)
+    SweepRakeback { epoch: u64 },
+
+    /// Admin: every `xp_unit` wagered on a played bet earns one loyalty XP (0 disables
+    /// XP), and `discount_xp_cost` XP buys `discount_bps` off the commission of one won
+    /// bet (a cost of 0 disables redemption)
+    UpdateLoyalty {
+        xp_unit: Uint128,
+        discount_bps: u16,
+        discount_xp_cost: u64,
+    },
+
+    /// Spend XP on fee discounts for the sender's next `bets` won bets
+    RedeemFeeDiscount { bets: u32 },
+
+    /// Admin: take `bps` off the commission of pots won by players with at least
+    /// `min_stake` LAUNCH staked in `staking_contract`; the highest reached threshold
+    /// applies. An empty list disables stake discounts.
+    UpdateStakeDiscounts {
+        staking_contract: Option<String>,
+        discounts: Vec<StakeDiscount>,
+    },
+
+    /// Admin: replace the fee schedule. The highest size tier a bet reaches replaces
+    /// `commission_bps`, `promo` replaces both while it runs, and discounts cannot take
+    /// a bet below `min_commission_bps`. Every rate is capped at 5000 bps.
+    UpdateFeeSchedule {
+        tiers: Vec<FeeTier>,
+        promo: Option<FeePromo>,
+        min_commission_bps: u16,
+    },
+
+    /// Admin: set the secp256k1 public key that signs promo vouchers; `None` stops
+    /// accepting vouchers
+    UpdatePromoKey { pubkey: Option<Binary> },
+
+    /// Admin: a credited bonus must be wagered `multiple` times (at most 100) before it
+    /// converts to `available`; 0 converts it right away
+    UpdateBonusWagering { multiple: u32 },
+
+    /// Admin: move `amount` from the treasury's vault balance to `address`'s bonus
+    /// balance, which can be staked but not withdrawn
+    CreditBonus { address: String, amount: Uint128 },
+
+    /// Set limits on the sender's own play; `None` = no limit. Stricter limits apply
+    /// at once, looser ones after a day. Replaces any change still waiting.
+    SetPlayerLimits {
+        daily_loss_limit: Option<Uint128>,
+        max_bet: Option<Uint128>,
+        cooldown_until: Option<u64>,
+    },
+
+    /// Admin: cap the games and volume (both stakes) between the same two players per
+    /// day; `None` removes the caps. Over a cap, bets are rejected or non-qualifying.
+    UpdatePairCaps { caps: Option<PairCaps> },
+
     /// Admin: propose a new admin (step 1 of 2-step transfer)
     TransferAdmin { new_admin: String },
 
@@ -87,23 +273,192 @@ pub enum ExecuteMsg {
     },
 }
 
+#[cw_serde]
+pub struct CommissionShareMsg {
+    pub label: String,
+    pub address: String,
+    pub bps: u16,
+    /// Pay out through `Distribute {}` on a launch-staking contract
+    pub distribute: bool,
+}
+
+/// A promo voucher signed by the promo key, redeemed with `CreateBet`, `AcceptBet` or
+/// `AcceptAndReveal`.
+///
+/// `signature` is a 64-byte secp256k1 signature of sha256 over the compact JSON of
+/// `PromoVoucherPayload`, which binds the voucher to this vault.
+#[cw_serde]
+pub struct PromoVoucher {
+    pub id: String,
+    pub grant: PromoGrant,
+    /// Last block time (seconds) at which the voucher can be redeemed
+    pub expires_at: u64,
+    /// Redemptions across all players; each player can redeem a voucher once
+    pub max_uses: u32,
+    /// The only player who can redeem the voucher; `None` = anyone
+    pub player: Option<String>,
+    pub signature: Binary,
+}
+
+#[cw_serde]
+pub enum PromoGrant {
+    /// `bps` off the commission if the holder wins the bet; 10000 = commission-free.
+    /// Applies after the fee schedule floor.
+    CommissionDiscount { bps: u16 },
+    /// Moved from the treasury's vault balance to the holder's bonus balance on
+    /// redemption, so it can fund the stake
+    Bonus { amount: Uint128 },
+}
+
+/// What a promo voucher signature covers, in this field order.
+#[cw_serde]
+pub struct PromoVoucherPayload {
+    /// Address of the vault the voucher is for
+    pub contract: String,
+    pub id: String,
+    pub grant: PromoGrant,
+    pub expires_at: u64,
+    pub max_uses: u32,
+    pub player: Option<String>,
+}
+
+/// The subset of the launch-staking interface used by `FlushFees`.
+#[cw_serde]
+pub enum StakingExecuteMsg {
+    Distribute {},
+}
+
+#[cw_serde]
+pub enum StakingQueryMsg {
+    Config {},
+    State {},
+    StakerInfo { address: String },
+}
+
+/// Fields read from launch-staking's `ConfigResponse`; other fields are ignored.
+#[derive(serde::Deserialize)]
+pub struct StakingConfig {
+    pub paused: bool,
+}
+
+/// Fields read from launch-staking's `StateResponse`; other fields are ignored.
+#[derive(serde::Deserialize)]
+pub struct StakingState {
+    pub total_staked: Uint128,
+}
+
+/// Fields read from launch-staking's `StakerInfoResponse`; other fields are ignored.
+#[derive(serde::Deserialize)]
+pub struct StakingStakerInfo {
+    pub staked: Uint128,
+}
+
+/// Sent to registered hook contracts (see `ExecuteMsg::RegisterHook`).
+#[cw_serde]
+pub enum HookMsg {
+    /// A bet was played to the end by `Reveal`, `AcceptAndReveal` or `ClaimTimeout`.
+    /// `amount` is each side's stake; `commission` is the vault's cut of the pot. Not
+    /// sent for bets marked non-qualifying by the pair caps.
+    BetResolvedHook {
+        bet_id: u64,
+        maker: String,
+        acceptor: String,
+        winner: String,
+        amount: Uint128,
+        commission: Uint128,
+    },
+}
+
+/// Chain governance messages, handled by the `sudo` entry point without an admin check.
+#[cw_serde]
+pub enum SudoMsg {
+    /// Replace the admin (e.g. lost or compromised key). Clears any pending admin transfer.
+    ReplaceAdmin { new_admin: String },
+
+    /// Block every execute message except `Reveal` and the exits (`CancelBet`, `ClaimTimeout`
+    /// and withdrawals) until `Unpause`
+    Pause {},
+
+    /// Resume normal operation after `Pause`
+    Unpause {},
+
+    /// Update configuration without the admin; same fields and bounds as `ExecuteMsg::UpdateConfig`
+    UpdateConfig {
+        treasury: Option<String>,
+        commission_bps: Option<u16>,
+        min_bet: Option<Uint128>,
+        reveal_timeout_secs: Option<u64>,
+        max_open_per_user: Option<u16>,
+        max_daily_amount_per_user: Option<Uint128>,
+        bet_ttl_secs: Option<u64>,
+    },
+}
+
 /// Message for contract migration.
+/// Storage upgrades for the stored cw2 version run automatically (see `migrations`).
 #[cw_serde]
 pub struct MigrateMsg {
     /// New accepted denom. If `None`, keeps current value.
     pub accepted_denom: Option<String>,
     /// If `true`, wipe all bets, vault balances, open-bet counts and daily usage.
-    /// Resets next_bet_id to 1. Use when switching to a new denom.
+    /// The wipe runs in batches via `ExecuteMsg::ContinueMigration`; other messages
+    /// are blocked until it completes, then next_bet_id is reset to 1.
+    /// Use when switching to a new denom.
     #[serde(default)]
     pub reset_state: bool,
 }
 
+#[cw_serde]
+pub struct BalanceEntry {
+    pub address: String,
+    pub amount: Uint128,
+}
+
+/// Hex SHA-256 over `len(address) || address || amount` (big-endian) for every entry, in order.
+/// Shared by `ExportState` and `ImportBalances` so a migrated page can be verified on-chain.
+pub fn balance_entries_checksum(entries: &[BalanceEntry]) -> String {
+    let mut hasher = Sha256::new();
+    for entry in entries {
+        hasher.update((entry.address.len() as u32).to_be_bytes());
+        hasher.update(entry.address.as_bytes());
+        hasher.update(entry.amount.u128().to_be_bytes());
+    }
+    hex::encode(hasher.finalize())
+}
+
 #[cw_serde]
 pub enum Side {
     Heads,
     Tails,
 }
 
+/// What a leaderboard ranks players by, over played games.
+#[cw_serde]
+pub enum LeaderboardMetric {
+    /// Sum of the player's stakes
+    Volume,
+    Wins,
+    /// Payouts received minus stakes
+    Pnl,
+}
+
+/// Time window of a leaderboard. Buckets are block time / 86400 for days and
+/// / 604800 for weeks; `None` selects the current one. The last 30 days and 12 weeks
+/// before the current bucket are kept, older ones can be pruned.
+#[cw_serde]
+pub enum LeaderboardPeriod {
+    AllTime,
+    Day { day: Option<u64> },
+    Week { week: Option<u64> },
+}
+
+/// Bucket length of an activity series.
+#[cw_serde]
+pub enum StatsGranularity {
+    Hour,
+    Day,
+}
+
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
@@ -128,6 +483,153 @@ pub enum QueryMsg {
         start_after: Option<u64>,
         limit: Option<u32>,
     },
+
+    /// Running vault totals compared with the tokens the contract actually holds
+    #[returns(SolvencyResponse)]
+    Solvency {},
+
+    /// Player profile: totals over finished bets, vault balance and open bet count
+    #[returns(PlayerStatsResponse)]
+    PlayerStats { address: String },
+
+    /// Totals over pruned bets per day (day = created_at_time / 86400), ascending.
+    /// Bets over the pair caps are not counted.
+    #[returns(DailyStatsResponse)]
+    DailyStats {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+
+    /// Commission shares waiting for `FlushFees`
+    #[returns(AccruedFeesResponse)]
+    AccruedFees {},
+
+    /// Referrer of `address`, the size of its referral tree and its referral earnings
+    #[returns(ReferralResponse)]
+    Referral { address: String },
+
+    /// Every jackpot tier with its current pool
+    #[returns(JackpotPoolsResponse)]
+    JackpotPools {},
+
+    /// Finished draws of a jackpot tier, by ascending cycle
+    #[returns(JackpotDrawsResponse)]
+    JackpotDraws {
+        tier: u8,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+
+    /// Rakeback reserve of an epoch (block time / 604800); `None` = the current epoch
+    #[returns(RakebackEpochResponse)]
+    RakebackEpoch { epoch: Option<u64> },
+
+    /// A player's rakeback for an epoch; `None` = the current epoch
+    #[returns(PlayerRakebackResponse)]
+    PlayerRakeback { address: String, epoch: Option<u64> },
+
+    /// Loyalty XP, level and outstanding fee discounts of a player
+    #[returns(LoyaltyResponse)]
+    Loyalty { address: String },
+
+    /// Commission rate the player's next won bet would pay, with their fee discount
+    /// and LAUNCH stake discount applied
+    #[returns(EffectiveCommissionResponse)]
+    EffectiveCommission { address: String },
+
+    /// Commission rate of a bet of `amount` per side resolved now, by the fee schedule
+    /// and, with `player`, their discounts as the winner
+    #[returns(QuoteFeeResponse)]
+    QuoteFee {
+        amount: Uint128,
+        player: Option<String>,
+    },
+
+    /// Redemptions of a promo voucher, and the bet `player` redeemed it on
+    #[returns(PromoVoucherResponse)]
+    PromoVoucher { id: String, player: Option<String> },
+
+    /// Bonus balance of a player and their progress toward converting it
+    #[returns(BonusResponse)]
+    Bonus { address: String },
+
+    /// Limits a player has set on their own play and their loss for today
+    #[returns(PlayerLimitsResponse)]
+    PlayerLimits { address: String },
+
+    /// Games and volume between a player and each opponent on `day` (days since the
+    /// epoch; today by default), ordered by opponent address. Days older than 30 days
+    /// can be pruned.
+    #[returns(PairExposureResponse)]
+    PairExposure {
+        address: String,
+        day: Option<u64>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Registered resolution hooks, ordered by contract address
+    #[returns(HooksResponse)]
+    Hooks {},
+
+    /// All-time bet activity: counts, volume, commission and unique players
+    #[returns(GlobalStatsResponse)]
+    GlobalStats {},
+
+    /// Bet activity per hour or day bucket for block times in `from..=to`, ascending.
+    /// Buckets without activity are omitted.
+    #[returns(StatsSeriesResponse)]
+    StatsSeries {
+        granularity: StatsGranularity,
+        from: u64,
+        to: u64,
+    },
+
+    /// Players ranked by `metric` within `period`, highest score first.
+    /// `start_after` is the address of the last entry of the previous page.
+    #[returns(LeaderboardResponse)]
+    Leaderboard {
+        metric: LeaderboardMetric,
+        period: LeaderboardPeriod,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    #[returns(ShutdownStatusResponse)]
+    ShutdownStatus {},
+
+    /// Progress of a state reset started by `migrate { reset_state: true }`, or of the
+    /// vault totals backfill started by the v0.3.0 migration
+    #[returns(MigrationStatusResponse)]
+    MigrationStatus {},
+
+    /// Paginated dump of every vault balance (available + locked + pending withdrawal),
+    /// ordered by address, with a checksum of the page for `ImportBalances`.
+    #[returns(ExportStateResponse)]
+    ExportState {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Number of balances and amount owed to users over a page of balances, by address,
+    /// for verifying a full export
+    #[returns(ExportSummaryResponse)]
+    ExportSummary {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Progress of balance imports from `source_contract`
+    #[returns(ImportStatusResponse)]
+    ImportStatus { source_contract: String },
+
+    /// Pending withdrawals, optionally filtered by owner
+    #[returns(PendingWithdrawalsResponse)]
+    PendingWithdrawals {
+        address: Option<String>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
 }
 
 // ---- Response types ----
@@ -143,12 +645,36 @@ pub struct ConfigResponse {
     pub max_open_per_user: u16,
     pub max_daily_amount_per_user: Uint128,
     pub bet_ttl_secs: u64,
+    pub withdraw_delay_threshold: Uint128,
+    pub withdraw_delay_secs: u64,
+    pub guardian: Option<Addr>,
+    pub bet_retention_secs: u64,
+    pub commission_split: Vec<CommissionShare>,
+    pub referral_bps: Vec<u16>,
+    pub jackpot_tiers: Vec<JackpotTier>,
+    pub rakeback_bps: u16,
+    pub rakeback_claim_epochs: u64,
+    pub xp_unit: Uint128,
+    pub discount_bps: u16,
+    pub discount_xp_cost: u64,
+    pub staking_contract: Option<Addr>,
+    pub stake_discounts: Vec<StakeDiscount>,
+    pub fee_tiers: Vec<FeeTier>,
+    pub fee_promo: Option<FeePromo>,
+    pub min_commission_bps: u16,
+    pub promo_pubkey: Option<Binary>,
+    pub bonus_wagering_multiple: u32,
+    pub pair_caps: Option<PairCaps>,
+    pub paused: bool,
 }
 
 #[cw_serde]
 pub struct VaultBalanceResponse {
     pub available: Uint128,
     pub locked: Uint128,
+    pub pending_withdrawal: Uint128,
+    /// Not withdrawable; see `QueryMsg::Bonus`
+    pub bonus: Uint128,
 }
 
 #[cw_serde]
@@ -157,18 +683,461 @@ pub struct BetResponse {
     pub maker: Addr,
     pub amount: Uint128,
     pub commitment: Binary,
-    pub status: String,
+    pub status: BetStatus,
     pub acceptor: Option<Addr>,
     pub acceptor_guess: Option<Side>,
+    pub created_at_height: u64,
     pub created_at_time: u64,
+    pub accepted_at_height: Option<u64>,
     pub accepted_at_time: Option<u64>,
+    pub resolved_at_height: Option<u64>,
+    /// Last second the bet can be accepted; `None` when bet TTL is disabled.
+    /// Computed from the current config, like the check in `AcceptBet`.
+    pub expires_at: Option<u64>,
+    /// Last second the maker can reveal; after it the acceptor can `ClaimTimeout`.
+    /// `None` until the bet is accepted. Computed from the current config.
+    pub reveal_deadline: Option<u64>,
     pub reveal_side: Option<Side>,
+    /// Maker's secret once revealed: `SHA256("coinflip_v1" || maker || reveal_side || secret)`
+    /// must equal `commitment`
+    pub reveal_secret: Option<Binary>,
     pub winner: Option<Addr>,
     pub payout_amount: Option<Uint128>,
     pub commission_paid: Option<Uint128>,
+    /// Commission rate applied at resolution, after discounts
+    pub commission_bps: Option<u16>,
+    /// Parts of the maker's and the acceptor's stake taken from their bonus balance
+    pub maker_bonus: Uint128,
+    pub acceptor_bonus: Uint128,
+    /// Over the pair caps: left out of stats, rewards and hooks
+    pub non_qualifying: bool,
 }
 
 #[cw_serde]
 pub struct BetsResponse {
     pub bets: Vec<BetResponse>,
+    /// Pass as `start_after` to fetch the next page; `None` when this is the last page
+    pub next_start_after: Option<u64>,
+}
+
+#[cw_serde]
+pub struct PendingWithdrawalResponse {
+    pub id: u64,
+    pub user: Addr,
+    pub amount: Uint128,
+    pub created_at_time: u64,
+    pub unlocks_at: u64,
+    pub frozen: bool,
+}
+
+#[cw_serde]
+pub struct PendingWithdrawalsResponse {
+    pub withdrawals: Vec<PendingWithdrawalResponse>,
+}
+
+#[cw_serde]
+pub struct ShutdownStatusResponse {
+    pub active: bool,
+    pub completed: bool,
+    pub started_at_time: Option<u64>,
+    /// Last processed bet id
+    pub cursor: u64,
+    /// Highest bet id that will be processed
+    pub last_bet_id: u64,
+    pub refunded_bets: u64,
+    /// Balances are frozen for export (`FinalizeExport`)
+    pub export_finalized: bool,
+}
+
+#[cw_serde]
+pub struct SolvencyResponse {
+    pub total_available: Uint128,
+    pub total_locked: Uint128,
+    pub total_pending_withdrawal: Uint128,
+    pub total_accrued_fees: Uint128,
+    pub total_jackpot: Uint128,
+    pub total_rakeback: Uint128,
+    pub total_bonus: Uint128,
+    /// available + locked + pending withdrawal + accrued fees + jackpot pools + rakeback
+    /// + bonus
+    pub total_owed: Uint128,
+    /// Tokens held by the contract
+    pub contract_balance: Uint128,
+    /// contract_balance - total_owed, if positive (sweepable by the admin)
+    pub surplus: Uint128,
+    /// total_owed - contract_balance, if positive
+    pub deficit: Uint128,
+    pub solvent: bool,
+}
+
+#[cw_serde]
+pub struct PlayerStatsResponse {
+    pub address: Addr,
+    pub games: u64,
+    pub wins: u64,
+    pub losses: u64,
+    pub canceled: u64,
+    pub wagered: Uint128,
+    pub won: Uint128,
+    pub commission_paid: Uint128,
+    pub current_streak: u32,
+    pub best_streak: u32,
+    /// Block time of the last played game (0 = never)
+    pub last_played: u64,
+    pub balance: VaultBalanceResponse,
+    /// Bets made by this player that are still open or accepted
+    pub open_bets: u16,
+}
+
+#[cw_serde]
+pub struct DailyStatsEntry {
+    pub day: u64,
+    pub bets: u64,
+    pub games: u64,
+    pub canceled: u64,
+    pub volume: Uint128,
+    pub commission: Uint128,
+    pub payout: Uint128,
+}
+
+#[cw_serde]
+pub struct DailyStatsResponse {
+    pub days: Vec<DailyStatsEntry>,
+}
+
+#[cw_serde]
+pub struct AccruedFee {
+    pub address: Addr,
+    /// Label of the address in the current split, if it is still part of it
+    pub label: Option<String>,
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub struct AccruedFeesResponse {
+    pub fees: Vec<AccruedFee>,
+    pub total: Uint128,
+}
+
+#[cw_serde]
+pub struct ReferralResponse {
+    pub address: Addr,
+    pub referrer: Option<Addr>,
+    /// Players at referral levels 1, 2 and 3 below this address
+    pub tree: Vec<u64>,
+    pub tree_size: u64,
+    /// Rewards credited from levels 1, 2 and 3
+    pub earned: Vec<Uint128>,
+    pub total_earned: Uint128,
+}
+
+#[cw_serde]
+pub struct JackpotPoolResponse {
+    pub tier: u8,
+    pub name: String,
+    pub bps: u16,
+    pub target: Uint128,
+    pub min_games: u64,
+    pub cycle: u64,
+    pub amount: Uint128,
+    /// Sum of the eligible stakes entered this cycle
+    pub total_weight: Uint128,
+    pub seed_committed: bool,
+    /// An earlier cycle that reached its target and waits for its draw
+    pub pending_draw: Option<JackpotPendingDraw>,
+}
+
+#[cw_serde]
+pub struct JackpotPendingDraw {
+    pub cycle: u64,
+    pub amount: Uint128,
+    pub total_weight: Uint128,
+    /// Height of the resolution that filled the pool
+    pub filled_at_height: u64,
+    /// First height at which `DrawJackpot` is accepted
+    pub draw_from_height: u64,
+    /// After this time `DrawJackpot` is refused and anyone can `FallbackDrawJackpot`
+    pub reveal_deadline: u64,
+}
+
+#[cw_serde]
+pub struct JackpotPoolsResponse {
+    pub pools: Vec<JackpotPoolResponse>,
+}
+
+#[cw_serde]
+pub struct JackpotDrawResponse {
+    pub tier: u8,
+    pub cycle: u64,
+    pub winner: Addr,
+    pub amount: Uint128,
+    pub winner_weight: Uint128,
+    pub total_weight: Uint128,
+    /// Revealed seed; empty for a fallback draw
+    pub seed: Binary,
+    pub entropy: Binary,
+    pub drawn_at: u64,
+}
+
+#[cw_serde]
+pub struct JackpotDrawsResponse {
+    pub draws: Vec<JackpotDrawResponse>,
+}
+
+#[cw_serde]
+pub struct RakebackEpochResponse {
+    pub epoch: u64,
+    pub current_epoch: u64,
+    /// Commission of the bets resolved in the epoch
+    pub generated: Uint128,
+    pub reserved: Uint128,
+    pub claimed: Uint128,
+    /// Last epoch during which the rakeback can be claimed
+    pub claimable_until: u64,
+    pub swept: bool,
+}
+
+#[cw_serde]
+pub struct PlayerRakebackResponse {
+    pub address: Addr,
+    pub epoch: u64,
+    pub generated: Uint128,
+    pub rebate: Uint128,
+    pub claimed: bool,
+    /// Whether `ClaimRakeback { epoch }` would pay out now
+    pub claimable: bool,
+}
+
+#[cw_serde]
+pub struct LoyaltyResponse {
+    pub address: Addr,
+    /// XP earned in total
+    pub xp: u64,
+    pub xp_spent: u64,
+    /// XP left to redeem
+    pub xp_balance: u64,
+    pub level: u64,
+    /// XP earned in total at which the next level is reached
+    pub next_level_xp: u64,
+    /// Won bets left with `discount_bps` taken off the commission
+    pub discount_bets: u32,
+    pub discount_bps: u16,
+}
+
+#[cw_serde]
+pub struct EffectiveCommissionResponse {
+    pub address: Addr,
+    /// `commission_bps` before discounts
+    pub commission_bps: u16,
+    /// Taken off by a redeemed fee discount, if one is left
+    pub fee_discount_bps: u16,
+    /// LAUNCH staked in the staking contract
+    pub staked: Uint128,
+    pub stake_discount_bps: u16,
+    pub effective_bps: u16,
+}
+
+#[cw_serde]
+pub struct QuoteFeeResponse {
+    pub amount: Uint128,
+    /// Rate of the fee schedule before discounts
+    pub schedule_bps: u16,
+    pub promo_active: bool,
+    pub fee_discount_bps: u16,
+    pub stake_discount_bps: u16,
+    /// Rate after discounts, never below `min_commission_bps`
+    pub effective_bps: u16,
+    /// Commission of the pot (both stakes) at `effective_bps`
+    pub commission: Uint128,
+}
+
+#[cw_serde]
+pub struct PromoVoucherResponse {
+    pub id: String,
+    pub redemptions: u32,
+    /// Bet on which the queried player redeemed the voucher
+    pub redeemed_on: Option<u64>,
+}
+
+#[cw_serde]
+pub struct PlayerLimitsResponse {
+    pub address: Addr,
+    /// Limits in force now
+    pub limits: PlayerLimits,
+    /// Looser limits waiting for their delay
+    pub pending: Option<PendingLimits>,
+    /// Net loss over today's played bets
+    pub daily_loss: Uint128,
+    /// Stake that can still be put at risk today; `None` without a daily loss limit
+    pub daily_loss_remaining: Option<Uint128>,
+}
+
+#[cw_serde]
+pub struct PairExposureEntry {
+    pub opponent: Addr,
+    pub games: u32,
+    /// Both players' stakes
+    pub volume: Uint128,
+}
+
+#[cw_serde]
+pub struct PairExposureResponse {
+    pub address: Addr,
+    pub day: u64,
+    pub caps: Option<PairCaps>,
+    pub pairs: Vec<PairExposureEntry>,
+    /// Pass as `start_after` to fetch the next page; `None` when this is the last page
+    pub next_start_after: Option<String>,
+}
+
+#[cw_serde]
+pub struct BonusResponse {
+    pub address: Addr,
+    /// Bonus not staked in a bet
+    pub bonus: Uint128,
+    /// Bonus staked in bets that are still live
+    pub bonus_locked: Uint128,
+    /// Stakes to play through before the bonus converts to `available`
+    pub wagering_required: Uint128,
+    pub wagered: Uint128,
+    /// required - wagered, if positive
+    pub wagering_remaining: Uint128,
+}
+
+#[cw_serde]
+pub struct HookResponse {
+    pub contract: Addr,
+    pub gas_limit: u64,
+}
+
+#[cw_serde]
+pub struct HooksResponse {
+    pub hooks: Vec<HookResponse>,
+}
+
+#[cw_serde]
+pub struct GlobalStatsResponse {
+    pub bets_created: u64,
+    /// Bets played to the end (revealed or timeout-claimed)
+    pub bets_resolved: u64,
+    /// Bets canceled or refunded before they were played
+    pub bets_canceled: u64,
+    /// Sum of both stakes in resolved bets
+    pub volume: Uint128,
+    pub commission: Uint128,
+    pub unique_players: u64,
+}
+
+#[cw_serde]
+pub struct StatsBucket {
+    /// Block time at which the bucket starts
+    pub start: u64,
+    pub bets_created: u64,
+    pub bets_resolved: u64,
+    pub bets_canceled: u64,
+    pub volume: Uint128,
+    pub commission: Uint128,
+    /// Distinct addresses that created or accepted a bet within the bucket
+    pub unique_players: u64,
+}
+
+#[cw_serde]
+pub struct StatsSeriesResponse {
+    pub granularity: StatsGranularity,
+    pub buckets: Vec<StatsBucket>,
+    /// Set when the page is full: pass as `from` to continue
+    pub next_from: Option<u64>,
+}
+
+#[cw_serde]
+pub struct LeaderboardEntry {
+    pub address: Addr,
+    pub score: Int128,
+    pub games: u64,
+    pub wins: u64,
+    pub wagered: Uint128,
+    pub won: Uint128,
+}
+
+#[cw_serde]
+pub struct LeaderboardResponse {
+    pub metric: LeaderboardMetric,
+    /// The requested period, with the bucket filled in
+    pub period: LeaderboardPeriod,
+    pub entries: Vec<LeaderboardEntry>,
+    /// Pass as `start_after` to get the next page; `None` on the last page
+    pub next_start_after: Option<String>,
+}
+
+#[cw_serde]
+pub struct MigrationStatusResponse {
+    /// True while the reset or the totals backfill is running and normal operations are blocked
+    pub in_progress: bool,
+    /// Map currently being cleared (`None` when no reset is running)
+    pub phase: Option<ResetPhase>,
+    pub cleared_entries: u64,
+    /// Balances counted so far by the totals backfill (`None` when no backfill is running)
+    pub counted_balances: Option<u64>,
+}
+
+#[cw_serde]
+pub struct ExportStateResponse {
+    pub entries: Vec<BalanceEntry>,
+    /// Sum of `amount` over this page
+    pub page_total: Uint128,
+    pub checksum: String,
+    /// Pass as `start_after` to fetch the next page; `None` when this is the last page
+    pub next_start_after: Option<String>,
+}
+
+#[cw_serde]
+pub struct ExportSummaryResponse {
+    /// Non-zero balances among the ones scanned on this page, and what they are owed
+    pub total_entries: u64,
+    pub total_amount: Uint128,
+    /// Pass as `start_after` to fetch the next page; `None` when this is the last page
+    pub next_start_after: Option<String>,
+}
+
+#[cw_serde]
+pub struct ImportStatusResponse {
+    pub source_contract: Addr,
+    pub rate_num: u64,
+    pub rate_denom: u64,
+    pub imported_entries: u64,
+    /// Sum of imported amounts as reported by the source (before conversion)
+    pub total_source_amount: Uint128,
+    /// Sum credited to vault balances here (after conversion)
+    pub total_credited: Uint128,
+}
+
+/// `data` of the `CreateBet` response
+#[cw_serde]
+pub struct CreateBetResponse {
+    pub bet_id: u64,
+}
+
+/// `data` of the `Reveal`, `AcceptAndReveal` and `ClaimTimeout` responses
+#[cw_serde]
+pub struct BetOutcomeResponse {
+    pub bet_id: u64,
+    pub status: BetStatus,
+    pub winner: Addr,
+    pub payout: Uint128,
+    pub commission: Uint128,
+    /// Maker's revealed side; `None` for timeout claims
+    pub side: Option<Side>,
+}
+
+impl BetOutcomeResponse {
+    pub fn from_bet(bet: &Bet) -> Self {
+        Self {
+            bet_id: bet.id,
+            status: bet.status.clone(),
+            winner: bet.payout_winner.clone().unwrap(),
+            payout: bet.payout_amount,
+            commission: bet.commission_paid,
+            side: bet.reveal_side.clone(),
+        }
+    }
 }
@@ -1,7 +1,33 @@
-use cosmwasm_std::{Deps, Env, Order, StdResult};
+use cosmwasm_std::{Addr, Deps, Env, Int128, Order, StdResult, Uint128};
 
-use crate::msg::{BetResponse, BetsResponse, ConfigResponse, VaultBalanceResponse};
-use crate::state::{BetStatus, BETS, CONFIG, VAULT_BALANCES};
+use crate::execute::fees::{floor_bps, schedule_bps};
+use crate::execute::limits::effective_limits;
+use crate::execute::loyalty::{level_of, level_xp};
+use crate::execute::rakeback::epoch_of;
+use crate::execute::stake_discounts::stake_discount;
+use crate::leaderboard::{self, DAY_SECS};
+use crate::msg::{
+    balance_entries_checksum, AccruedFee, AccruedFeesResponse, BalanceEntry, BetResponse,
+    BetsResponse, BonusResponse, ConfigResponse, DailyStatsEntry, DailyStatsResponse,
+    EffectiveCommissionResponse, ExportStateResponse, ExportSummaryResponse, GlobalStatsResponse,
+    HookResponse, HooksResponse, ImportStatusResponse, JackpotDrawResponse, JackpotDrawsResponse,
+    JackpotPendingDraw, JackpotPoolResponse, JackpotPoolsResponse, LeaderboardEntry,
+    LeaderboardMetric, LeaderboardPeriod, LeaderboardResponse, LoyaltyResponse,
+    MigrationStatusResponse, PairExposureEntry, PairExposureResponse, PendingWithdrawalResponse,
+    PendingWithdrawalsResponse, PlayerLimitsResponse, PlayerRakebackResponse, PlayerStatsResponse,
+    PromoVoucherResponse, QuoteFeeResponse, RakebackEpochResponse, ReferralResponse,
+    ShutdownStatusResponse, SolvencyResponse, StatsBucket, StatsGranularity, StatsSeriesResponse,
+    VaultBalanceResponse,
+};
+use crate::state::{
+    Config, VaultBalance, BetStatus, ACCRUED_FEES, BETS, BONUS_WAGERING, CONFIG, DAILY_LOSS,
+    DAILY_STATS, HOOKS, IMPORT_SOURCES, JACKPOT_DRAWS, JACKPOT_POOLS, LEADERBOARD, LOYALTY,
+    PAIR_STATS, PAUSED, PENDING_LIMITS, PENDING_WITHDRAWALS, PLAYER_STATS, PROMO_REDEEMERS,
+    PROMO_REDEMPTIONS, RAKEBACK, RAKEBACK_EPOCHS, REFERRAL_EARNINGS, REFERRAL_TREES, REFERRERS,
+    RESET_PROGRESS, SHUTDOWN, STATS_SERIES, TOTALS_BACKFILL, USER_OPEN_BET_COUNT, VAULT_BALANCES,
+    VAULT_TOTALS,
+};
+use crate::stats::HOUR_SECS;
 
 pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     let config = CONFIG.load(deps.storage)?;
@@ -15,6 +41,27 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
         max_open_per_user: config.max_open_per_user,
         max_daily_amount_per_user: config.max_daily_amount_per_user,
         bet_ttl_secs: config.bet_ttl_secs,
+        withdraw_delay_threshold: config.withdraw_delay_threshold,
+        withdraw_delay_secs: config.withdraw_delay_secs,
+        guardian: config.guardian,
+        bet_retention_secs: config.bet_retention_secs,
+        commission_split: config.commission_split,
+        referral_bps: config.referral_bps,
+        jackpot_tiers: config.jackpot_tiers,
+        rakeback_bps: config.rakeback_bps,
+        rakeback_claim_epochs: config.rakeback_claim_epochs,
+        xp_unit: config.xp_unit,
+        discount_bps: config.discount_bps,
+        discount_xp_cost: config.discount_xp_cost,
+        staking_contract: config.staking_contract,
+        stake_discounts: config.stake_discounts,
+        fee_tiers: config.fee_tiers,
+        fee_promo: config.fee_promo,
+        min_commission_bps: config.min_commission_bps,
+        promo_pubkey: config.promo_pubkey,
+        bonus_wagering_multiple: config.bonus_wagering_multiple,
+        pair_caps: config.pair_caps,
+        paused: PAUSED.may_load(deps.storage)?.unwrap_or(false),
     })
 }
 
@@ -26,12 +73,15 @@ pub fn query_vault_balance(deps: Deps, address: String) -> StdResult<VaultBalanc
     Ok(VaultBalanceResponse {
         available: balance.available,
         locked: balance.locked,
+        pending_withdrawal: balance.pending_withdrawal,
+        bonus: balance.bonus,
     })
 }
 
 pub fn query_bet(deps: Deps, bet_id: u64) -> StdResult<BetResponse> {
+    let config = CONFIG.load(deps.storage)?;
     let bet = BETS.load(deps.storage, bet_id)?;
-    Ok(bet_to_response(bet))
+    Ok(bet_to_response(bet, &config))
 }
 
 pub fn query_open_bets(
@@ -54,7 +104,7 @@ pub fn query_open_bets(
                 if config.bet_ttl_secs > 0 && now > bet.created_at_time + config.bet_ttl_secs {
                     return None;
                 }
-                Some(bet_to_response(bet))
+                Some(bet_to_response(bet, &config))
             } else {
                 None
             }
@@ -62,7 +112,7 @@ pub fn query_open_bets(
         .take(limit)
         .collect();
 
-    Ok(BetsResponse { bets })
+    Ok(bets_page(bets, limit))
 }
 
 pub fn query_user_bets(
@@ -71,6 +121,7 @@ pub fn query_user_bets(
     start_after: Option<u64>,
     limit: Option<u32>,
 ) -> StdResult<BetsResponse> {
+    let config = CONFIG.load(deps.storage)?;
     let addr = deps.api.addr_validate(&address)?;
     let limit = limit.unwrap_or(20).min(100) as usize;
     let start = start_after.map(|s| s + 1).unwrap_or(0);
@@ -80,7 +131,7 @@ pub fn query_user_bets(
         .filter_map(|item| {
             let (_, bet) = item.ok()?;
             if bet.maker == addr || bet.acceptor.as_ref() == Some(&addr) {
-                Some(bet_to_response(bet))
+                Some(bet_to_response(bet, &config))
             } else {
                 None
             }
@@ -88,21 +139,761 @@ pub fn query_user_bets(
         .take(limit)
         .collect();
 
-    Ok(BetsResponse { bets })
+    Ok(bets_page(bets, limit))
+}
+
+/// A full page may have more results after it; a short page is the last one.
+fn bets_page(bets: Vec<BetResponse>, limit: usize) -> BetsResponse {
+    let next_start_after = if bets.len() == limit {
+        bets.last().map(|b| b.id)
+    } else {
+        None
+    };
+    BetsResponse {
+        bets,
+        next_start_after,
+    }
+}
+
+pub fn query_pending_withdrawals(
+    deps: Deps,
+    address: Option<String>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<PendingWithdrawalsResponse> {
+    let addr = address.map(|a| deps.api.addr_validate(&a)).transpose()?;
+    let limit = limit.unwrap_or(20).min(100) as usize;
+    let start = start_after.map(|s| s + 1).unwrap_or(0);
+
+    let withdrawals: Vec<PendingWithdrawalResponse> = PENDING_WITHDRAWALS
+        .range(deps.storage, Some(cw_storage_plus::Bound::inclusive(start)), None, Order::Ascending)
+        .filter_map(|item| {
+            let (_, w) = item.ok()?;
+            if addr.is_none() || addr.as_ref() == Some(&w.user) {
+                Some(PendingWithdrawalResponse {
+                    id: w.id,
+                    user: w.user,
+                    amount: w.amount,
+                    created_at_time: w.created_at_time,
+                    unlocks_at: w.unlocks_at,
+                    frozen: w.frozen,
+                })
+            } else {
+                None
+            }
+        })
+        .take(limit)
+        .collect();
+
+    Ok(PendingWithdrawalsResponse { withdrawals })
+}
+
+pub fn query_shutdown_status(deps: Deps) -> StdResult<ShutdownStatusResponse> {
+    let state = SHUTDOWN.may_load(deps.storage)?;
+    Ok(match state {
+        Some(s) => ShutdownStatusResponse {
+            active: true,
+            completed: s.completed,
+            started_at_time: Some(s.started_at_time),
+            cursor: s.cursor,
+            last_bet_id: s.last_bet_id,
+            refunded_bets: s.refunded_bets,
+            export_finalized: s.export_finalized,
+        },
+        None => ShutdownStatusResponse {
+            active: false,
+            completed: false,
+            started_at_time: None,
+            cursor: 0,
+            last_bet_id: 0,
+            refunded_bets: 0,
+            export_finalized: false,
+        },
+    })
+}
+
+pub fn query_solvency(deps: Deps, env: Env) -> StdResult<SolvencyResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let totals = VAULT_TOTALS.load(deps.storage)?;
+    let total_owed = totals.total_owed();
+    let contract_balance =
+        crate::execute::admin_sweep::contract_balance(deps, &env, &config)?;
+
+    Ok(SolvencyResponse {
+        total_available: totals.total_available,
+        total_locked: totals.total_locked,
+        total_pending_withdrawal: totals.total_pending_withdrawal,
+        total_accrued_fees: totals.total_accrued_fees,
+        total_jackpot: totals.total_jackpot,
+        total_rakeback: totals.total_rakeback,
+        total_bonus: totals.total_bonus,
+        total_owed,
+        contract_balance,
+        surplus: contract_balance.saturating_sub(total_owed),
+        deficit: total_owed.saturating_sub(contract_balance),
+        solvent: contract_balance >= total_owed,
+    })
+}
+
+pub fn query_player_stats(deps: Deps, address: String) -> StdResult<PlayerStatsResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    let stats = PLAYER_STATS
+        .may_load(deps.storage, &addr)?
+        .unwrap_or_default();
+    let open_bets = USER_OPEN_BET_COUNT
+        .may_load(deps.storage, &addr)?
+        .unwrap_or(0);
+    let balance = query_vault_balance(deps, addr.to_string())?;
+    Ok(PlayerStatsResponse {
+        address: addr,
+        games: stats.games,
+        wins: stats.wins,
+        losses: stats.losses,
+        canceled: stats.canceled,
+        wagered: stats.wagered,
+        won: stats.won,
+        commission_paid: stats.commission_paid,
+        current_streak: stats.current_streak,
+        best_streak: stats.best_streak,
+        last_played: stats.last_played,
+        balance,
+        open_bets,
+    })
+}
+
+pub fn query_daily_stats(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<DailyStatsResponse> {
+    let limit = limit.unwrap_or(30).min(100) as usize;
+    let start = start_after.map(cw_storage_plus::Bound::exclusive);
+
+    let days = DAILY_STATS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (day, stats) = item?;
+            Ok(DailyStatsEntry {
+                day,
+                bets: stats.bets,
+                games: stats.games,
+                canceled: stats.canceled,
+                volume: stats.volume,
+                commission: stats.commission,
+                payout: stats.payout,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(DailyStatsResponse { days })
+}
+
+pub fn query_accrued_fees(deps: Deps) -> StdResult<AccruedFeesResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let fees = ACCRUED_FEES
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (address, amount) = item?;
+            let label = config
+                .commission_split
+                .iter()
+                .find(|share| share.address == address)
+                .map(|share| share.label.clone());
+            Ok(AccruedFee { address, label, amount })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    let total = fees.iter().map(|fee| fee.amount).sum();
+    Ok(AccruedFeesResponse { fees, total })
+}
+
+pub fn query_referral(deps: Deps, address: String) -> StdResult<ReferralResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let tree = REFERRAL_TREES.may_load(deps.storage, &address)?.unwrap_or_default();
+    let earned = REFERRAL_EARNINGS.may_load(deps.storage, &address)?.unwrap_or_default();
+    Ok(ReferralResponse {
+        referrer: REFERRERS.may_load(deps.storage, &address)?,
+        address,
+        tree: tree.to_vec(),
+        tree_size: tree.iter().sum(),
+        earned: earned.to_vec(),
+        total_earned: earned.iter().sum(),
+    })
+}
+
+pub fn query_jackpot_pools(deps: Deps) -> StdResult<JackpotPoolsResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let pools = config
+        .jackpot_tiers
+        .into_iter()
+        .enumerate()
+        .map(|(id, tier)| {
+            let pool = JACKPOT_POOLS.may_load(deps.storage, id as u8)?.unwrap_or_default();
+            Ok(JackpotPoolResponse {
+                tier: id as u8,
+                name: tier.name,
+                bps: tier.bps,
+                target: tier.target,
+                min_games: tier.min_games,
+                cycle: pool.cycle,
+                amount: pool.amount,
+                total_weight: pool.total_weight,
+                seed_committed: pool.commitment.is_some(),
+                pending_draw: pool.filled.map(|fill| JackpotPendingDraw {
+                    cycle: fill.cycle,
+                    amount: fill.amount,
+                    total_weight: fill.total_weight,
+                    filled_at_height: fill.height,
+                    draw_from_height: fill.draw_from_height,
+                    reveal_deadline: fill.reveal_deadline,
+                }),
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(JackpotPoolsResponse { pools })
+}
+
+pub fn query_jackpot_draws(
+    deps: Deps,
+    tier: u8,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<JackpotDrawsResponse> {
+    let limit = limit.unwrap_or(30).min(100) as usize;
+    let start = start_after.map(cw_storage_plus::Bound::exclusive);
+
+    let draws = JACKPOT_DRAWS
+        .prefix(tier)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (cycle, draw) = item?;
+            Ok(JackpotDrawResponse {
+                tier,
+                cycle,
+                winner: draw.winner,
+                amount: draw.amount,
+                winner_weight: draw.winner_weight,
+                total_weight: draw.total_weight,
+                seed: draw.seed,
+                entropy: draw.entropy,
+                drawn_at: draw.drawn_at,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(JackpotDrawsResponse { draws })
+}
+
+pub fn query_rakeback_epoch(
+    deps: Deps,
+    env: Env,
+    epoch: Option<u64>,
+) -> StdResult<RakebackEpochResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let current_epoch = epoch_of(env.block.time.seconds());
+    let epoch = epoch.unwrap_or(current_epoch);
+    let reserve = RAKEBACK_EPOCHS.may_load(deps.storage, epoch)?.unwrap_or_default();
+    Ok(RakebackEpochResponse {
+        epoch,
+        current_epoch,
+        generated: reserve.generated,
+        reserved: reserve.reserved,
+        claimed: reserve.claimed,
+        claimable_until: epoch + config.rakeback_claim_epochs,
+        swept: reserve.swept,
+    })
+}
+
+pub fn query_player_rakeback(
+    deps: Deps,
+    env: Env,
+    address: String,
+    epoch: Option<u64>,
+) -> StdResult<PlayerRakebackResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let address = deps.api.addr_validate(&address)?;
+    let current_epoch = epoch_of(env.block.time.seconds());
+    let epoch = epoch.unwrap_or(current_epoch);
+    let entry = RAKEBACK.may_load(deps.storage, (epoch, &address))?.unwrap_or_default();
+    let claimable = !entry.claimed
+        && !entry.rebate.is_zero()
+        && epoch < current_epoch
+        && current_epoch <= epoch + config.rakeback_claim_epochs;
+    Ok(PlayerRakebackResponse {
+        address,
+        epoch,
+        generated: entry.generated,
+        rebate: entry.rebate,
+        claimed: entry.claimed,
+        claimable,
+    })
+}
+
+pub fn query_loyalty(deps: Deps, address: String) -> StdResult<LoyaltyResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let loyalty = LOYALTY.may_load(deps.storage, &address)?.unwrap_or_default();
+    let level = level_of(loyalty.xp);
+    Ok(LoyaltyResponse {
+        address,
+        xp: loyalty.xp,
+        xp_spent: loyalty.xp_spent,
+        xp_balance: loyalty.xp - loyalty.xp_spent,
+        level,
+        next_level_xp: level_xp(level + 1),
+        discount_bets: loyalty.discount_bets,
+        discount_bps: loyalty.discount_bps,
+    })
+}
+
+pub fn query_effective_commission(
+    deps: Deps,
+    address: String,
+) -> StdResult<EffectiveCommissionResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let address = deps.api.addr_validate(&address)?;
+    let (fee_discount_bps, staked, stake_discount_bps) = player_discounts(deps, &config, &address)?;
+    let effective_bps = floor_bps(
+        &config,
+        config.commission_bps,
+        config
+            .commission_bps
+            .saturating_sub(fee_discount_bps)
+            .saturating_sub(stake_discount_bps),
+    );
+    Ok(EffectiveCommissionResponse {
+        address,
+        commission_bps: config.commission_bps,
+        fee_discount_bps,
+        staked,
+        stake_discount_bps,
+        effective_bps,
+    })
+}
+
+pub fn query_quote_fee(
+    deps: Deps,
+    env: Env,
+    amount: Uint128,
+    player: Option<String>,
+) -> StdResult<QuoteFeeResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let now = env.block.time.seconds();
+    let schedule_bps = schedule_bps(&config, amount, now);
+    let (fee_discount_bps, _, stake_discount_bps) = match player {
+        Some(player) => player_discounts(deps, &config, &deps.api.addr_validate(&player)?)?,
+        None => (0, Uint128::zero(), 0),
+    };
+    let effective_bps = floor_bps(
+        &config,
+        schedule_bps,
+        schedule_bps
+            .saturating_sub(fee_discount_bps)
+            .saturating_sub(stake_discount_bps),
+    );
+    Ok(QuoteFeeResponse {
+        amount,
+        schedule_bps,
+        promo_active: config.fee_promo.as_ref().is_some_and(|promo| promo.is_active(now)),
+        fee_discount_bps,
+        stake_discount_bps,
+        effective_bps,
+        commission: (amount + amount).multiply_ratio(effective_bps, 10_000u128),
+    })
+}
+
+pub fn query_promo_voucher(
+    deps: Deps,
+    id: String,
+    player: Option<String>,
+) -> StdResult<PromoVoucherResponse> {
+    let redemptions = PROMO_REDEMPTIONS.may_load(deps.storage, &id)?.unwrap_or(0);
+    let redeemed_on = match player {
+        Some(player) => {
+            let player = deps.api.addr_validate(&player)?;
+            PROMO_REDEEMERS.may_load(deps.storage, (&id, &player))?
+        }
+        None => None,
+    };
+    Ok(PromoVoucherResponse {
+        id,
+        redemptions,
+        redeemed_on,
+    })
+}
+
+pub fn query_player_limits(
+    deps: Deps,
+    env: Env,
+    address: String,
+) -> StdResult<PlayerLimitsResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    let now = env.block.time.seconds();
+    let limits = effective_limits(deps.storage, &addr, now)?;
+    let pending = PENDING_LIMITS
+        .may_load(deps.storage, &addr)?
+        .filter(|pending| pending.effective_at > now);
+    let daily_loss = DAILY_LOSS
+        .may_load(deps.storage, (&addr, now / DAY_SECS))?
+        .unwrap_or_default()
+        .net();
+    let locked = VAULT_BALANCES.may_load(deps.storage, &addr)?.unwrap_or_default().locked;
+    let daily_loss_remaining = limits
+        .daily_loss_limit
+        .map(|limit| limit.saturating_sub(daily_loss + locked));
+    Ok(PlayerLimitsResponse {
+        address: addr,
+        limits,
+        pending,
+        daily_loss,
+        daily_loss_remaining,
+    })
+}
+
+pub fn query_pair_exposure(
+    deps: Deps,
+    env: Env,
+    address: String,
+    day: Option<u64>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<PairExposureResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    let day = day.unwrap_or(env.block.time.seconds() / DAY_SECS);
+    let start = start_after.map(|a| deps.api.addr_validate(&a)).transpose()?;
+    let limit = limit.unwrap_or(50).min(200) as usize;
+
+    let pairs = PAIR_STATS
+        .prefix((day, &addr))
+        .range(
+            deps.storage,
+            start.as_ref().map(cw_storage_plus::Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| {
+            let (opponent, stats) = item?;
+            Ok(PairExposureEntry {
+                opponent,
+                games: stats.games,
+                volume: stats.volume,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let next_start_after = if pairs.len() == limit {
+        pairs.last().map(|p| p.opponent.to_string())
+    } else {
+        None
+    };
+    Ok(PairExposureResponse {
+        address: addr,
+        day,
+        caps: CONFIG.load(deps.storage)?.pair_caps,
+        pairs,
+        next_start_after,
+    })
+}
+
+pub fn query_bonus(deps: Deps, address: String) -> StdResult<BonusResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    let balance = VAULT_BALANCES.may_load(deps.storage, &addr)?.unwrap_or_default();
+    let wagering = BONUS_WAGERING.may_load(deps.storage, &addr)?.unwrap_or_default();
+    Ok(BonusResponse {
+        address: addr,
+        bonus: balance.bonus,
+        bonus_locked: wagering.locked,
+        wagering_required: wagering.required,
+        wagered: wagering.wagered,
+        wagering_remaining: wagering.required.saturating_sub(wagering.wagered),
+    })
+}
+
+/// A player's outstanding fee discount, LAUNCH stake and stake discount.
+fn player_discounts(deps: Deps, config: &Config, player: &Addr) -> StdResult<(u16, Uint128, u16)> {
+    let loyalty = LOYALTY.may_load(deps.storage, player)?.unwrap_or_default();
+    let fee_discount_bps = if loyalty.discount_bets > 0 {
+        loyalty.discount_bps
+    } else {
+        0
+    };
+    let (staked, stake_discount_bps) = stake_discount(deps.querier, config, player);
+    Ok((fee_discount_bps, staked, stake_discount_bps))
+}
+
+pub fn query_hooks(deps: Deps) -> StdResult<HooksResponse> {
+    let hooks = HOOKS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (contract, gas_limit) = item?;
+            Ok(HookResponse { contract, gas_limit })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(HooksResponse { hooks })
+}
+
+pub fn query_global_stats(deps: Deps) -> StdResult<GlobalStatsResponse> {
+    let stats = STATS_SERIES.may_load(deps.storage, ("all", 0))?.unwrap_or_default();
+    Ok(GlobalStatsResponse {
+        bets_created: stats.bets_created,
+        bets_resolved: stats.bets_resolved,
+        bets_canceled: stats.bets_canceled,
+        volume: stats.volume,
+        commission: stats.commission,
+        unique_players: stats.unique_players,
+    })
+}
+
+pub fn query_stats_series(
+    deps: Deps,
+    granularity: StatsGranularity,
+    from: u64,
+    to: u64,
+) -> StdResult<StatsSeriesResponse> {
+    const LIMIT: usize = 200;
+    let (key, secs) = match granularity {
+        StatsGranularity::Hour => ("hour", HOUR_SECS),
+        StatsGranularity::Day => ("day", DAY_SECS),
+    };
+
+    let buckets = STATS_SERIES
+        .prefix(key)
+        .range(
+            deps.storage,
+            Some(cw_storage_plus::Bound::inclusive(from / secs)),
+            Some(cw_storage_plus::Bound::inclusive(to / secs)),
+            Order::Ascending,
+        )
+        .take(LIMIT)
+        .map(|item| {
+            let (bucket, stats) = item?;
+            Ok(StatsBucket {
+                start: bucket * secs,
+                bets_created: stats.bets_created,
+                bets_resolved: stats.bets_resolved,
+                bets_canceled: stats.bets_canceled,
+                volume: stats.volume,
+                commission: stats.commission,
+                unique_players: stats.unique_players,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let next_from = if buckets.len() == LIMIT {
+        buckets.last().map(|b| b.start + secs)
+    } else {
+        None
+    };
+
+    Ok(StatsSeriesResponse {
+        granularity,
+        buckets,
+        next_from,
+    })
+}
+
+pub fn query_leaderboard(
+    deps: Deps,
+    env: Env,
+    metric: LeaderboardMetric,
+    period: LeaderboardPeriod,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<LeaderboardResponse> {
+    let limit = limit.unwrap_or(30).min(100) as usize;
+    let scope = leaderboard::scope(&period, env.block.time.seconds());
+    let board = leaderboard::board(&metric, scope);
+
+    // Resume below the previous page's last entry, at its current score
+    let start_after = start_after.map(|a| deps.api.addr_validate(&a)).transpose()?;
+    let end = match &start_after {
+        Some(addr) => {
+            let stats = leaderboard::load_stats(deps.storage, scope, addr)?;
+            Some((leaderboard::score(&metric, &stats)?, addr))
+        }
+        None => None,
+    };
+
+    let entries = LEADERBOARD
+        .sub_prefix(&board)
+        .keys(
+            deps.storage,
+            None,
+            end.map(cw_storage_plus::Bound::exclusive),
+            Order::Descending,
+        )
+        .take(limit)
+        .map(|item| {
+            let (score, address) = item?;
+            let stats = leaderboard::load_stats(deps.storage, scope, &address)?;
+            Ok(LeaderboardEntry {
+                address,
+                score: Int128::new(score),
+                games: stats.games,
+                wins: stats.wins,
+                wagered: stats.wagered,
+                won: stats.won,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let next_start_after = if entries.len() == limit {
+        entries.last().map(|e| e.address.to_string())
+    } else {
+        None
+    };
+    let period = match scope {
+        None => LeaderboardPeriod::AllTime,
+        Some(("day", day)) => LeaderboardPeriod::Day { day: Some(day) },
+        Some((_, week)) => LeaderboardPeriod::Week { week: Some(week) },
+    };
+
+    Ok(LeaderboardResponse {
+        metric,
+        period,
+        entries,
+        next_start_after,
+    })
+}
+
+pub fn query_migration_status(deps: Deps) -> StdResult<MigrationStatusResponse> {
+    let progress = RESET_PROGRESS.may_load(deps.storage)?;
+    let backfill = TOTALS_BACKFILL.may_load(deps.storage)?;
+    Ok(MigrationStatusResponse {
+        in_progress: progress.is_some() || backfill.is_some(),
+        cleared_entries: progress.as_ref().map_or(0, |p| p.cleared_entries),
+        phase: progress.map(|p| p.phase),
+        counted_balances: backfill.map(|b| b.counted_balances),
+    })
+}
+
+pub fn query_export_state(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<ExportStateResponse> {
+    let start = start_after.map(|a| deps.api.addr_validate(&a)).transpose()?;
+    let limit = limit.unwrap_or(50).min(200) as usize;
+
+    let entries: Vec<BalanceEntry> = VAULT_BALANCES
+        .range(
+            deps.storage,
+            start.as_ref().map(cw_storage_plus::Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .filter_map(|item| {
+            let (addr, balance) = item.ok()?;
+            let amount = total_owed(&balance);
+            if amount.is_zero() {
+                return None;
+            }
+            Some(BalanceEntry {
+                address: addr.to_string(),
+                amount,
+            })
+        })
+        .take(limit)
+        .collect();
+
+    let page_total = entries.iter().map(|e| e.amount).sum();
+    let next_start_after = if entries.len() == limit {
+        entries.last().map(|e| e.address.clone())
+    } else {
+        None
+    };
+
+    Ok(ExportStateResponse {
+        checksum: balance_entries_checksum(&entries),
+        entries,
+        page_total,
+        next_start_after,
+    })
+}
+
+pub fn query_export_summary(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<ExportSummaryResponse> {
+    let start = start_after.map(|a| deps.api.addr_validate(&a)).transpose()?;
+    let limit = limit.unwrap_or(100).min(500) as usize;
+
+    let balances = VAULT_BALANCES
+        .range(
+            deps.storage,
+            start.as_ref().map(cw_storage_plus::Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut total_entries: u64 = 0;
+    let mut total_amount = Uint128::zero();
+    for (_, balance) in &balances {
+        let amount = total_owed(balance);
+        if !amount.is_zero() {
+            total_entries += 1;
+            total_amount += amount;
+        }
+    }
+    let next_start_after = if balances.len() == limit {
+        balances.last().map(|(addr, _)| addr.to_string())
+    } else {
+        None
+    };
+    Ok(ExportSummaryResponse {
+        total_entries,
+        total_amount,
+        next_start_after,
+    })
+}
+
+pub fn query_import_status(deps: Deps, source_contract: String) -> StdResult<ImportStatusResponse> {
+    let source = deps.api.addr_validate(&source_contract)?;
+    let import = IMPORT_SOURCES.load(deps.storage, &source)?;
+    Ok(ImportStatusResponse {
+        source_contract: source,
+        rate_num: import.rate_num,
+        rate_denom: import.rate_denom,
+        imported_entries: import.imported_entries,
+        total_source_amount: import.total_source_amount,
+        total_credited: import.total_credited,
+    })
+}
+
+/// Everything the vault owes a user: available + locked + pending withdrawal. The
+/// bonus is not withdrawable, so it is not exported.
+fn total_owed(balance: &VaultBalance) -> Uint128 {
+    balance.available + balance.locked + balance.pending_withdrawal
 }
 
-fn bet_to_response(bet: crate::state::Bet) -> BetResponse {
+fn bet_to_response(bet: crate::state::Bet, config: &Config) -> BetResponse {
+    let expires_at = if config.bet_ttl_secs > 0 {
+        Some(bet.created_at_time + config.bet_ttl_secs)
+    } else {
+        None
+    };
     BetResponse {
         id: bet.id,
         maker: bet.maker,
         amount: bet.amount,
         commitment: bet.commitment,
-        status: format!("{:?}", bet.status).to_lowercase(),
+        status: bet.status,
         acceptor: bet.acceptor,
         acceptor_guess: bet.acceptor_guess,
+        created_at_height: bet.created_at_height,
         created_at_time: bet.created_at_time,
+        accepted_at_height: bet.accepted_at_height,
         accepted_at_time: bet.accepted_at_time,
+        resolved_at_height: bet.resolved_at_height,
+        expires_at,
+        reveal_deadline: bet
+            .accepted_at_time
+            .map(|t| t + config.reveal_timeout_secs),
         reveal_side: bet.reveal_side,
+        reveal_secret: bet.reveal_secret,
         winner: bet.payout_winner,
         payout_amount: if bet.payout_amount.is_zero() {
             None
@@ -114,5 +905,9 @@ fn bet_to_response(bet: crate::state::Bet) -> BetResponse {
         } else {
             Some(bet.commission_paid)
         },
+        commission_bps: bet.commission_bps,
+        maker_bonus: bet.maker_bonus,
+        acceptor_bonus: bet.acceptor_bonus,
+        non_qualifying: bet.non_qualifying,
     }
 }
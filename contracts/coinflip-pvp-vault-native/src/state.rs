@@ -1,13 +1,33 @@
-use cosmwasm_std::{Addr, Binary, Uint128};
+use cosmwasm_std::{Addr, Binary, Event, StdResult, Storage, Uint128};
 use cw_storage_plus::{Item, Map};
 use cosmwasm_schema::cw_serde;
 
+use crate::events;
+
 /// Default bet TTL: 3 hours = 10800 seconds.
 /// Used by serde when loading old Config from storage that lacks this field.
 pub fn default_bet_ttl_secs() -> u64 {
     10800
 }
 
+/// Default pending-withdrawal delay: 24 hours = 86400 seconds.
+/// Used by serde when loading old Config from storage that lacks this field.
+pub fn default_withdraw_delay_secs() -> u64 {
+    86400
+}
+
+/// Default rakeback claim window: 4 epochs after the epoch ends.
+/// Used by serde when loading old Config from storage that lacks this field.
+pub fn default_rakeback_claim_epochs() -> u64 {
+    4
+}
+
+/// Default retention for resolved bets: 30 days = 2592000 seconds.
+/// Used by serde when loading old Config from storage that lacks this field.
+pub fn default_bet_retention_secs() -> u64 {
+    2_592_000
+}
+
 #[cw_serde]
 pub struct Config {
     pub admin: Addr,
@@ -22,12 +42,160 @@ pub struct Config {
     /// 0 = no expiration. Default: 10800 (3 hours).
     #[serde(default = "default_bet_ttl_secs")]
     pub bet_ttl_secs: u64,
+    /// Withdrawals that take a user's instant withdrawals within `withdraw_delay_secs` above
+    /// this amount become pending and unlock after `withdraw_delay_secs`.
+    /// 0 = every withdrawal is paid out instantly.
+    #[serde(default)]
+    pub withdraw_delay_threshold: Uint128,
+    /// How long a pending withdrawal waits before it can be claimed (seconds).
+    #[serde(default = "default_withdraw_delay_secs")]
+    pub withdraw_delay_secs: u64,
+    /// Optional guardian allowed to freeze pending withdrawals during the delay window.
+    #[serde(default)]
+    pub guardian: Option<Addr>,
+    /// Resolved bets created longer ago than this can be removed by `PruneBets` (seconds).
+    #[serde(default = "default_bet_retention_secs")]
+    pub bet_retention_secs: u64,
+    /// How commission is split between fee recipients. Empty = all of it is credited
+    /// to `treasury`'s vault balance.
+    #[serde(default)]
+    pub commission_split: Vec<CommissionShare>,
+    /// Referral rewards in bps of the pot for referral levels 1..=3, paid out of the
+    /// commission to the winner's referrers. Empty = no referral rewards.
+    #[serde(default)]
+    pub referral_bps: Vec<u16>,
+    /// Jackpot tiers funded from every resolved pot out of the commission, indexed by tier id
+    #[serde(default)]
+    pub jackpot_tiers: Vec<JackpotTier>,
+    /// Share of the commission of each bet reserved as rakeback for its players, in bps.
+    /// 0 = no rakeback.
+    #[serde(default)]
+    pub rakeback_bps: u16,
+    /// Epochs after an epoch ends during which its rakeback can be claimed
+    #[serde(default = "default_rakeback_claim_epochs")]
+    pub rakeback_claim_epochs: u64,
+    /// Wagered amount that earns one loyalty XP. 0 = no XP accrues.
+    #[serde(default)]
+    pub xp_unit: Uint128,
+    /// Commission bps taken off each bet won with a fee discount
+    #[serde(default)]
+    pub discount_bps: u16,
+    /// XP spent per discounted bet. 0 = fee discounts cannot be redeemed.
+    #[serde(default)]
+    pub discount_xp_cost: u64,
+    /// launch-staking contract whose stakers get `stake_discounts`
+    #[serde(default)]
+    pub staking_contract: Option<Addr>,
+    /// Commission discounts by the winner's LAUNCH stake, ascending by `min_stake`.
+    /// Empty = no stake discounts.
+    #[serde(default)]
+    pub stake_discounts: Vec<StakeDiscount>,
+    /// Commission by bet size, ascending by `min_amount`; the highest reached tier
+    /// replaces `commission_bps`. Empty = `commission_bps` for every bet.
+    #[serde(default)]
+    pub fee_tiers: Vec<FeeTier>,
+    /// Promotional rate replacing the size tiers while it runs
+    #[serde(default)]
+    pub fee_promo: Option<FeePromo>,
+    /// Lowest commission rate fee and stake discounts can bring a bet down to
+    #[serde(default)]
+    pub min_commission_bps: u16,
+    /// secp256k1 public key (33 or 65 bytes) that signs promo vouchers.
+    /// `None` = vouchers are rejected.
+    #[serde(default)]
+    pub promo_pubkey: Option<Binary>,
+    /// Multiple of each credited bonus that must be wagered before the bonus converts
+    /// to `available`. 0 = bonus converts right away.
+    #[serde(default)]
+    pub bonus_wagering_multiple: u32,
+    /// Daily caps on play between the same two players. `None` = no caps.
+    #[serde(default)]
+    pub pair_caps: Option<PairCaps>,
+}
+
+/// One recipient of the commission split. Shares accrue at resolution and are paid
+/// out by `FlushFees`.
+#[cw_serde]
+pub struct CommissionShare {
+    /// Name shown in events and queries, e.g. "treasury", "staking", "jackpot"
+    pub label: String,
+    pub address: Addr,
+    /// Share of each commission in bps; all shares add up to 10000
+    pub bps: u16,
+    /// Pay out through `Distribute {}` on a launch-staking contract instead of a transfer
+    pub distribute: bool,
+}
+
+/// Caps on the games one pair of players plays against each other per day
+/// (block time / 86400), in either role.
+#[cw_serde]
+pub struct PairCaps {
+    /// Games per day; 0 = no cap
+    pub max_games: u32,
+    /// Sum of both stakes over the day's games; 0 = no cap
+    pub max_volume: Uint128,
+    pub mode: PairCapMode,
+}
+
+#[cw_serde]
+pub enum PairCapMode {
+    /// Bets over a cap cannot be accepted
+    Reject,
+    /// Bets over a cap are played but marked non-qualifying: they are left out of
+    /// stats, leaderboards, loyalty XP, referral rewards, jackpots, rakeback and hooks
+    NonQualifying,
+}
+
+/// A jackpot tier: its pool is drawn once it reaches `target`.
+#[cw_serde]
+pub struct JackpotTier {
+    pub name: String,
+    /// Contribution of each resolved pot, in bps of the pot
+    pub bps: u16,
+    pub target: Uint128,
+    /// Finished games a player needs before their stakes count toward the draw
+    pub min_games: u64,
+}
+
+/// Commission rate of bets of at least `min_amount` per side.
+#[cw_serde]
+pub struct FeeTier {
+    pub min_amount: Uint128,
+    pub bps: u16,
+}
+
+/// Commission rate of bets resolved from `start` until before `end` (block time).
+#[cw_serde]
+pub struct FeePromo {
+    pub start: u64,
+    pub end: u64,
+    pub bps: u16,
+}
+
+impl FeePromo {
+    pub fn is_active(&self, now: u64) -> bool {
+        (self.start..self.end).contains(&now)
+    }
+}
+
+/// Commission bps taken off pots won by players with at least `min_stake` LAUNCH staked.
+#[cw_serde]
+pub struct StakeDiscount {
+    pub min_stake: Uint128,
+    pub bps: u16,
 }
 
 #[cw_serde]
 pub struct VaultBalance {
     pub available: Uint128,
     pub locked: Uint128,
+    /// Funds reserved by delayed withdrawals that have not been claimed or canceled yet.
+    #[serde(default)]
+    pub pending_withdrawal: Uint128,
+    /// Promotional credit that can be staked but not withdrawn until its wagering
+    /// requirement is met. Bonus staked in bets is counted in `locked`.
+    #[serde(default)]
+    pub bonus: Uint128,
 }
 
 impl Default for VaultBalance {
@@ -35,10 +203,47 @@ impl Default for VaultBalance {
         Self {
             available: Uint128::zero(),
             locked: Uint128::zero(),
+            pending_withdrawal: Uint128::zero(),
+            bonus: Uint128::zero(),
         }
     }
 }
 
+/// Running sums over every `VaultBalance`, maintained by `save_balance`.
+#[cw_serde]
+#[derive(Default)]
+pub struct VaultTotals {
+    pub total_available: Uint128,
+    pub total_locked: Uint128,
+    pub total_pending_withdrawal: Uint128,
+    /// Commission shares accrued in `ACCRUED_FEES` and not flushed yet
+    #[serde(default)]
+    pub total_accrued_fees: Uint128,
+    /// Sum of the jackpot pools waiting for their draw
+    #[serde(default)]
+    pub total_jackpot: Uint128,
+    /// Rakeback reserved and neither claimed nor swept yet
+    #[serde(default)]
+    pub total_rakeback: Uint128,
+    /// Bonus balances not staked in a bet; staked bonus is in `total_locked`
+    #[serde(default)]
+    pub total_bonus: Uint128,
+}
+
+impl VaultTotals {
+    /// Everything the contract owes to users, fee recipients, jackpot winners and
+    /// rakeback claims, bonus included.
+    pub fn total_owed(&self) -> Uint128 {
+        self.total_available
+            + self.total_locked
+            + self.total_pending_withdrawal
+            + self.total_accrued_fees
+            + self.total_jackpot
+            + self.total_rakeback
+            + self.total_bonus
+    }
+}
+
 #[cw_serde]
 pub enum BetStatus {
     Open,
@@ -46,6 +251,8 @@ pub enum BetStatus {
     Revealed,
     Canceled,
     TimeoutClaimed,
+    /// Stake returned to the players by emergency shutdown
+    Refunded,
 }
 
 #[cw_serde]
@@ -71,6 +278,383 @@ pub struct Bet {
     pub payout_winner: Option<Addr>,
     pub commission_paid: Uint128,
     pub payout_amount: Uint128,
+    /// Commission rate applied at resolution, after fee and stake discounts.
+    /// `None` until resolved, and for bets resolved before it was recorded.
+    #[serde(default)]
+    pub commission_bps: Option<u16>,
+    /// Commission discounts from promo vouchers redeemed on this bet
+    #[serde(default)]
+    pub promo_discounts: Vec<PromoDiscount>,
+    /// Parts of the maker's and the acceptor's stake taken from their bonus balance
+    #[serde(default)]
+    pub maker_bonus: Uint128,
+    #[serde(default)]
+    pub acceptor_bonus: Uint128,
+    /// Accepted over a pair cap in `NonQualifying` mode
+    #[serde(default)]
+    pub non_qualifying: bool,
+    /// Set once the bet is counted in PLAYER_STATS. Bets resolved before live stats
+    /// existed are counted when pruned.
+    #[serde(default)]
+    pub stats_recorded: bool,
+}
+
+/// A promo voucher's commission discount, applied if `player` wins the bet.
+#[cw_serde]
+pub struct PromoDiscount {
+    pub player: Addr,
+    pub voucher_id: String,
+    pub bps: u16,
+}
+
+#[cw_serde]
+pub struct PendingWithdrawal {
+    pub id: u64,
+    pub user: Addr,
+    pub amount: Uint128,
+    pub created_at_time: u64,
+    /// Earliest block time at which the withdrawal can be claimed.
+    pub unlocks_at: u64,
+    /// Set by the guardian (or admin) — a frozen withdrawal cannot be claimed or canceled.
+    pub frozen: bool,
+}
+
+/// A withdrawal paid out instantly, kept until `withdraw_delay_secs` have passed.
+#[cw_serde]
+pub struct RecentWithdrawal {
+    pub amount: Uint128,
+    pub at: u64,
+}
+
+#[cw_serde]
+pub struct ShutdownState {
+    /// Block time at which the admin triggered the shutdown.
+    pub started_at_time: u64,
+    /// Last bet id already processed by `ProcessShutdown` (0 = none yet).
+    pub cursor: u64,
+    /// Highest bet id that existed when the shutdown was triggered.
+    pub last_bet_id: u64,
+    pub refunded_bets: u64,
+    /// True once every live bet has been refunded.
+    pub completed: bool,
+    /// True once the admin froze the balances for export (`FinalizeExport`).
+    #[serde(default)]
+    pub export_finalized: bool,
+}
+
+#[cw_serde]
+pub struct ImportSource {
+    pub rate_num: u64,
+    pub rate_denom: u64,
+    pub imported_entries: u64,
+    pub total_source_amount: Uint128,
+    pub total_credited: Uint128,
+}
+
+/// Totals over a player's finished bets.
+#[cw_serde]
+#[derive(Default)]
+pub struct PlayerStats {
+    /// Accepted bets that were played to the end (revealed or timeout-claimed)
+    pub games: u64,
+    pub wins: u64,
+    pub losses: u64,
+    /// Bets canceled or refunded before they were played
+    pub canceled: u64,
+    /// Sum of the player's stakes in played games
+    pub wagered: Uint128,
+    /// Sum of payouts received as the winner
+    pub won: Uint128,
+    /// Commission taken from the pots this player won
+    #[serde(default)]
+    pub commission_paid: Uint128,
+    /// Consecutive wins up to the last played game
+    #[serde(default)]
+    pub current_streak: u32,
+    #[serde(default)]
+    pub best_streak: u32,
+    /// Block time of the last played game (0 = never)
+    #[serde(default)]
+    pub last_played: u64,
+}
+
+/// A player's played games within one leaderboard period bucket.
+#[cw_serde]
+#[derive(Default)]
+pub struct PeriodStats {
+    pub games: u64,
+    pub wins: u64,
+    pub wagered: Uint128,
+    pub won: Uint128,
+}
+
+impl From<&PlayerStats> for PeriodStats {
+    fn from(stats: &PlayerStats) -> Self {
+        PeriodStats {
+            games: stats.games,
+            wins: stats.wins,
+            wagered: stats.wagered,
+            won: stats.won,
+        }
+    }
+}
+
+/// The current cycle of a jackpot tier's pool.
+#[cw_serde]
+#[derive(Default)]
+pub struct JackpotPool {
+    /// Draw round taking contributions and entries; entries and draws are kept per cycle
+    pub cycle: u64,
+    pub amount: Uint128,
+    /// Sum of the eligible stakes this cycle; the winner is drawn weighted by stake
+    pub total_weight: Uint128,
+    /// Stakes entered this cycle, i.e. the next index in `JACKPOT_RANGES`
+    #[serde(default)]
+    pub entries: u32,
+    /// sha256 of the seed the admin reveals with `DrawJackpot`. The cycle is only
+    /// funded once it is set.
+    pub commitment: Option<Binary>,
+    /// An earlier cycle that reached its target and waits for its draw
+    pub filled: Option<JackpotFill>,
+}
+
+/// A cycle frozen when it reached its target: later stakes enter the next cycle.
+#[cw_serde]
+pub struct JackpotFill {
+    pub cycle: u64,
+    pub amount: Uint128,
+    pub total_weight: Uint128,
+    pub entries: u32,
+    pub commitment: Binary,
+    pub height: u64,
+    /// Block and bet data of the resolution that filled the pool and of every
+    /// resolution before `draw_from_height`, mixed with the seed
+    pub entropy: Binary,
+    /// First height at which the seed can be revealed; the entropy is final by then
+    pub draw_from_height: u64,
+    /// After this time the draw is void and anyone can roll the amount into the next cycle
+    pub reveal_deadline: u64,
+}
+
+/// One eligible stake of a jackpot cycle: it covers the weights `[end - stake, end)`.
+#[cw_serde]
+pub struct JackpotRange {
+    pub player: Addr,
+    pub end: Uint128,
+}
+
+/// A finished jackpot draw.
+#[cw_serde]
+pub struct JackpotDraw {
+    pub winner: Addr,
+    pub amount: Uint128,
+    /// The winner's eligible stakes and the cycle's total, i.e. their odds
+    pub winner_weight: Uint128,
+    pub total_weight: Uint128,
+    /// Revealed seed; empty for a fallback draw
+    pub seed: Binary,
+    pub entropy: Binary,
+    pub drawn_at: u64,
+}
+
+/// Rakeback reserve of one epoch.
+#[cw_serde]
+#[derive(Default)]
+pub struct RakebackEpoch {
+    /// Commission of the bets resolved in the epoch
+    pub generated: Uint128,
+    pub reserved: Uint128,
+    pub claimed: Uint128,
+    /// Set once the unclaimed rest went back to the treasury
+    pub swept: bool,
+}
+
+/// A player's rakeback for one epoch.
+#[cw_serde]
+#[derive(Default)]
+pub struct RakebackEntry {
+    /// The player's half of the commission of their bets
+    pub generated: Uint128,
+    pub rebate: Uint128,
+    pub claimed: bool,
+}
+
+/// A player's loyalty XP and redeemed fee discounts.
+#[cw_serde]
+#[derive(Default)]
+pub struct Loyalty {
+    /// XP earned in total; the level is derived from it
+    pub xp: u64,
+    pub xp_spent: u64,
+    /// Wagered amount not yet worth a whole XP
+    pub carry: Uint128,
+    /// Bets left to win with `discount_bps` taken off the commission
+    pub discount_bets: u32,
+    pub discount_bps: u16,
+}
+
+/// Limits a player has set on their own play. `None` = no limit.
+#[cw_serde]
+#[derive(Default)]
+pub struct PlayerLimits {
+    /// Net loss per day (block time / 86400), counting stakes in live bets as lost
+    pub daily_loss_limit: Option<Uint128>,
+    /// Largest stake the player can bet
+    pub max_bet: Option<Uint128>,
+    /// No bets of the player can be created or accepted before this block time
+    pub cooldown_until: Option<u64>,
+}
+
+/// Limits that loosen a player's current ones, applied once `effective_at` is reached.
+#[cw_serde]
+pub struct PendingLimits {
+    pub limits: PlayerLimits,
+    pub effective_at: u64,
+}
+
+/// Games played by a pair of players over one day.
+#[cw_serde]
+#[derive(Default)]
+pub struct PairStats {
+    pub games: u32,
+    /// Sum of both stakes
+    pub volume: Uint128,
+}
+
+/// A player's results over one day, for their daily loss limit.
+#[cw_serde]
+#[derive(Default)]
+pub struct DailyLoss {
+    /// Stakes of the bets lost
+    pub lost: Uint128,
+    /// Payouts of the bets won, less their stakes
+    pub won: Uint128,
+}
+
+impl DailyLoss {
+    pub fn net(&self) -> Uint128 {
+        self.lost.saturating_sub(self.won)
+    }
+}
+
+/// A player's progress toward converting their bonus balance to `available`.
+#[cw_serde]
+#[derive(Default)]
+pub struct BonusWagering {
+    /// Stakes to play through, from the multiple of each bonus credited
+    pub required: Uint128,
+    /// Stakes of played bets since the requirement started
+    pub wagered: Uint128,
+    /// Bonus staked in bets that are still live
+    pub locked: Uint128,
+}
+
+/// Global bet activity within one series bucket.
+#[cw_serde]
+#[derive(Default)]
+pub struct ActivityStats {
+    pub bets_created: u64,
+    /// Bets played to the end (revealed or timeout-claimed)
+    pub bets_resolved: u64,
+    /// Bets canceled or refunded before they were played
+    pub bets_canceled: u64,
+    /// Sum of both stakes in resolved bets
+    pub volume: Uint128,
+    pub commission: Uint128,
+    /// Distinct addresses that created or accepted a bet
+    pub unique_players: u64,
+}
+
+/// Totals over pruned bets created on one day. Non-qualifying bets are left out.
+#[cw_serde]
+#[derive(Default)]
+pub struct DailyStats {
+    pub bets: u64,
+    pub games: u64,
+    pub canceled: u64,
+    /// Sum of both stakes in played games
+    pub volume: Uint128,
+    pub commission: Uint128,
+    pub payout: Uint128,
+}
+
+/// Maps wiped by a state reset, in the order they are cleared.
+#[cw_serde]
+pub enum ResetPhase {
+    VaultBalances,
+    Bets,
+    OpenBetCounts,
+    DailyUsage,
+    DailyLoss,
+    PendingWithdrawals,
+    RecentWithdrawals,
+    PlayerStats,
+    DailyStats,
+    PeriodStats,
+    Leaderboard,
+    StatsSeries,
+    SeriesPlayers,
+    AccruedFees,
+    ReferralEarnings,
+    JackpotPools,
+    JackpotEntries,
+    JackpotRanges,
+    JackpotDraws,
+    RakebackEpochs,
+    RakebackEntries,
+    Loyalty,
+    BonusWagering,
+    PairStats,
+}
+
+impl ResetPhase {
+    pub fn next(&self) -> Option<ResetPhase> {
+        match self {
+            ResetPhase::VaultBalances => Some(ResetPhase::Bets),
+            ResetPhase::Bets => Some(ResetPhase::OpenBetCounts),
+            ResetPhase::OpenBetCounts => Some(ResetPhase::DailyUsage),
+            ResetPhase::DailyUsage => Some(ResetPhase::DailyLoss),
+            ResetPhase::DailyLoss => Some(ResetPhase::PendingWithdrawals),
+            ResetPhase::PendingWithdrawals => Some(ResetPhase::RecentWithdrawals),
+            ResetPhase::RecentWithdrawals => Some(ResetPhase::PlayerStats),
+            ResetPhase::PlayerStats => Some(ResetPhase::DailyStats),
+            ResetPhase::DailyStats => Some(ResetPhase::PeriodStats),
+            ResetPhase::PeriodStats => Some(ResetPhase::Leaderboard),
+            ResetPhase::Leaderboard => Some(ResetPhase::StatsSeries),
+            ResetPhase::StatsSeries => Some(ResetPhase::SeriesPlayers),
+            ResetPhase::SeriesPlayers => Some(ResetPhase::AccruedFees),
+            ResetPhase::AccruedFees => Some(ResetPhase::ReferralEarnings),
+            ResetPhase::ReferralEarnings => Some(ResetPhase::JackpotPools),
+            ResetPhase::JackpotPools => Some(ResetPhase::JackpotEntries),
+            ResetPhase::JackpotEntries => Some(ResetPhase::JackpotRanges),
+            ResetPhase::JackpotRanges => Some(ResetPhase::JackpotDraws),
+            ResetPhase::JackpotDraws => Some(ResetPhase::RakebackEpochs),
+            ResetPhase::RakebackEpochs => Some(ResetPhase::RakebackEntries),
+            ResetPhase::RakebackEntries => Some(ResetPhase::Loyalty),
+            ResetPhase::Loyalty => Some(ResetPhase::BonusWagering),
+            ResetPhase::BonusWagering => Some(ResetPhase::PairStats),
+            ResetPhase::PairStats => None,
+        }
+    }
+}
+
+#[cw_serde]
+pub struct ResetProgress {
+    /// Map currently being cleared. Cleared keys are removed, so the phase is the cursor.
+    pub phase: ResetPhase,
+    pub cleared_entries: u64,
+}
+
+/// Vault totals being counted from `VAULT_BALANCES` in batches, for state written
+/// before the totals existed.
+#[cw_serde]
+#[derive(Default)]
+pub struct TotalsBackfill {
+    /// Last balance counted; the next batch starts after it
+    pub last_counted: Option<Addr>,
+    pub counted_balances: u64,
+    /// Sums over the balances counted so far
+    pub totals: VaultTotals,
 }
 
 // ---- Storage keys ----
@@ -78,11 +662,127 @@ pub struct Bet {
 pub const CONFIG: Item<Config> = Item::new("config");
 pub const NEXT_BET_ID: Item<u64> = Item::new("next_bet_id");
 pub const VAULT_BALANCES: Map<&Addr, VaultBalance> = Map::new("vault_balances");
+/// Sums over VAULT_BALANCES, so solvency checks do not have to iterate it
+pub const VAULT_TOTALS: Item<VaultTotals> = Item::new("vault_totals");
 pub const BETS: Map<u64, Bet> = Map::new("bets");
 pub const USER_OPEN_BET_COUNT: Map<&Addr, u16> = Map::new("user_open_bet_count");
 
 /// Daily usage tracking: (address, day_bucket) -> amount_used
 pub const DAILY_USAGE: Map<(&Addr, u64), Uint128> = Map::new("daily_usage");
+/// Daily results for loss limits: (address, day_bucket) -> DailyLoss
+pub const DAILY_LOSS: Map<(&Addr, u64), DailyLoss> = Map::new("daily_loss");
+/// Daily play between pairs: (day_bucket, player, opponent) -> PairStats, saved under
+/// both players of the pair. Day first, so past days can be pruned in key order.
+pub const PAIR_STATS: Map<(u64, &Addr, &Addr), PairStats> = Map::new("pair_stats");
+
+/// Limits players set on themselves: address -> PlayerLimits. Kept across state resets,
+/// like PENDING_LIMITS, so a reset cannot lift a self-exclusion.
+pub const PLAYER_LIMITS: Map<&Addr, PlayerLimits> = Map::new("player_limits");
+/// Loosened limits waiting for the delay: address -> PendingLimits
+pub const PENDING_LIMITS: Map<&Addr, PendingLimits> = Map::new("pending_limits");
 
 /// Pending admin for 2-step ownership transfer
 pub const PENDING_ADMIN: Item<Addr> = Item::new("pending_admin");
+
+/// Set by chain governance via `sudo`. Absent = not paused.
+pub const PAUSED: Item<bool> = Item::new("paused");
+
+/// Contracts notified of every resolved bet: contract -> gas limit of the call
+pub const HOOKS: Map<&Addr, u64> = Map::new("hooks");
+/// Hook called under each reply id by the last resolution: reply id -> contract.
+/// Rewritten on every dispatch, so it never holds more than `MAX_HOOKS` entries.
+pub const HOOK_CALLS: Map<u64, Addr> = Map::new("hook_calls");
+
+/// Commission shares waiting for `FlushFees`: recipient -> amount
+pub const ACCRUED_FEES: Map<&Addr, Uint128> = Map::new("accrued_fees");
+
+/// Referral registry: player -> referrer. Set once, never changed, kept across state resets.
+pub const REFERRERS: Map<&Addr, Addr> = Map::new("referrers");
+/// Referral tree sizes: referrer -> players at levels 1, 2 and 3 below it
+pub const REFERRAL_TREES: Map<&Addr, [u64; 3]> = Map::new("referral_trees");
+/// Referral rewards: referrer -> amounts credited from levels 1, 2 and 3
+pub const REFERRAL_EARNINGS: Map<&Addr, [Uint128; 3]> = Map::new("referral_earnings");
+
+/// Jackpot pools: tier id -> current cycle
+pub const JACKPOT_POOLS: Map<u8, JackpotPool> = Map::new("jackpot_pools");
+/// Draw entries: (tier id, cycle, player) -> eligible stakes in that cycle
+pub const JACKPOT_ENTRIES: Map<(u8, u64, &Addr), Uint128> = Map::new("jackpot_entries");
+/// Draw ranges: (tier id, cycle, index) -> JackpotRange, in the order the stakes entered
+pub const JACKPOT_RANGES: Map<(u8, u64, u32), JackpotRange> = Map::new("jackpot_ranges");
+/// Draw history: (tier id, cycle) -> JackpotDraw
+pub const JACKPOT_DRAWS: Map<(u8, u64), JackpotDraw> = Map::new("jackpot_draws");
+
+/// Rakeback reserves: epoch (block time / 604800) -> RakebackEpoch
+pub const RAKEBACK_EPOCHS: Map<u64, RakebackEpoch> = Map::new("rakeback_epochs");
+/// Rakeback per player: (epoch, player) -> RakebackEntry
+pub const RAKEBACK: Map<(u64, &Addr), RakebackEntry> = Map::new("rakeback");
+
+/// Promo voucher redemptions: voucher id -> times redeemed. Kept across state resets,
+/// like PROMO_REDEEMERS, so vouchers cannot be replayed.
+pub const PROMO_REDEMPTIONS: Map<&str, u32> = Map::new("promo_redemptions");
+/// (voucher id, player) -> bet the voucher was redeemed on
+pub const PROMO_REDEEMERS: Map<(&str, &Addr), u64> = Map::new("promo_redeemers");
+
+/// Loyalty XP and fee discounts: player -> Loyalty
+pub const LOYALTY: Map<&Addr, Loyalty> = Map::new("loyalty");
+
+/// Bonus wagering requirements: player -> BonusWagering. Removed once the player has
+/// no bonus left, in their balance or staked.
+pub const BONUS_WAGERING: Map<&Addr, BonusWagering> = Map::new("bonus_wagering");
+
+/// Delayed withdrawals: withdrawal_id -> PendingWithdrawal
+pub const PENDING_WITHDRAWALS: Map<u64, PendingWithdrawal> = Map::new("pending_withdrawals");
+/// Instant withdrawals of each user within the delay window, oldest first
+pub const RECENT_WITHDRAWALS: Map<&Addr, Vec<RecentWithdrawal>> = Map::new("recent_withdrawals");
+/// Next pending withdrawal id (starts at 1; backfilled by the v0.3.0 migration).
+pub const NEXT_WITHDRAWAL_ID: Item<u64> = Item::new("next_withdrawal_id");
+
+/// Emergency shutdown progress. Present = shutdown mode (only withdrawals allowed).
+pub const SHUTDOWN: Item<ShutdownState> = Item::new("shutdown");
+
+/// Vaults whose exported balances may be imported: source_contract -> rate and running totals
+pub const IMPORT_SOURCES: Map<&Addr, ImportSource> = Map::new("import_sources");
+/// Imported balances: (source_contract, user) -> source amount. Guards against double imports.
+pub const IMPORTED_BALANCES: Map<(&Addr, &Addr), Uint128> = Map::new("imported_balances");
+
+/// Totals over finished bets: player -> PlayerStats
+pub const PLAYER_STATS: Map<&Addr, PlayerStats> = Map::new("player_stats");
+/// Archived totals of pruned bets: day bucket (created_at_time / 86400) -> DailyStats
+pub const DAILY_STATS: Map<u64, DailyStats> = Map::new("daily_stats");
+/// Leaderboard counters: (period, bucket, player) -> PeriodStats.
+/// Period is "day" or "week"; bucket is block time / period length.
+pub const PERIOD_STATS: Map<(&str, u64, &Addr), PeriodStats> = Map::new("period_stats");
+/// Sorted leaderboard index: (board, score, player) -> (). See `leaderboard::board`.
+pub const LEADERBOARD: Map<(&str, i128, &Addr), ()> = Map::new("leaderboard");
+/// Activity series: (granularity, bucket) -> ActivityStats. Granularity is "hour" or
+/// "day" with bucket = block time / its length, or "all" with bucket 0 for all-time totals.
+pub const STATS_SERIES: Map<(&str, u64), ActivityStats> = Map::new("stats_series");
+/// Addresses already counted in a series bucket's `unique_players`
+pub const SERIES_PLAYERS: Map<(&str, u64, &Addr), ()> = Map::new("series_players");
+
+/// Batched state reset started by `migrate { reset_state: true }`.
+/// Present = resetting (only `ContinueMigration` allowed).
+pub const RESET_PROGRESS: Item<ResetProgress> = Item::new("reset_progress");
+/// Batched count of `VAULT_TOTALS` started by the v0.3.0 migration.
+/// Present = counting (only `ContinueMigration` allowed).
+pub const TOTALS_BACKFILL: Item<TotalsBackfill> = Item::new("totals_backfill");
+
+/// Save a vault balance and apply the difference to `VAULT_TOTALS`.
+/// Every write to `VAULT_BALANCES` must go through here.
+/// Returns the `coinflip_balance_changed` event for the response.
+pub fn save_balance(
+    storage: &mut dyn Storage,
+    addr: &Addr,
+    balance: &VaultBalance,
+) -> StdResult<Event> {
+    let old = VAULT_BALANCES.may_load(storage, addr)?.unwrap_or_default();
+    let mut totals = VAULT_TOTALS.may_load(storage)?.unwrap_or_default();
+    totals.total_available = (totals.total_available + balance.available).checked_sub(old.available)?;
+    totals.total_locked = (totals.total_locked + balance.locked).checked_sub(old.locked)?;
+    totals.total_pending_withdrawal = (totals.total_pending_withdrawal + balance.pending_withdrawal)
+        .checked_sub(old.pending_withdrawal)?;
+    totals.total_bonus = (totals.total_bonus + balance.bonus).checked_sub(old.bonus)?;
+    VAULT_TOTALS.save(storage, &totals)?;
+    VAULT_BALANCES.save(storage, addr, balance)?;
+    Ok(events::balance_changed(addr, &old, balance))
+}
@@ -1,12 +1,16 @@
 use cosmwasm_std::{
-    entry_point, to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Order, Response,
-    StdResult,
+    entry_point, to_json_binary, Binary, Deps, DepsMut, Env, Event, MessageInfo, Reply, Response,
+    StdError, StdResult, Storage, Uint128,
 };
 use cw2::{ensure_from_older_version, set_contract_version};
 
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
-use crate::state::{Config, CONFIG, NEXT_BET_ID, PENDING_ADMIN, VAULT_BALANCES, BETS, USER_OPEN_BET_COUNT, DAILY_USAGE};
+use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, SudoMsg};
+use crate::state::{
+    default_bet_retention_secs, default_rakeback_claim_epochs, default_withdraw_delay_secs, Config,
+    VaultTotals, CONFIG, NEXT_BET_ID, NEXT_WITHDRAWAL_ID, PAUSED, PENDING_ADMIN, RESET_PROGRESS,
+    SHUTDOWN, TOTALS_BACKFILL, VAULT_TOTALS,
+};
 
 const CONTRACT_NAME: &str = "crates.io:coinflip-pvp-vault";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -38,10 +42,32 @@ pub fn instantiate(
         max_open_per_user: msg.max_open_per_user,
         max_daily_amount_per_user: msg.max_daily_amount_per_user,
         bet_ttl_secs: msg.bet_ttl_secs,
+        withdraw_delay_threshold: Uint128::zero(),
+        withdraw_delay_secs: default_withdraw_delay_secs(),
+        guardian: None,
+        bet_retention_secs: default_bet_retention_secs(),
+        commission_split: vec![],
+        referral_bps: vec![],
+        jackpot_tiers: vec![],
+        rakeback_bps: 0,
+        rakeback_claim_epochs: default_rakeback_claim_epochs(),
+        xp_unit: Uint128::zero(),
+        discount_bps: 0,
+        discount_xp_cost: 0,
+        staking_contract: None,
+        stake_discounts: vec![],
+        fee_tiers: vec![],
+        fee_promo: None,
+        min_commission_bps: 0,
+        promo_pubkey: None,
+        bonus_wagering_multiple: 0,
+        pair_caps: None,
     };
 
     CONFIG.save(deps.storage, &config)?;
     NEXT_BET_ID.save(deps.storage, &1u64)?;
+    NEXT_WITHDRAWAL_ID.save(deps.storage, &1u64)?;
+    VAULT_TOTALS.save(deps.storage, &VaultTotals::default())?;
 
     Ok(Response::new()
         .add_attribute("method", "instantiate")
@@ -56,6 +82,8 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    ensure_allowed(deps.storage, &msg)?;
+
     match msg {
         ExecuteMsg::Receive(cw20_msg) => {
             crate::execute::deposit::execute_receive(deps, env, info, cw20_msg)
@@ -63,20 +91,48 @@ pub fn execute(
         ExecuteMsg::Withdraw { amount } => {
             crate::execute::withdraw::execute_withdraw(deps, env, info, amount)
         }
-        ExecuteMsg::CreateBet { amount, commitment } => {
-            crate::execute::create_bet::execute_create_bet(deps, env, info, amount, commitment)
+        ExecuteMsg::ClaimWithdrawal { withdrawal_id } => {
+            crate::execute::pending_withdrawal::execute_claim_withdrawal(deps, env, info, withdrawal_id)
         }
-        ExecuteMsg::CancelBet { bet_id } => {
-            crate::execute::cancel_bet::execute_cancel_bet(deps, env, info, bet_id)
+        ExecuteMsg::CancelWithdrawal { withdrawal_id } => {
+            crate::execute::pending_withdrawal::execute_cancel_withdrawal(deps, env, info, withdrawal_id)
         }
-        ExecuteMsg::AcceptBet { bet_id, guess } => {
-            crate::execute::accept_bet::execute_accept_bet(deps, env, info, bet_id, guess)
+        ExecuteMsg::FreezeWithdrawal { withdrawal_id } => {
+            crate::execute::pending_withdrawal::execute_set_withdrawal_frozen(
+                deps, env, info, withdrawal_id, true,
+            )
         }
-        ExecuteMsg::AcceptAndReveal { bet_id, guess, side, secret } => {
-            crate::execute::accept_and_reveal::execute_accept_and_reveal(
-                deps, env, info, bet_id, guess, side, secret,
+        ExecuteMsg::UnfreezeWithdrawal { withdrawal_id } => {
+            crate::execute::pending_withdrawal::execute_set_withdrawal_frozen(
+                deps, env, info, withdrawal_id, false,
             )
         }
+        ExecuteMsg::CreateBet {
+            amount,
+            commitment,
+            voucher,
+        } => crate::execute::create_bet::execute_create_bet(
+            deps, env, info, amount, commitment, voucher,
+        ),
+        ExecuteMsg::CancelBet { bet_id } => {
+            crate::execute::cancel_bet::execute_cancel_bet(deps, env, info, bet_id)
+        }
+        ExecuteMsg::AcceptBet {
+            bet_id,
+            guess,
+            voucher,
+        } => crate::execute::accept_bet::execute_accept_bet(
+            deps, env, info, bet_id, guess, voucher,
+        ),
+        ExecuteMsg::AcceptAndReveal {
+            bet_id,
+            guess,
+            side,
+            secret,
+            voucher,
+        } => crate::execute::accept_and_reveal::execute_accept_and_reveal(
+            deps, env, info, bet_id, guess, side, secret, voucher,
+        ),
         ExecuteMsg::Reveal { bet_id, side, secret } => {
             crate::execute::reveal::execute_reveal(deps, env, info, bet_id, side, secret)
         }
@@ -102,6 +158,150 @@ pub fn execute(
             max_daily_amount_per_user,
             bet_ttl_secs,
         ),
+        ExecuteMsg::UpdateWithdrawPolicy {
+            threshold,
+            delay_secs,
+            guardian,
+        } => crate::execute::pending_withdrawal::execute_update_withdraw_policy(
+            deps, info, threshold, delay_secs, guardian,
+        ),
+        ExecuteMsg::EmergencyShutdown {} => {
+            crate::execute::shutdown::execute_emergency_shutdown(deps, env, info)
+        }
+        ExecuteMsg::PruneBets { before_id, limit } => {
+            crate::execute::prune_bets::execute_prune_bets(deps, env, info, before_id, limit)
+        }
+        ExecuteMsg::PrunePeriodStats { limit } => {
+            crate::execute::prune_stats::execute_prune_period_stats(deps, env, limit)
+        }
+        ExecuteMsg::PrunePairStats { limit } => {
+            crate::execute::prune_stats::execute_prune_pair_stats(deps, env, limit)
+        }
+        ExecuteMsg::UpdateBetRetention { bet_retention_secs } => {
+            crate::execute::prune_bets::execute_update_bet_retention(deps, info, bet_retention_secs)
+        }
+        ExecuteMsg::ContinueMigration { limit } => {
+            crate::execute::continue_migration::execute_continue_migration(deps, env, info, limit)
+        }
+        ExecuteMsg::ProcessShutdown { limit } => {
+            crate::execute::shutdown::execute_process_shutdown(deps, env, info, limit)
+        }
+        ExecuteMsg::FinalizeExport {} => {
+            crate::execute::shutdown::execute_finalize_export(deps, info)
+        }
+        ExecuteMsg::ConfigureImport {
+            source_contract,
+            rate_num,
+            rate_denom,
+        } => crate::execute::import_balances::execute_configure_import(
+            deps, info, source_contract, rate_num, rate_denom,
+        ),
+        ExecuteMsg::ImportBalances {
+            source_contract,
+            start_after,
+            entries,
+            checksum,
+        } => crate::execute::import_balances::execute_import_balances(
+            deps, env, info, source_contract, start_after, entries, checksum,
+        ),
+        ExecuteMsg::UpdateCommissionSplit { shares } => {
+            crate::execute::fees::execute_update_commission_split(deps, info, shares)
+        }
+        ExecuteMsg::FlushFees {} => crate::execute::fees::execute_flush_fees(deps, info),
+        ExecuteMsg::SetReferrer { referrer } => {
+            crate::execute::referrals::execute_set_referrer(deps, info, referrer)
+        }
+        ExecuteMsg::UpdateReferralBps { bps } => {
+            crate::execute::referrals::execute_update_referral_bps(deps, info, bps)
+        }
+        ExecuteMsg::UpdateJackpotTiers { tiers } => {
+            crate::execute::jackpot::execute_update_jackpot_tiers(deps, info, tiers)
+        }
+        ExecuteMsg::CommitJackpotSeed { tier, commitment } => {
+            crate::execute::jackpot::execute_commit_jackpot_seed(deps, info, tier, commitment)
+        }
+        ExecuteMsg::DrawJackpot { tier, seed } => {
+            crate::execute::jackpot::execute_draw_jackpot(deps, env, info, tier, seed)
+        }
+        ExecuteMsg::FallbackDrawJackpot { tier } => {
+            crate::execute::jackpot::execute_fallback_draw_jackpot(deps, env, tier)
+        }
+        ExecuteMsg::PruneJackpotEntries { tier, cycle, limit } => {
+            crate::execute::jackpot::execute_prune_jackpot_entries(deps, tier, cycle, limit)
+        }
+        ExecuteMsg::UpdateRakeback { bps, claim_epochs } => {
+            crate::execute::rakeback::execute_update_rakeback(deps, info, bps, claim_epochs)
+        }
+        ExecuteMsg::ClaimRakeback { epoch } => {
+            crate::execute::rakeback::execute_claim_rakeback(deps, env, info, epoch)
+        }
+        ExecuteMsg::SweepRakeback { epoch } => {
+            crate::execute::rakeback::execute_sweep_rakeback(deps, env, epoch)
+        }
+        ExecuteMsg::UpdateLoyalty {
+            xp_unit,
+            discount_bps,
+            discount_xp_cost,
+        } => crate::execute::loyalty::execute_update_loyalty(
+            deps,
+            info,
+            xp_unit,
+            discount_bps,
+            discount_xp_cost,
+        ),
+        ExecuteMsg::RedeemFeeDiscount { bets } => {
+            crate::execute::loyalty::execute_redeem_fee_discount(deps, info, bets)
+        }
+        ExecuteMsg::UpdateStakeDiscounts {
+            staking_contract,
+            discounts,
+        } => crate::execute::stake_discounts::execute_update_stake_discounts(
+            deps,
+            info,
+            staking_contract,
+            discounts,
+        ),
+        ExecuteMsg::UpdatePromoKey { pubkey } => {
+            crate::execute::promo::execute_update_promo_key(deps, info, pubkey)
+        }
+        ExecuteMsg::UpdateBonusWagering { multiple } => {
+            crate::execute::bonus::execute_update_bonus_wagering(deps, info, multiple)
+        }
+        ExecuteMsg::CreditBonus { address, amount } => {
+            crate::execute::bonus::execute_credit_bonus(deps, info, address, amount)
+        }
+        ExecuteMsg::SetPlayerLimits {
+            daily_loss_limit,
+            max_bet,
+            cooldown_until,
+        } => crate::execute::limits::execute_set_player_limits(
+            deps,
+            env,
+            info,
+            daily_loss_limit,
+            max_bet,
+            cooldown_until,
+        ),
+        ExecuteMsg::UpdatePairCaps { caps } => {
+            crate::execute::pairs::execute_update_pair_caps(deps, info, caps)
+        }
+        ExecuteMsg::UpdateFeeSchedule {
+            tiers,
+            promo,
+            min_commission_bps,
+        } => crate::execute::fees::execute_update_fee_schedule(
+            deps,
+            info,
+            tiers,
+            promo,
+            min_commission_bps,
+        ),
+        ExecuteMsg::RegisterHook { contract, gas_limit } => {
+            crate::execute::hooks::execute_register_hook(deps, info, contract, gas_limit)
+        }
+        ExecuteMsg::RemoveHook { contract } => {
+            crate::execute::hooks::execute_remove_hook(deps, info, contract)
+        }
         ExecuteMsg::TransferAdmin { new_admin } => execute_transfer_admin(deps, info, new_admin),
         ExecuteMsg::AcceptAdmin {} => execute_accept_admin(deps, info),
         ExecuteMsg::AdminSweep { recipient } => {
@@ -110,8 +310,82 @@ pub fn execute(
     }
 }
 
+/// Reject messages that are not allowed in the contract's current mode.
+/// While a state reset or the totals backfill is running only `ContinueMigration` is accepted.
+/// While paused by governance only `Reveal` and the exits are accepted: makers do
+/// not miss their reveal deadline, and users can still cancel open bets, claim timed-out
+/// bets and take their funds out.
+/// During emergency shutdown only withdrawals, shutdown processing, fee, jackpot and
+/// rakeback payouts and admin handover remain available.
+fn ensure_allowed(storage: &dyn Storage, msg: &ExecuteMsg) -> Result<(), ContractError> {
+    let migrating = RESET_PROGRESS.may_load(storage)?.is_some()
+        || TOTALS_BACKFILL.may_load(storage)?.is_some();
+    if migrating {
+        return match msg {
+            ExecuteMsg::ContinueMigration { .. } => Ok(()),
+            _ => Err(ContractError::MigrationInProgress),
+        };
+    }
+    if PAUSED.may_load(storage)?.unwrap_or(false) {
+        let allowed = matches!(
+            msg,
+            ExecuteMsg::Reveal { .. }
+                | ExecuteMsg::CancelBet { .. }
+                | ExecuteMsg::ClaimTimeout { .. }
+                | ExecuteMsg::Withdraw { .. }
+                | ExecuteMsg::ClaimWithdrawal { .. }
+                | ExecuteMsg::CancelWithdrawal { .. }
+        );
+        if !allowed {
+            return Err(ContractError::Paused);
+        }
+    }
+    if let Some(shutdown) = SHUTDOWN.may_load(storage)? {
+        // Exported balances stay frozen; only housekeeping that leaves them alone runs
+        if shutdown.export_finalized {
+            let allowed = matches!(
+                msg,
+                ExecuteMsg::PruneBets { .. }
+                    | ExecuteMsg::PrunePeriodStats { .. }
+                    | ExecuteMsg::PrunePairStats { .. }
+                    | ExecuteMsg::PruneJackpotEntries { .. }
+                    | ExecuteMsg::FlushFees {}
+                    | ExecuteMsg::TransferAdmin { .. }
+                    | ExecuteMsg::AcceptAdmin {}
+            );
+            return if allowed { Ok(()) } else { Err(ContractError::ExportFinalized) };
+        }
+        let allowed = matches!(
+            msg,
+            ExecuteMsg::Withdraw { .. }
+                | ExecuteMsg::ClaimWithdrawal { .. }
+                | ExecuteMsg::CancelWithdrawal { .. }
+                | ExecuteMsg::FreezeWithdrawal { .. }
+                | ExecuteMsg::UnfreezeWithdrawal { .. }
+                | ExecuteMsg::ProcessShutdown { .. }
+                | ExecuteMsg::FinalizeExport {}
+                | ExecuteMsg::PruneBets { .. }
+                | ExecuteMsg::PrunePeriodStats { .. }
+                | ExecuteMsg::PrunePairStats { .. }
+                | ExecuteMsg::FlushFees {}
+                | ExecuteMsg::DrawJackpot { .. }
+                | ExecuteMsg::FallbackDrawJackpot { .. }
+                | ExecuteMsg::PruneJackpotEntries { .. }
+                | ExecuteMsg::ClaimRakeback { .. }
+                | ExecuteMsg::SweepRakeback { .. }
+                | ExecuteMsg::TransferAdmin { .. }
+                | ExecuteMsg::AcceptAdmin {}
+        );
+        if !allowed {
+            return Err(ContractError::ContractShutdown);
+        }
+    }
+    Ok(())
+}
+
 /// Admin-only: update contract configuration.
 /// Only the fields that are `Some(...)` get updated.
+#[allow(clippy::too_many_arguments)]
 fn execute_update_config(
     deps: DepsMut,
     info: MessageInfo,
@@ -123,11 +397,43 @@ fn execute_update_config(
     max_daily_amount_per_user: Option<cosmwasm_std::Uint128>,
     bet_ttl_secs: Option<u64>,
 ) -> Result<Response, ContractError> {
-    let mut config = CONFIG.load(deps.storage)?;
+    let config = CONFIG.load(deps.storage)?;
     if info.sender != config.admin {
         return Err(ContractError::Unauthorized);
     }
 
+    let config = update_config(
+        deps,
+        treasury,
+        commission_bps,
+        min_bet,
+        reveal_timeout_secs,
+        max_open_per_user,
+        max_daily_amount_per_user,
+        bet_ttl_secs,
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_config")
+        .add_attribute("admin", info.sender.to_string())
+        .add_event(config_event("coinflip_config_updated", &config)))
+}
+
+/// Validate and apply the `Some(...)` fields to Config.
+/// Shared by the admin and governance (sudo) paths.
+#[allow(clippy::too_many_arguments)]
+fn update_config(
+    deps: DepsMut,
+    treasury: Option<String>,
+    commission_bps: Option<u16>,
+    min_bet: Option<cosmwasm_std::Uint128>,
+    reveal_timeout_secs: Option<u64>,
+    max_open_per_user: Option<u16>,
+    max_daily_amount_per_user: Option<cosmwasm_std::Uint128>,
+    bet_ttl_secs: Option<u64>,
+) -> Result<Config, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
     if let Some(t) = treasury {
         config.treasury = deps.api.addr_validate(&t)?;
     }
@@ -135,13 +441,21 @@ fn execute_update_config(
         if bps > 5000 {
             return Err(ContractError::InvalidCommission { max_bps: 5000 });
         }
+        if bps < config.min_commission_bps {
+            return Err(ContractError::InvalidFeeSchedule {
+                reason: format!(
+                    "commission_bps below min_commission_bps {}",
+                    config.min_commission_bps
+                ),
+            });
+        }
         config.commission_bps = bps;
     }
     if let Some(mb) = min_bet {
         config.min_bet = mb;
     }
     if let Some(rt) = reveal_timeout_secs {
-        if rt < 60 || rt > 86400 {
+        if !(60..=86400).contains(&rt) {
             return Err(ContractError::InvalidTimeout { min: 60, max: 86400 });
         }
         config.reveal_timeout_secs = rt;
@@ -154,17 +468,26 @@ fn execute_update_config(
     }
     if let Some(ttl) = bet_ttl_secs {
         // 0 = disabled; otherwise must be 300s..604800s (5 min to 7 days)
-        if ttl > 0 && (ttl < 300 || ttl > 604800) {
+        if ttl > 0 && !(300..=604800).contains(&ttl) {
             return Err(ContractError::InvalidTimeout { min: 300, max: 604800 });
         }
         config.bet_ttl_secs = ttl;
     }
 
     CONFIG.save(deps.storage, &config)?;
+    Ok(config)
+}
 
-    Ok(Response::new()
-        .add_attribute("action", "update_config")
-        .add_attribute("admin", info.sender.to_string()))
+/// Config values after an update, shared by the admin and governance events.
+fn config_event(ty: &str, config: &Config) -> Event {
+    Event::new(ty)
+        .add_attribute("treasury", config.treasury.to_string())
+        .add_attribute("commission_bps", config.commission_bps.to_string())
+        .add_attribute("min_bet", config.min_bet.to_string())
+        .add_attribute("reveal_timeout_secs", config.reveal_timeout_secs.to_string())
+        .add_attribute("max_open_per_user", config.max_open_per_user.to_string())
+        .add_attribute("max_daily_amount_per_user", config.max_daily_amount_per_user.to_string())
+        .add_attribute("bet_ttl_secs", config.bet_ttl_secs.to_string())
 }
 
 /// Step 1 of 2-step admin transfer: current admin proposes a new admin.
@@ -183,7 +506,12 @@ fn execute_transfer_admin(
 
     Ok(Response::new()
         .add_attribute("action", "transfer_admin")
-        .add_attribute("pending_admin", validated.to_string()))
+        .add_attribute("pending_admin", validated.to_string())
+        .add_event(
+            Event::new("coinflip_admin_transfer_proposed")
+                .add_attribute("admin", config.admin.to_string())
+                .add_attribute("pending_admin", validated.to_string()),
+        ))
 }
 
 /// Step 2 of 2-step admin transfer: pending admin accepts ownership.
@@ -195,26 +523,110 @@ fn execute_accept_admin(
     match pending {
         Some(addr) if addr == info.sender => {
             let mut config = CONFIG.load(deps.storage)?;
+            let old_admin = config.admin;
             config.admin = addr.clone();
             CONFIG.save(deps.storage, &config)?;
             PENDING_ADMIN.remove(deps.storage);
 
             Ok(Response::new()
                 .add_attribute("action", "accept_admin")
-                .add_attribute("new_admin", addr.to_string()))
+                .add_attribute("new_admin", addr.to_string())
+                .add_event(
+                    Event::new("coinflip_admin_transferred")
+                        .add_attribute("old_admin", old_admin.to_string())
+                        .add_attribute("new_admin", addr.to_string()),
+                ))
         }
         _ => Err(ContractError::Unauthorized),
     }
 }
 
+/// Chain governance entry point. Bypasses the admin check so the contract can be
+/// recovered if the admin key is lost or compromised.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn sudo(deps: DepsMut, _env: Env, msg: SudoMsg) -> Result<Response, ContractError> {
+    match msg {
+        SudoMsg::ReplaceAdmin { new_admin } => sudo_replace_admin(deps, new_admin),
+        SudoMsg::Pause {} => sudo_set_paused(deps, true),
+        SudoMsg::Unpause {} => sudo_set_paused(deps, false),
+        SudoMsg::UpdateConfig {
+            treasury,
+            commission_bps,
+            min_bet,
+            reveal_timeout_secs,
+            max_open_per_user,
+            max_daily_amount_per_user,
+            bet_ttl_secs,
+        } => {
+            let config = update_config(
+                deps,
+                treasury,
+                commission_bps,
+                min_bet,
+                reveal_timeout_secs,
+                max_open_per_user,
+                max_daily_amount_per_user,
+                bet_ttl_secs,
+            )?;
+            Ok(Response::new()
+                .add_attribute("action", "sudo_update_config")
+                .add_event(config_event("coinflip_sudo_config_updated", &config)))
+        }
+    }
+}
+
+/// Governance: replace the admin directly and drop any pending 2-step transfer.
+fn sudo_replace_admin(deps: DepsMut, new_admin: String) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    let old_admin = config.admin;
+    config.admin = deps.api.addr_validate(&new_admin)?;
+    CONFIG.save(deps.storage, &config)?;
+    PENDING_ADMIN.remove(deps.storage);
+
+    Ok(Response::new()
+        .add_attribute("action", "sudo_replace_admin")
+        .add_event(
+            Event::new("coinflip_sudo_admin_replaced")
+                .add_attribute("old_admin", old_admin.to_string())
+                .add_attribute("new_admin", config.admin.to_string()),
+        ))
+}
+
+/// Governance: pause or resume the contract (see `ensure_allowed`).
+fn sudo_set_paused(deps: DepsMut, paused: bool) -> Result<Response, ContractError> {
+    PAUSED.save(deps.storage, &paused)?;
+
+    let (action, event) = if paused {
+        ("sudo_pause", "coinflip_sudo_paused")
+    } else {
+        ("sudo_unpause", "coinflip_sudo_unpaused")
+    };
+    Ok(Response::new()
+        .add_attribute("action", action)
+        .add_event(Event::new(event)))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        id if crate::execute::hooks::is_hook_reply(id) => {
+            crate::execute::hooks::reply_hook_failed(deps, msg)
+        }
+        id => Err(StdError::generic_err(format!("unknown reply id: {id}")).into()),
+    }
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn migrate(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     msg: MigrateMsg,
 ) -> Result<Response, ContractError> {
     let version = ensure_from_older_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
+    // Versioned storage upgrades for everything written before CONTRACT_VERSION
+    let applied = crate::migrations::run_migrations(deps.storage, &env, &version)?;
+
     let mut config = CONFIG.load(deps.storage)?;
 
     // v0.5.0: allow switching CW20 token address during migration
@@ -222,56 +634,33 @@ pub fn migrate(
         config.token_cw20 = deps.api.addr_validate(&new_token)?;
     }
 
-    // v0.5.1: full state reset (bets, vaults, counters)
-    let mut cleared_count: u64 = 0;
+    // v0.5.1: full state reset (bets, vaults, counters).
+    // Runs in batches via ContinueMigration, so it cannot exceed the block gas limit on large maps
     if msg.reset_state {
-        // Clear all vault balances
-        let vault_keys: Vec<_> = VAULT_BALANCES
-            .keys(deps.storage, None, None, Order::Ascending)
-            .collect::<StdResult<Vec<_>>>()?;
-        for key in &vault_keys {
-            VAULT_BALANCES.remove(deps.storage, key);
-        }
-
-        // Clear all bets
-        let bet_keys: Vec<_> = BETS
-            .keys(deps.storage, None, None, Order::Ascending)
-            .collect::<StdResult<Vec<_>>>()?;
-        for key in &bet_keys {
-            BETS.remove(deps.storage, *key);
-        }
-
-        // Clear open bet counts
-        let obc_keys: Vec<_> = USER_OPEN_BET_COUNT
-            .keys(deps.storage, None, None, Order::Ascending)
-            .collect::<StdResult<Vec<_>>>()?;
-        for key in &obc_keys {
-            USER_OPEN_BET_COUNT.remove(deps.storage, key);
-        }
-
-        // Clear daily usage
-        let du_keys: Vec<_> = DAILY_USAGE
-            .keys(deps.storage, None, None, Order::Ascending)
-            .collect::<StdResult<Vec<_>>>()?;
-        for key in &du_keys {
-            DAILY_USAGE.remove(deps.storage, (&key.0, key.1));
-        }
-
-        // Reset bet counter
-        NEXT_BET_ID.save(deps.storage, &1u64)?;
-
-        cleared_count = (vault_keys.len() + bet_keys.len() + obc_keys.len() + du_keys.len()) as u64;
+        crate::execute::continue_migration::start_reset(deps.storage)?;
     }
 
     CONFIG.save(deps.storage, &config)?;
 
+    let migrations = if applied.is_empty() {
+        "none".to_string()
+    } else {
+        applied.join(",")
+    };
     Ok(Response::new()
         .add_attribute("action", "migrate")
         .add_attribute("from_version", version.to_string())
         .add_attribute("to_version", CONTRACT_VERSION)
+        .add_attribute("migrations", migrations.clone())
         .add_attribute("token_cw20", config.token_cw20.to_string())
         .add_attribute("state_reset", msg.reset_state.to_string())
-        .add_attribute("cleared_entries", cleared_count.to_string()))
+        .add_event(
+            Event::new("coinflip_migrated")
+                .add_attribute("from_version", version.to_string())
+                .add_attribute("to_version", CONTRACT_VERSION)
+                .add_attribute("migrations", migrations)
+                .add_attribute("state_reset", msg.reset_state.to_string()),
+        ))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -295,5 +684,100 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             start_after,
             limit,
         )?),
+        QueryMsg::ShutdownStatus {} => to_json_binary(&crate::query::query_shutdown_status(deps)?),
+        QueryMsg::Solvency {} => to_json_binary(&crate::query::query_solvency(deps, env)?),
+        QueryMsg::PlayerStats { address } => {
+            to_json_binary(&crate::query::query_player_stats(deps, address)?)
+        }
+        QueryMsg::DailyStats { start_after, limit } => {
+            to_json_binary(&crate::query::query_daily_stats(deps, start_after, limit)?)
+        }
+        QueryMsg::AccruedFees {} => to_json_binary(&crate::query::query_accrued_fees(deps)?),
+        QueryMsg::Referral { address } => {
+            to_json_binary(&crate::query::query_referral(deps, address)?)
+        }
+        QueryMsg::JackpotPools {} => to_json_binary(&crate::query::query_jackpot_pools(deps)?),
+        QueryMsg::JackpotDraws {
+            tier,
+            start_after,
+            limit,
+        } => to_json_binary(&crate::query::query_jackpot_draws(deps, tier, start_after, limit)?),
+        QueryMsg::RakebackEpoch { epoch } => {
+            to_json_binary(&crate::query::query_rakeback_epoch(deps, env, epoch)?)
+        }
+        QueryMsg::PlayerRakeback { address, epoch } => {
+            to_json_binary(&crate::query::query_player_rakeback(deps, env, address, epoch)?)
+        }
+        QueryMsg::Loyalty { address } => {
+            to_json_binary(&crate::query::query_loyalty(deps, address)?)
+        }
+        QueryMsg::EffectiveCommission { address } => {
+            to_json_binary(&crate::query::query_effective_commission(deps, address)?)
+        }
+        QueryMsg::PromoVoucher { id, player } => {
+            to_json_binary(&crate::query::query_promo_voucher(deps, id, player)?)
+        }
+        QueryMsg::Bonus { address } => to_json_binary(&crate::query::query_bonus(deps, address)?),
+        QueryMsg::PlayerLimits { address } => {
+            to_json_binary(&crate::query::query_player_limits(deps, env, address)?)
+        }
+        QueryMsg::PairExposure {
+            address,
+            day,
+            start_after,
+            limit,
+        } => to_json_binary(&crate::query::query_pair_exposure(
+            deps,
+            env,
+            address,
+            day,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::QuoteFee { amount, player } => {
+            to_json_binary(&crate::query::query_quote_fee(deps, env, amount, player)?)
+        }
+        QueryMsg::Hooks {} => to_json_binary(&crate::query::query_hooks(deps)?),
+        QueryMsg::GlobalStats {} => to_json_binary(&crate::query::query_global_stats(deps)?),
+        QueryMsg::StatsSeries {
+            granularity,
+            from,
+            to,
+        } => to_json_binary(&crate::query::query_stats_series(deps, granularity, from, to)?),
+        QueryMsg::Leaderboard {
+            metric,
+            period,
+            start_after,
+            limit,
+        } => to_json_binary(&crate::query::query_leaderboard(
+            deps,
+            env,
+            metric,
+            period,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::MigrationStatus {} => {
+            to_json_binary(&crate::query::query_migration_status(deps)?)
+        }
+        QueryMsg::ExportState { start_after, limit } => {
+            to_json_binary(&crate::query::query_export_state(deps, start_after, limit)?)
+        }
+        QueryMsg::ExportSummary { start_after, limit } => {
+            to_json_binary(&crate::query::query_export_summary(deps, start_after, limit)?)
+        }
+        QueryMsg::ImportStatus { source_contract } => {
+            to_json_binary(&crate::query::query_import_status(deps, source_contract)?)
+        }
+        QueryMsg::PendingWithdrawals {
+            address,
+            start_after,
+            limit,
+        } => to_json_binary(&crate::query::query_pending_withdrawals(
+            deps,
+            address,
+            start_after,
+            limit,
+        )?),
     }
 }
@@ -59,4 +59,195 @@ pub enum ContractError {
 
     #[error("No orphaned tokens to sweep")]
     NothingToSweep,
+
+    #[error("Pending withdrawal not found: {id}")]
+    WithdrawalNotFound { id: u64 },
+
+    #[error("Withdrawal {id} is locked until timestamp {unlocks_at}")]
+    WithdrawalLocked { id: u64, unlocks_at: u64 },
+
+    #[error("Withdrawal {id} is frozen")]
+    WithdrawalFrozen { id: u64 },
+
+    #[error("Withdrawal {id} unlocked at timestamp {unlocks_at} and can no longer be frozen")]
+    WithdrawalUnlocked { id: u64, unlocks_at: u64 },
+
+    #[error("Invalid conversion rate: numerator and denominator must be non-zero")]
+    InvalidRate,
+
+    #[error("Import source not configured: {source_contract}")]
+    ImportSourceNotConfigured { source_contract: String },
+
+    #[error("Checksum mismatch: expected {expected}, computed {computed}")]
+    ChecksumMismatch { expected: String, computed: String },
+
+    #[error("Balance of {address} was already imported from this source")]
+    AlreadyImported { address: String },
+
+    #[error("Import source {source_contract} has not finalized its balance export")]
+    ImportSourceNotFinalized { source_contract: String },
+
+    #[error("Import entry {index} does not match the source's export")]
+    ImportEntryMismatch { index: u64 },
+
+    #[error("Contract is in emergency shutdown: only withdrawals are allowed")]
+    ContractShutdown,
+
+    #[error("Emergency shutdown already active")]
+    ShutdownAlreadyActive,
+
+    #[error("Emergency shutdown is not active")]
+    ShutdownNotActive,
+
+    #[error("Emergency shutdown already completed: all live bets refunded")]
+    ShutdownAlreadyCompleted,
+
+    #[error("Emergency shutdown has not refunded every live bet yet")]
+    ShutdownNotCompleted,
+
+    #[error("Balances were finalized for export: they can no longer be moved")]
+    ExportFinalized,
+
+    #[error("State reset in progress: call ContinueMigration until it completes")]
+    MigrationInProgress,
+
+    #[error("No state reset in progress")]
+    NoMigrationInProgress,
+
+    #[error("Contract is paused by governance")]
+    Paused,
+
+    #[error("Bet retention must be at least {min} seconds")]
+    InvalidRetention { min: u64 },
+
+    #[error("Hook gas limit must be between {min} and {max}")]
+    InvalidHookGasLimit { min: u64, max: u64 },
+
+    #[error("Too many hooks: max {max}")]
+    TooManyHooks { max: u32 },
+
+    #[error("Hook not registered: {contract}")]
+    HookNotFound { contract: String },
+
+    #[error("Invalid commission split: {reason}")]
+    InvalidCommissionSplit { reason: String },
+
+    #[error("No accrued fees can be flushed")]
+    NothingToFlush,
+
+    #[error("Referrer already set")]
+    ReferrerAlreadySet,
+
+    #[error("Referrer would create a referral cycle")]
+    ReferralCycle,
+
+    #[error("Invalid referral bps: {reason}")]
+    InvalidReferralBps { reason: String },
+
+    #[error("Invalid jackpot tiers: {reason}")]
+    InvalidJackpotTiers { reason: String },
+
+    #[error("Jackpot tier not found: {tier}")]
+    JackpotTierNotFound { tier: u8 },
+
+    #[error("Jackpot seed already committed for tier {tier}")]
+    JackpotSeedAlreadyCommitted { tier: u8 },
+
+    #[error("Jackpot pool of tier {tier} is not ready to be drawn")]
+    JackpotNotFilled { tier: u8 },
+
+    #[error("Jackpot draw of tier {tier} waits for the entropy of blocks up to height {height}")]
+    JackpotEntropyPending { tier: u8, height: u64 },
+
+    #[error("Jackpot seed of tier {tier} was due by {deadline}; only the fallback draw is left")]
+    JackpotRevealExpired { tier: u8, deadline: u64 },
+
+    #[error("Jackpot seed of tier {tier} can still be revealed until {deadline}")]
+    JackpotRevealPending { tier: u8, deadline: u64 },
+
+    #[error("Jackpot cycle {cycle} of tier {tier} has not been drawn yet")]
+    JackpotCycleNotFinished { tier: u8, cycle: u64 },
+
+    #[error("Invalid rakeback settings: {reason}")]
+    InvalidRakeback { reason: String },
+
+    #[error("Rakeback epoch {epoch} has not ended yet")]
+    RakebackEpochNotOver { epoch: u64 },
+
+    #[error("Rakeback claim window of epoch {epoch} has closed")]
+    RakebackClaimExpired { epoch: u64 },
+
+    #[error("Rakeback of epoch {epoch} can still be claimed")]
+    RakebackClaimWindowOpen { epoch: u64 },
+
+    #[error("No rakeback to settle for epoch {epoch}")]
+    NoRakeback { epoch: u64 },
+
+    #[error("Invalid loyalty settings: {reason}")]
+    InvalidLoyalty { reason: String },
+
+    #[error("Fee discounts cannot be redeemed")]
+    FeeDiscountsDisabled,
+
+    #[error("Fee discount must cover at least one bet")]
+    ZeroDiscountBets,
+
+    #[error("Insufficient XP: need {need}, have {have}")]
+    InsufficientXp { need: u64, have: u64 },
+
+    #[error("Fee discounts at {bps} bps must be used up before redeeming at another rate")]
+    FeeDiscountActive { bps: u16 },
+
+    #[error("Invalid stake discounts: {reason}")]
+    InvalidStakeDiscounts { reason: String },
+
+    #[error("Invalid fee schedule: {reason}")]
+    InvalidFeeSchedule { reason: String },
+
+    #[error("Promo vouchers are not enabled")]
+    PromosDisabled,
+
+    #[error("Invalid promo public key: must be 33 or 65 bytes, got {len}")]
+    InvalidPromoKey { len: usize },
+
+    #[error("Invalid promo voucher: {reason}")]
+    InvalidVoucher { reason: String },
+
+    #[error("Promo voucher {id} has expired")]
+    VoucherExpired { id: String },
+
+    #[error("Promo voucher {id} has no uses left")]
+    VoucherExhausted { id: String },
+
+    #[error("Promo voucher {id} already redeemed")]
+    VoucherAlreadyRedeemed { id: String },
+
+    #[error("Promo voucher {id} is for another player")]
+    VoucherNotForPlayer { id: String },
+
+    #[error("Treasury balance cannot fund the promo bonus: need {need}, have {have}")]
+    PromoBonusUnfunded { need: String, have: String },
+
+    #[error("Invalid bonus wagering multiple: max {max}")]
+    InvalidWageringMultiple { max: u32 },
+
+    #[error("{player} is in a cooldown until {until}")]
+    PlayerCooldown { player: String, until: u64 },
+
+    #[error("Bet is above the max bet of {max} set by {player}")]
+    AboveMaxBet { player: String, max: String },
+
+    #[error("Daily loss limit of {limit} set by {player}: lost {lost}, {at_stake} at stake")]
+    DailyLossLimit {
+        player: String,
+        limit: String,
+        lost: String,
+        at_stake: String,
+    },
+
+    #[error("Invalid pair caps: {reason}")]
+    InvalidPairCaps { reason: String },
+
+    #[error("{maker} and {acceptor} have reached today's pair cap")]
+    PairCapReached { maker: String, acceptor: String },
 }
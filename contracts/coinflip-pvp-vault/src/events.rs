@@ -0,0 +1,117 @@
+//! Typed events for indexers.
+//!
+//! Every state change emits one or more `coinflip_*` events; the chain prefixes
+//! custom event types with `wasm-`, so they arrive as e.g. `wasm-coinflip_bet_created`.
+//! Each event type always carries the same attributes. Balance changes are reported
+//! separately as `coinflip_balance_changed`, one per touched address, with the
+//! values before and after the change.
+//!
+//! The legacy `action` attributes on the `wasm` event are kept alongside.
+
+use cosmwasm_std::{Addr, Event, Uint128};
+
+use crate::msg::Side;
+use crate::state::{Bet, VaultBalance};
+
+/// Balance of `address` before and after a write (see `state::save_balance`).
+pub fn balance_changed(address: &Addr, before: &VaultBalance, after: &VaultBalance) -> Event {
+    Event::new("coinflip_balance_changed")
+        .add_attribute("address", address.to_string())
+        .add_attribute("available_before", before.available.to_string())
+        .add_attribute("available_after", after.available.to_string())
+        .add_attribute("locked_before", before.locked.to_string())
+        .add_attribute("locked_after", after.locked.to_string())
+        .add_attribute("pending_withdrawal_before", before.pending_withdrawal.to_string())
+        .add_attribute("pending_withdrawal_after", after.pending_withdrawal.to_string())
+        .add_attribute("bonus_before", before.bonus.to_string())
+        .add_attribute("bonus_after", after.bonus.to_string())
+}
+
+pub fn bet_created(bet: &Bet) -> Event {
+    Event::new("coinflip_bet_created")
+        .add_attribute("bet_id", bet.id.to_string())
+        .add_attribute("maker", bet.maker.to_string())
+        .add_attribute("amount", bet.amount.to_string())
+        .add_attribute("commitment", bet.commitment.to_base64())
+}
+
+/// Emitted for `AcceptBet` and for the accept half of `AcceptAndReveal`.
+pub fn bet_accepted(bet: &Bet, acceptor: &Addr, guess: &Side) -> Event {
+    Event::new("coinflip_bet_accepted")
+        .add_attribute("bet_id", bet.id.to_string())
+        .add_attribute("maker", bet.maker.to_string())
+        .add_attribute("acceptor", acceptor.to_string())
+        .add_attribute("amount", bet.amount.to_string())
+        .add_attribute("guess", side_str(guess))
+}
+
+pub fn bet_canceled(bet: &Bet) -> Event {
+    Event::new("coinflip_bet_canceled")
+        .add_attribute("bet_id", bet.id.to_string())
+        .add_attribute("maker", bet.maker.to_string())
+        .add_attribute("amount", bet.amount.to_string())
+}
+
+/// Emitted when a bet is resolved by `Reveal` or `AcceptAndReveal`.
+pub fn bet_revealed(
+    bet: &Bet,
+    acceptor: &Addr,
+    guess: &Side,
+    side: &Side,
+    treasury: &Addr,
+) -> Event {
+    resolution("coinflip_bet_revealed", bet, acceptor, treasury)
+        .add_attribute("guess", side_str(guess))
+        .add_attribute("side", side_str(side))
+}
+
+/// Emitted when the acceptor wins by `ClaimTimeout`.
+pub fn bet_timeout_claimed(bet: &Bet, acceptor: &Addr, treasury: &Addr) -> Event {
+    resolution("coinflip_bet_timeout_claimed", bet, acceptor, treasury)
+}
+
+/// Attributes shared by both resolution events.
+fn resolution(ty: &str, bet: &Bet, acceptor: &Addr, treasury: &Addr) -> Event {
+    let winner = bet.payout_winner.as_ref().map(|w| w.to_string()).unwrap_or_default();
+    Event::new(ty)
+        .add_attribute("bet_id", bet.id.to_string())
+        .add_attribute("maker", bet.maker.to_string())
+        .add_attribute("acceptor", acceptor.to_string())
+        .add_attribute("amount", bet.amount.to_string())
+        .add_attribute("winner", winner)
+        .add_attribute("payout", bet.payout_amount.to_string())
+        .add_attribute("commission", bet.commission_paid.to_string())
+        .add_attribute("treasury", treasury.to_string())
+}
+
+/// Emitted per bet refunded by `ProcessShutdown`.
+pub fn bet_refunded(bet: &Bet) -> Event {
+    let acceptor = bet.acceptor.as_ref().map(|a| a.to_string()).unwrap_or_default();
+    Event::new("coinflip_bet_refunded")
+        .add_attribute("bet_id", bet.id.to_string())
+        .add_attribute("maker", bet.maker.to_string())
+        .add_attribute("acceptor", acceptor)
+        .add_attribute("amount", bet.amount.to_string())
+}
+
+/// Vault token moved between a user and the contract (deposit, withdraw, admin payout).
+pub fn transfer(ty: &str, address: &Addr, amount: Uint128) -> Event {
+    Event::new(ty)
+        .add_attribute("address", address.to_string())
+        .add_attribute("amount", amount.to_string())
+}
+
+/// Lifecycle of a delayed withdrawal: requested, claimed, canceled.
+pub fn withdrawal(ty: &str, withdrawal_id: u64, address: &Addr, amount: Uint128) -> Event {
+    Event::new(ty)
+        .add_attribute("withdrawal_id", withdrawal_id.to_string())
+        .add_attribute("address", address.to_string())
+        .add_attribute("amount", amount.to_string())
+}
+
+fn side_str(side: &Side) -> &'static str {
+    match side {
+        Side::Heads => "heads",
+        Side::Tails => "tails",
+    }
+}
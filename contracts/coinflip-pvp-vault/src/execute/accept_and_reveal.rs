@@ -1,9 +1,16 @@
-use cosmwasm_std::{Binary, DepsMut, Env, MessageInfo, Response, Uint128};
+use cosmwasm_std::{to_json_binary, Binary, DepsMut, Env, MessageInfo, Response};
 use sha2::{Digest, Sha256};
 
 use crate::error::ContractError;
-use crate::msg::Side;
-use crate::state::{BetStatus, BETS, CONFIG, USER_OPEN_BET_COUNT, VAULT_BALANCES};
+use crate::events;
+use crate::execute::bonus::lock_stake;
+use crate::execute::limits::check_limits;
+use crate::execute::pairs::record_pair_game;
+use crate::execute::promo::redeem_voucher;
+use crate::execute::settle::settle_bet;
+use crate::msg::{BetOutcomeResponse, PromoVoucher, Side};
+use crate::state::{BetStatus, BETS, CONFIG, VAULT_BALANCES};
+use crate::stats;
 
 /// Accept a bet AND reveal the maker's secret in a single atomic transaction.
 ///
@@ -13,6 +20,7 @@ use crate::state::{BetStatus, BETS, CONFIG, USER_OPEN_BET_COUNT, VAULT_BALANCES}
 /// The caller (info.sender) is the acceptor.
 /// The maker's secret is verified via commitment — no sender == maker check needed
 /// because SHA256(secret) == commitment IS the authorization proof.
+#[allow(clippy::too_many_arguments)]
 pub fn execute_accept_and_reveal(
     deps: DepsMut,
     env: Env,
@@ -21,6 +29,7 @@ pub fn execute_accept_and_reveal(
     guess: Side,
     side: Side,
     secret: Binary,
+    voucher: Option<PromoVoucher>,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
     let mut bet = BETS
@@ -53,17 +62,33 @@ pub fn execute_accept_and_reveal(
         return Err(ContractError::SelfAcceptNotAllowed);
     }
 
+    // Redeem the voucher first: a bonus can fund the stake
+    let mut promo_events = vec![];
+    if let Some(voucher) = voucher {
+        let (discount, events) =
+            redeem_voucher(deps.storage, deps.api, &env, &config, &info.sender, bet_id, voucher)?;
+        bet.promo_discounts.extend(discount);
+        promo_events = events;
+    }
+
     // Check acceptor balance
     let mut acceptor_bal = VAULT_BALANCES
         .may_load(deps.storage, &info.sender)?
         .unwrap_or_default();
-    if acceptor_bal.available < bet.amount {
+    if acceptor_bal.available + acceptor_bal.bonus < bet.amount {
         return Err(ContractError::InsufficientAvailableBalance {
             need: bet.amount.to_string(),
-            have: acceptor_bal.available.to_string(),
+            have: (acceptor_bal.available + acceptor_bal.bonus).to_string(),
         });
     }
 
+    // Check both players' own limits; the maker's may have tightened since the bet was made
+    let time = env.block.time.seconds();
+    check_limits(deps.storage, &info.sender, time, bet.amount, false)?;
+    check_limits(deps.storage, &bet.maker, time, bet.amount, true)?;
+    let pair_events =
+        record_pair_game(deps.storage, config.pair_caps.as_ref(), &mut bet, &info.sender, time)?;
+
     // ─── Reveal checks ──────────────────────────────────────────
 
     // Verify commitment: SHA256("coinflip_v1" || maker_addr || side || secret)
@@ -93,57 +118,19 @@ pub fn execute_accept_and_reveal(
         info.sender.clone()
     };
 
-    // ─── Compute payouts ─────────────────────────────────────────
-
-    let pot = bet.amount * Uint128::new(2);
-    let commission = pot * Uint128::from(config.commission_bps) / Uint128::new(10_000);
-    let payout = pot - commission;
-
-    // ─── Update balances ─────────────────────────────────────────
-
-    // Lock acceptor funds, then unlock both + credit winner
-    acceptor_bal.available -= bet.amount;
-    // acceptor_bal.locked stays the same (lock and unlock cancel out atomically)
-
-    let mut maker_bal = VAULT_BALANCES.load(deps.storage, &bet.maker)?;
-    maker_bal.locked -= bet.amount;
-
-    if maker_wins {
-        maker_bal.available += payout;
-    } else {
-        acceptor_bal.available += payout;
-    }
-
-    VAULT_BALANCES.save(deps.storage, &bet.maker, &maker_bal)?;
-    VAULT_BALANCES.save(deps.storage, &info.sender, &acceptor_bal)?;
-
-    // Credit treasury
-    let mut treasury_bal = VAULT_BALANCES
-        .may_load(deps.storage, &config.treasury)?
-        .unwrap_or_default();
-    treasury_bal.available += commission;
-    VAULT_BALANCES.save(deps.storage, &config.treasury, &treasury_bal)?;
-
-    // Decrement maker's open bet count
-    let open_count = USER_OPEN_BET_COUNT
-        .may_load(deps.storage, &bet.maker)?
-        .unwrap_or(0);
-    USER_OPEN_BET_COUNT.save(deps.storage, &bet.maker, &open_count.saturating_sub(1))?;
-
-    // ─── Update bet state (Open → Revealed, skip Accepted) ──────
+    // ─── Settle (Open → Revealed, skip Accepted) ────────────────
 
-    bet.status = BetStatus::Revealed;
+    // Lock acceptor funds; settling unlocks both and credits the winner
+    bet.acceptor_bonus = lock_stake(deps.storage, &info.sender, &mut acceptor_bal, bet.amount)?;
     bet.acceptor = Some(info.sender.clone());
     bet.acceptor_guess = Some(guess.clone());
     bet.accepted_at_height = Some(env.block.height);
     bet.accepted_at_time = Some(env.block.time.seconds());
     bet.reveal_secret = Some(secret);
     bet.reveal_side = Some(side.clone());
-    bet.resolved_at_height = Some(env.block.height);
-    bet.payout_winner = Some(winner.clone());
-    bet.commission_paid = commission;
-    bet.payout_amount = payout;
-    BETS.save(deps.storage, bet_id, &bet)?;
+    stats::record_player(deps.storage, &info.sender, env.block.time.seconds())?;
+    let settlement =
+        settle_bet(deps, &env, &config, &mut bet, acceptor_bal, &winner, BetStatus::Revealed)?;
 
     Ok(Response::new()
         .add_attribute("action", "coinflip.accept_and_reveal")
@@ -152,7 +139,14 @@ pub fn execute_accept_and_reveal(
         .add_attribute("guess", format!("{:?}", guess))
         .add_attribute("side", format!("{:?}", side))
         .add_attribute("winner", winner.to_string())
-        .add_attribute("payout", payout.to_string())
-        .add_attribute("commission", commission.to_string())
-        .add_attribute("treasury", config.treasury.to_string()))
+        .add_attribute("payout", settlement.payout.to_string())
+        .add_attribute("commission", settlement.commission.to_string())
+        .add_attribute("treasury", config.treasury.to_string())
+        .add_event(events::bet_accepted(&bet, &info.sender, &guess))
+        .add_event(events::bet_revealed(&bet, &info.sender, &guess, &side, &config.treasury))
+        .add_events(promo_events)
+        .add_events(pair_events)
+        .add_events(settlement.events)
+        .add_submessages(settlement.hooks)
+        .set_data(to_json_binary(&BetOutcomeResponse::from_bet(&bet))?))
 }
@@ -1,8 +1,14 @@
 use cosmwasm_std::{DepsMut, Env, MessageInfo, Response};
 
 use crate::error::ContractError;
-use crate::msg::Side;
-use crate::state::{BetStatus, BETS, CONFIG, VAULT_BALANCES};
+use crate::events;
+use crate::execute::bonus::lock_stake;
+use crate::execute::limits::check_limits;
+use crate::execute::pairs::record_pair_game;
+use crate::execute::promo::redeem_voucher;
+use crate::msg::{PromoVoucher, Side};
+use crate::state::{save_balance, BetStatus, BETS, CONFIG, VAULT_BALANCES};
+use crate::stats;
 
 pub fn execute_accept_bet(
     deps: DepsMut,
@@ -10,6 +16,7 @@ pub fn execute_accept_bet(
     info: MessageInfo,
     bet_id: u64,
     guess: Side,
+    voucher: Option<PromoVoucher>,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
     let mut bet = BETS.load(deps.storage, bet_id).map_err(|_| ContractError::BetNotFound { id: bet_id })?;
@@ -38,21 +45,36 @@ pub fn execute_accept_bet(
         return Err(ContractError::SelfAcceptNotAllowed);
     }
 
+    // Redeem the voucher first: a bonus can fund the stake
+    let mut promo_events = vec![];
+    if let Some(voucher) = voucher {
+        let (discount, events) =
+            redeem_voucher(deps.storage, deps.api, &env, &config, &info.sender, bet_id, voucher)?;
+        bet.promo_discounts.extend(discount);
+        promo_events = events;
+    }
+
     // Check acceptor balance
     let mut balance = VAULT_BALANCES
         .may_load(deps.storage, &info.sender)?
         .unwrap_or_default();
-    if balance.available < bet.amount {
+    if balance.available + balance.bonus < bet.amount {
         return Err(ContractError::InsufficientAvailableBalance {
             need: bet.amount.to_string(),
-            have: balance.available.to_string(),
+            have: (balance.available + balance.bonus).to_string(),
         });
     }
 
-    // Lock acceptor funds
-    balance.available -= bet.amount;
-    balance.locked += bet.amount;
-    VAULT_BALANCES.save(deps.storage, &info.sender, &balance)?;
+    // Check both players' own limits; the maker's may have tightened since the bet was made
+    let time = env.block.time.seconds();
+    check_limits(deps.storage, &info.sender, time, bet.amount, false)?;
+    check_limits(deps.storage, &bet.maker, time, bet.amount, true)?;
+    let pair_events =
+        record_pair_game(deps.storage, config.pair_caps.as_ref(), &mut bet, &info.sender, time)?;
+
+    // Lock acceptor funds, bonus first
+    bet.acceptor_bonus = lock_stake(deps.storage, &info.sender, &mut balance, bet.amount)?;
+    let balance_event = save_balance(deps.storage, &info.sender, &balance)?;
 
     // Update bet
     bet.status = BetStatus::Accepted;
@@ -61,10 +83,15 @@ pub fn execute_accept_bet(
     bet.accepted_at_height = Some(env.block.height);
     bet.accepted_at_time = Some(env.block.time.seconds());
     BETS.save(deps.storage, bet_id, &bet)?;
+    stats::record_player(deps.storage, &info.sender, env.block.time.seconds())?;
 
     Ok(Response::new()
         .add_attribute("action", "coinflip.bet_accepted")
         .add_attribute("bet_id", bet_id.to_string())
         .add_attribute("acceptor", info.sender.to_string())
-        .add_attribute("guess", format!("{:?}", guess)))
+        .add_attribute("guess", format!("{:?}", guess))
+        .add_event(events::bet_accepted(&bet, &info.sender, &guess))
+        .add_events(promo_events)
+        .add_events(pair_events)
+        .add_event(balance_event))
 }
@@ -1,13 +1,25 @@
 use cosmwasm_std::{
-    to_json_binary, CosmosMsg, DepsMut, Env, MessageInfo, Order, Response, Uint128, WasmMsg,
+    to_json_binary, CosmosMsg, Deps, DepsMut, Env, Event, MessageInfo, Response, StdResult,
+    Uint128, WasmMsg,
 };
 use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg};
 
 use crate::error::ContractError;
-use crate::state::{CONFIG, VAULT_BALANCES};
+use crate::state::{Config, CONFIG, VAULT_TOTALS};
+
+/// CW20 balance held by this contract.
+pub fn contract_balance(deps: Deps, env: &Env, config: &Config) -> StdResult<Uint128> {
+    let cw20_balance: BalanceResponse = deps.querier.query_wasm_smart(
+        config.token_cw20.to_string(),
+        &Cw20QueryMsg::Balance {
+            address: env.contract.address.to_string(),
+        },
+    )?;
+    Ok(cw20_balance.balance)
+}
 
 /// Admin-only: sweep orphaned CW20 tokens from the contract.
-/// Calculates: contract CW20 balance - sum(all vault available + locked).
+/// Calculates: contract CW20 balance - total owed to users (available + locked + pending).
 /// Sends the difference to `recipient` (defaults to admin).
 pub fn execute_admin_sweep(
     deps: DepsMut,
@@ -20,25 +32,11 @@ pub fn execute_admin_sweep(
         return Err(ContractError::Unauthorized);
     }
 
-    // Query the CW20 balance held by this contract
-    let cw20_balance: BalanceResponse = deps.querier.query_wasm_smart(
-        config.token_cw20.to_string(),
-        &Cw20QueryMsg::Balance {
-            address: env.contract.address.to_string(),
-        },
-    )?;
-
-    // Sum all vault balances (available + locked)
-    let mut total_vault = Uint128::zero();
-    for item in VAULT_BALANCES.range(deps.storage, None, None, Order::Ascending) {
-        let (_, balance) = item?;
-        total_vault += balance.available;
-        total_vault += balance.locked;
-    }
+    let contract_balance = contract_balance(deps.as_ref(), &env, &config)?;
+    let total_vault = VAULT_TOTALS.load(deps.storage)?.total_owed();
 
     // Orphaned = CW20 balance on contract - total tracked in vaults
-    let orphaned = cw20_balance
-        .balance
+    let orphaned = contract_balance
         .checked_sub(total_vault)
         .unwrap_or(Uint128::zero());
 
@@ -67,6 +65,13 @@ pub fn execute_admin_sweep(
         .add_attribute("action", "admin_sweep")
         .add_attribute("orphaned_amount", orphaned.to_string())
         .add_attribute("recipient", to.to_string())
-        .add_attribute("contract_balance", cw20_balance.balance.to_string())
-        .add_attribute("total_vault", total_vault.to_string()))
+        .add_attribute("contract_balance", contract_balance.to_string())
+        .add_attribute("total_vault", total_vault.to_string())
+        .add_event(
+            Event::new("coinflip_swept")
+                .add_attribute("recipient", to.to_string())
+                .add_attribute("amount", orphaned.to_string())
+                .add_attribute("contract_balance", contract_balance.to_string())
+                .add_attribute("total_owed", total_vault.to_string()),
+        ))
 }
@@ -0,0 +1,181 @@
+//! Non-withdrawable bonus balance and its wagering requirement.
+//!
+//! A bonus credited to a player, by the admin or a promo voucher, is paid from the
+//! treasury's vault balance and adds `bonus_wagering_multiple` times its amount to the
+//! player's wagering requirement. Stakes are taken from the bonus first, and the share
+//! of a payout won with bonus stake goes back to the bonus. Every played bet counts its
+//! full stake toward the requirement; once it is met, the whole bonus converts to
+//! `available`. A player who loses all their bonus starts over with no requirement.
+
+use cosmwasm_std::{Addr, DepsMut, Event, MessageInfo, Response, StdResult, Storage, Uint128};
+
+use crate::error::ContractError;
+use crate::state::{
+    save_balance, BonusWagering, Config, VaultBalance, BONUS_WAGERING, CONFIG, VAULT_BALANCES,
+};
+
+pub const MAX_WAGERING_MULTIPLE: u32 = 100;
+
+/// Admin-only: set how many times a credited bonus must be wagered before it converts.
+pub fn execute_update_bonus_wagering(
+    deps: DepsMut,
+    info: MessageInfo,
+    multiple: u32,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized);
+    }
+    if multiple > MAX_WAGERING_MULTIPLE {
+        return Err(ContractError::InvalidWageringMultiple {
+            max: MAX_WAGERING_MULTIPLE,
+        });
+    }
+
+    config.bonus_wagering_multiple = multiple;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_bonus_wagering")
+        .add_attribute("multiple", multiple.to_string())
+        .add_event(
+            Event::new("coinflip_bonus_wagering_updated")
+                .add_attribute("multiple", multiple.to_string()),
+        ))
+}
+
+/// Admin-only: move `amount` from the treasury's available balance to `address`'s bonus.
+pub fn execute_credit_bonus(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized);
+    }
+    let player = deps.api.addr_validate(&address)?;
+    let events = credit_bonus(deps.storage, &config, &player, amount)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "credit_bonus")
+        .add_attribute("user", player.to_string())
+        .add_attribute("amount", amount.to_string())
+        .add_events(events))
+}
+
+/// Move `amount` from the treasury's available balance to `player`'s bonus and raise
+/// their wagering requirement.
+pub fn credit_bonus(
+    storage: &mut dyn Storage,
+    config: &Config,
+    player: &Addr,
+    amount: Uint128,
+) -> Result<Vec<Event>, ContractError> {
+    let mut treasury_bal = VAULT_BALANCES
+        .may_load(storage, &config.treasury)?
+        .unwrap_or_default();
+    if treasury_bal.available < amount {
+        return Err(ContractError::InsufficientAvailableBalance {
+            need: amount.to_string(),
+            have: treasury_bal.available.to_string(),
+        });
+    }
+    treasury_bal.available -= amount;
+    let mut events = vec![save_balance(storage, &config.treasury, &treasury_bal)?];
+
+    let mut wagering = BONUS_WAGERING.may_load(storage, player)?.unwrap_or_default();
+    wagering.required += amount * Uint128::from(config.bonus_wagering_multiple);
+    let mut balance = VAULT_BALANCES.may_load(storage, player)?.unwrap_or_default();
+    balance.bonus += amount;
+    events.push(
+        Event::new("coinflip_bonus_credited")
+            .add_attribute("user", player.to_string())
+            .add_attribute("amount", amount.to_string())
+            .add_attribute("wagering_required", wagering.required.to_string())
+            .add_attribute("wagered", wagering.wagered.to_string()),
+    );
+    events.extend(settle_wagering(storage, player, &mut balance, wagering)?);
+    events.push(save_balance(storage, player, &balance)?);
+    Ok(events)
+}
+
+/// Move a stake of `amount` to `locked`, taking it from the bonus first; returns the
+/// part taken from the bonus. The caller checks that `available + bonus` covers the
+/// stake and saves the balance.
+pub fn lock_stake(
+    storage: &mut dyn Storage,
+    player: &Addr,
+    balance: &mut VaultBalance,
+    amount: Uint128,
+) -> StdResult<Uint128> {
+    let from_bonus = amount.min(balance.bonus);
+    balance.bonus -= from_bonus;
+    balance.available -= amount - from_bonus;
+    balance.locked += amount;
+
+    if !from_bonus.is_zero() {
+        let mut wagering = BONUS_WAGERING.may_load(storage, player)?.unwrap_or_default();
+        wagering.locked += from_bonus;
+        BONUS_WAGERING.save(storage, player, &wagering)?;
+    }
+    Ok(from_bonus)
+}
+
+/// Unlock a stake of `stake`, of which `bonus_stake` came from the bonus, and credit
+/// `returned` (the payout, the refunded stake or nothing) in the same proportion to the
+/// bonus and `available`. A `played` stake counts toward the wagering requirement.
+/// The caller saves the balance.
+pub fn release_stake(
+    storage: &mut dyn Storage,
+    player: &Addr,
+    balance: &mut VaultBalance,
+    stake: Uint128,
+    bonus_stake: Uint128,
+    returned: Uint128,
+    played: bool,
+) -> StdResult<Vec<Event>> {
+    balance.locked = balance.locked.checked_sub(stake)?;
+    let to_bonus = returned.multiply_ratio(bonus_stake, stake);
+    balance.bonus += to_bonus;
+    balance.available += returned - to_bonus;
+
+    let Some(mut wagering) = BONUS_WAGERING.may_load(storage, player)? else {
+        return Ok(vec![]);
+    };
+    wagering.locked = wagering.locked.checked_sub(bonus_stake)?;
+    if played {
+        wagering.wagered += stake;
+    }
+    settle_wagering(storage, player, balance, wagering)
+}
+
+/// Convert the bonus to `available` once the requirement is met, and drop the
+/// requirement once no bonus is left.
+fn settle_wagering(
+    storage: &mut dyn Storage,
+    player: &Addr,
+    balance: &mut VaultBalance,
+    mut wagering: BonusWagering,
+) -> StdResult<Vec<Event>> {
+    let mut events = vec![];
+    if wagering.wagered >= wagering.required && !balance.bonus.is_zero() {
+        events.push(
+            Event::new("coinflip_bonus_converted")
+                .add_attribute("user", player.to_string())
+                .add_attribute("amount", balance.bonus.to_string())
+                .add_attribute("wagered", wagering.wagered.to_string()),
+        );
+        balance.available += balance.bonus;
+        balance.bonus = Uint128::zero();
+        wagering.required = Uint128::zero();
+        wagering.wagered = Uint128::zero();
+    }
+    if balance.bonus.is_zero() && wagering.locked.is_zero() {
+        BONUS_WAGERING.remove(storage, player);
+    } else {
+        BONUS_WAGERING.save(storage, player, &wagering)?;
+    }
+    Ok(events)
+}
@@ -1,11 +1,15 @@
 use cosmwasm_std::{DepsMut, Env, MessageInfo, Response};
 
 use crate::error::ContractError;
-use crate::state::{BetStatus, BETS, USER_OPEN_BET_COUNT, VAULT_BALANCES};
+use crate::events;
+use crate::execute::bonus::release_stake;
+use crate::execute::promo::refund_vouchers;
+use crate::state::{save_balance, BetStatus, BETS, USER_OPEN_BET_COUNT, VAULT_BALANCES};
+use crate::stats;
 
 pub fn execute_cancel_bet(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     bet_id: u64,
 ) -> Result<Response, ContractError> {
@@ -26,9 +30,16 @@ pub fn execute_cancel_bet(
 
     // Unlock funds back to maker
     let mut balance = VAULT_BALANCES.load(deps.storage, &bet.maker)?;
-    balance.locked -= bet.amount;
-    balance.available += bet.amount;
-    VAULT_BALANCES.save(deps.storage, &bet.maker, &balance)?;
+    let bonus_events = release_stake(
+        deps.storage,
+        &bet.maker,
+        &mut balance,
+        bet.amount,
+        bet.maker_bonus,
+        bet.amount,
+        false,
+    )?;
+    let balance_event = save_balance(deps.storage, &bet.maker, &balance)?;
 
     // Decrement maker's open bets count
     let open_count = USER_OPEN_BET_COUNT
@@ -36,11 +47,19 @@ pub fn execute_cancel_bet(
         .unwrap_or(0);
     USER_OPEN_BET_COUNT.save(deps.storage, &bet.maker, &open_count.saturating_sub(1))?;
 
+    // The bet was never played: its discount vouchers can be redeemed again
+    let promo_events = refund_vouchers(deps.storage, &bet)?;
+
     // Update bet status
     bet.status = BetStatus::Canceled;
+    stats::record_bet(deps.storage, &mut bet, Some(env.block.time.seconds()))?;
     BETS.save(deps.storage, bet_id, &bet)?;
 
     Ok(Response::new()
         .add_attribute("action", "coinflip.bet_canceled")
-        .add_attribute("bet_id", bet_id.to_string()))
+        .add_attribute("bet_id", bet_id.to_string())
+        .add_event(events::bet_canceled(&bet))
+        .add_events(bonus_events)
+        .add_events(promo_events)
+        .add_event(balance_event))
 }
@@ -0,0 +1,392 @@
+use cosmwasm_std::{DepsMut, Env, Event, MessageInfo, Order, Response, StdResult, Storage};
+use cw_storage_plus::Bound;
+
+use crate::error::ContractError;
+use crate::state::{
+    ResetPhase, ResetProgress, VaultTotals, ACCRUED_FEES, BETS, BONUS_WAGERING, DAILY_LOSS,
+    DAILY_STATS, DAILY_USAGE, JACKPOT_DRAWS, JACKPOT_ENTRIES, JACKPOT_POOLS, JACKPOT_RANGES,
+    LEADERBOARD, LOYALTY, NEXT_BET_ID, PAIR_STATS, PENDING_WITHDRAWALS, PERIOD_STATS, PLAYER_STATS,
+    RAKEBACK, RAKEBACK_EPOCHS, RECENT_WITHDRAWALS, REFERRAL_EARNINGS, RESET_PROGRESS,
+    SERIES_PLAYERS, STATS_SERIES, TOTALS_BACKFILL, USER_OPEN_BET_COUNT, VAULT_BALANCES,
+    VAULT_TOTALS,
+};
+
+/// Permissionless: continue a state reset started by `migrate { reset_state: true }`,
+/// or the vault totals backfill started by the v0.7.0 migration.
+///
+/// Deletes at most `limit` entries per call, walking the maps phase by phase.
+/// Because cleared entries are removed, each phase simply restarts from the
+/// first remaining key — the stored phase is the cursor. When the last phase
+/// is empty the bet counter is reset and normal operations resume.
+pub fn execute_continue_migration(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let limit = limit.unwrap_or(100).clamp(1, 500) as usize;
+    let mut progress = match RESET_PROGRESS.may_load(deps.storage)? {
+        Some(progress) => progress,
+        None => return continue_totals_backfill(deps.storage, limit),
+    };
+
+    let mut budget = limit;
+    let mut completed = false;
+
+    while budget > 0 {
+        let cleared = clear_batch(deps.storage, &progress.phase, budget)?;
+        progress.cleared_entries += cleared as u64;
+        budget -= cleared;
+
+        // A short batch means the current map is empty
+        if budget > 0 {
+            match progress.phase.next() {
+                Some(next) => progress.phase = next,
+                None => {
+                    completed = true;
+                    break;
+                }
+            }
+        }
+    }
+
+    if completed {
+        NEXT_BET_ID.save(deps.storage, &1u64)?;
+        VAULT_TOTALS.save(deps.storage, &VaultTotals::default())?;
+        RESET_PROGRESS.remove(deps.storage);
+    } else {
+        RESET_PROGRESS.save(deps.storage, &progress)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "continue_migration")
+        .add_attribute("cleared", (limit - budget).to_string())
+        .add_attribute("cleared_entries", progress.cleared_entries.to_string())
+        .add_attribute("phase", format!("{:?}", progress.phase))
+        .add_attribute("completed", completed.to_string())
+        .add_event(
+            Event::new("coinflip_migration_progress")
+                .add_attribute("cleared", (limit - budget).to_string())
+                .add_attribute("cleared_entries", progress.cleared_entries.to_string())
+                .add_attribute("phase", format!("{:?}", progress.phase))
+                .add_attribute("completed", completed.to_string()),
+        ))
+}
+
+/// Count the next `limit` balances into the totals backfill. Balances cannot change
+/// while it runs, so the sums match `VAULT_BALANCES` once the last balance is counted.
+fn continue_totals_backfill(
+    storage: &mut dyn Storage,
+    limit: usize,
+) -> Result<Response, ContractError> {
+    let mut backfill = TOTALS_BACKFILL
+        .may_load(storage)?
+        .ok_or(ContractError::NoMigrationInProgress)?;
+
+    let balances = VAULT_BALANCES
+        .range(
+            storage,
+            backfill.last_counted.as_ref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (_, balance) in &balances {
+        backfill.totals.total_available += balance.available;
+        backfill.totals.total_locked += balance.locked;
+        backfill.totals.total_pending_withdrawal += balance.pending_withdrawal;
+        backfill.totals.total_bonus += balance.bonus;
+    }
+    backfill.counted_balances += balances.len() as u64;
+    if let Some((addr, _)) = balances.last() {
+        backfill.last_counted = Some(addr.clone());
+    }
+
+    // A short batch means every balance has been counted
+    let completed = balances.len() < limit;
+    if completed {
+        VAULT_TOTALS.save(storage, &backfill.totals)?;
+        TOTALS_BACKFILL.remove(storage);
+    } else {
+        TOTALS_BACKFILL.save(storage, &backfill)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "continue_migration")
+        .add_attribute("counted", balances.len().to_string())
+        .add_attribute("counted_balances", backfill.counted_balances.to_string())
+        .add_attribute("phase", "TotalsBackfill")
+        .add_attribute("completed", completed.to_string())
+        .add_event(
+            Event::new("coinflip_migration_progress")
+                .add_attribute("counted", balances.len().to_string())
+                .add_attribute("counted_balances", backfill.counted_balances.to_string())
+                .add_attribute("phase", "TotalsBackfill")
+                .add_attribute("completed", completed.to_string()),
+        ))
+}
+
+/// Remove up to `limit` entries of the map belonging to `phase`; returns how many were removed.
+fn clear_batch(storage: &mut dyn Storage, phase: &ResetPhase, limit: usize) -> StdResult<usize> {
+    let cleared = match phase {
+        ResetPhase::VaultBalances => {
+            let keys = VAULT_BALANCES
+                .keys(storage, None, None, Order::Ascending)
+                .take(limit)
+                .collect::<StdResult<Vec<_>>>()?;
+            for key in &keys {
+                VAULT_BALANCES.remove(storage, key);
+            }
+            keys.len()
+        }
+        ResetPhase::Bets => {
+            let keys = BETS
+                .keys(storage, None, None, Order::Ascending)
+                .take(limit)
+                .collect::<StdResult<Vec<_>>>()?;
+            for key in &keys {
+                BETS.remove(storage, *key);
+            }
+            keys.len()
+        }
+        ResetPhase::OpenBetCounts => {
+            let keys = USER_OPEN_BET_COUNT
+                .keys(storage, None, None, Order::Ascending)
+                .take(limit)
+                .collect::<StdResult<Vec<_>>>()?;
+            for key in &keys {
+                USER_OPEN_BET_COUNT.remove(storage, key);
+            }
+            keys.len()
+        }
+        ResetPhase::DailyUsage => {
+            let keys = DAILY_USAGE
+                .keys(storage, None, None, Order::Ascending)
+                .take(limit)
+                .collect::<StdResult<Vec<_>>>()?;
+            for key in &keys {
+                DAILY_USAGE.remove(storage, (&key.0, key.1));
+            }
+            keys.len()
+        }
+        ResetPhase::DailyLoss => {
+            let keys = DAILY_LOSS
+                .keys(storage, None, None, Order::Ascending)
+                .take(limit)
+                .collect::<StdResult<Vec<_>>>()?;
+            for key in &keys {
+                DAILY_LOSS.remove(storage, (&key.0, key.1));
+            }
+            keys.len()
+        }
+        ResetPhase::PendingWithdrawals => {
+            let keys = PENDING_WITHDRAWALS
+                .keys(storage, None, None, Order::Ascending)
+                .take(limit)
+                .collect::<StdResult<Vec<_>>>()?;
+            for key in &keys {
+                PENDING_WITHDRAWALS.remove(storage, *key);
+            }
+            keys.len()
+        }
+        ResetPhase::RecentWithdrawals => {
+            let keys = RECENT_WITHDRAWALS
+                .keys(storage, None, None, Order::Ascending)
+                .take(limit)
+                .collect::<StdResult<Vec<_>>>()?;
+            for key in &keys {
+                RECENT_WITHDRAWALS.remove(storage, key);
+            }
+            keys.len()
+        }
+        ResetPhase::PlayerStats => {
+            let keys = PLAYER_STATS
+                .keys(storage, None, None, Order::Ascending)
+                .take(limit)
+                .collect::<StdResult<Vec<_>>>()?;
+            for key in &keys {
+                PLAYER_STATS.remove(storage, key);
+            }
+            keys.len()
+        }
+        ResetPhase::DailyStats => {
+            let keys = DAILY_STATS
+                .keys(storage, None, None, Order::Ascending)
+                .take(limit)
+                .collect::<StdResult<Vec<_>>>()?;
+            for key in &keys {
+                DAILY_STATS.remove(storage, *key);
+            }
+            keys.len()
+        }
+        ResetPhase::PeriodStats => {
+            let keys = PERIOD_STATS
+                .keys(storage, None, None, Order::Ascending)
+                .take(limit)
+                .collect::<StdResult<Vec<_>>>()?;
+            for (period, bucket, player) in &keys {
+                PERIOD_STATS.remove(storage, (period, *bucket, player));
+            }
+            keys.len()
+        }
+        ResetPhase::Leaderboard => {
+            let keys = LEADERBOARD
+                .keys(storage, None, None, Order::Ascending)
+                .take(limit)
+                .collect::<StdResult<Vec<_>>>()?;
+            for (board, score, player) in &keys {
+                LEADERBOARD.remove(storage, (board, *score, player));
+            }
+            keys.len()
+        }
+        ResetPhase::StatsSeries => {
+            let keys = STATS_SERIES
+                .keys(storage, None, None, Order::Ascending)
+                .take(limit)
+                .collect::<StdResult<Vec<_>>>()?;
+            for (granularity, bucket) in &keys {
+                STATS_SERIES.remove(storage, (granularity, *bucket));
+            }
+            keys.len()
+        }
+        ResetPhase::SeriesPlayers => {
+            let keys = SERIES_PLAYERS
+                .keys(storage, None, None, Order::Ascending)
+                .take(limit)
+                .collect::<StdResult<Vec<_>>>()?;
+            for (granularity, bucket, player) in &keys {
+                SERIES_PLAYERS.remove(storage, (granularity, *bucket, player));
+            }
+            keys.len()
+        }
+        ResetPhase::AccruedFees => {
+            let keys = ACCRUED_FEES
+                .keys(storage, None, None, Order::Ascending)
+                .take(limit)
+                .collect::<StdResult<Vec<_>>>()?;
+            for key in &keys {
+                ACCRUED_FEES.remove(storage, key);
+            }
+            keys.len()
+        }
+        ResetPhase::JackpotPools => {
+            let keys = JACKPOT_POOLS
+                .keys(storage, None, None, Order::Ascending)
+                .take(limit)
+                .collect::<StdResult<Vec<_>>>()?;
+            for key in &keys {
+                JACKPOT_POOLS.remove(storage, *key);
+            }
+            keys.len()
+        }
+        ResetPhase::JackpotEntries => {
+            let keys = JACKPOT_ENTRIES
+                .keys(storage, None, None, Order::Ascending)
+                .take(limit)
+                .collect::<StdResult<Vec<_>>>()?;
+            for (tier, cycle, player) in &keys {
+                JACKPOT_ENTRIES.remove(storage, (*tier, *cycle, player));
+            }
+            keys.len()
+        }
+        ResetPhase::JackpotRanges => {
+            let keys = JACKPOT_RANGES
+                .keys(storage, None, None, Order::Ascending)
+                .take(limit)
+                .collect::<StdResult<Vec<_>>>()?;
+            for key in &keys {
+                JACKPOT_RANGES.remove(storage, *key);
+            }
+            keys.len()
+        }
+        ResetPhase::JackpotDraws => {
+            let keys = JACKPOT_DRAWS
+                .keys(storage, None, None, Order::Ascending)
+                .take(limit)
+                .collect::<StdResult<Vec<_>>>()?;
+            for key in &keys {
+                JACKPOT_DRAWS.remove(storage, *key);
+            }
+            keys.len()
+        }
+        ResetPhase::RakebackEpochs => {
+            let keys = RAKEBACK_EPOCHS
+                .keys(storage, None, None, Order::Ascending)
+                .take(limit)
+                .collect::<StdResult<Vec<_>>>()?;
+            for key in &keys {
+                RAKEBACK_EPOCHS.remove(storage, *key);
+            }
+            keys.len()
+        }
+        ResetPhase::RakebackEntries => {
+            let keys = RAKEBACK
+                .keys(storage, None, None, Order::Ascending)
+                .take(limit)
+                .collect::<StdResult<Vec<_>>>()?;
+            for (epoch, player) in &keys {
+                RAKEBACK.remove(storage, (*epoch, player));
+            }
+            keys.len()
+        }
+        ResetPhase::Loyalty => {
+            let keys = LOYALTY
+                .keys(storage, None, None, Order::Ascending)
+                .take(limit)
+                .collect::<StdResult<Vec<_>>>()?;
+            for key in &keys {
+                LOYALTY.remove(storage, key);
+            }
+            keys.len()
+        }
+        ResetPhase::BonusWagering => {
+            let keys = BONUS_WAGERING
+                .keys(storage, None, None, Order::Ascending)
+                .take(limit)
+                .collect::<StdResult<Vec<_>>>()?;
+            for key in &keys {
+                BONUS_WAGERING.remove(storage, key);
+            }
+            keys.len()
+        }
+        ResetPhase::PairStats => {
+            let keys = PAIR_STATS
+                .keys(storage, None, None, Order::Ascending)
+                .take(limit)
+                .collect::<StdResult<Vec<_>>>()?;
+            for (day, player, opponent) in &keys {
+                PAIR_STATS.remove(storage, (*day, player, opponent));
+            }
+            keys.len()
+        }
+        ResetPhase::ReferralEarnings => {
+            let keys = REFERRAL_EARNINGS
+                .keys(storage, None, None, Order::Ascending)
+                .take(limit)
+                .collect::<StdResult<Vec<_>>>()?;
+            for key in &keys {
+                REFERRAL_EARNINGS.remove(storage, key);
+            }
+            keys.len()
+        }
+    };
+    Ok(cleared)
+}
+
+/// Start a batched reset: normal operations are blocked until it completes.
+pub fn start_reset(storage: &mut dyn Storage) -> Result<(), ContractError> {
+    if RESET_PROGRESS.may_load(storage)?.is_some() {
+        return Err(ContractError::MigrationInProgress);
+    }
+    // The reset wipes every balance and zeroes the totals, so there is nothing left to count
+    TOTALS_BACKFILL.remove(storage);
+    RESET_PROGRESS.save(
+        storage,
+        &ResetProgress {
+            phase: ResetPhase::VaultBalances,
+            cleared_entries: 0,
+        },
+    )?;
+    Ok(())
+}
@@ -1,7 +1,15 @@
-use cosmwasm_std::{Binary, DepsMut, Env, MessageInfo, Response, Uint128};
+use cosmwasm_std::{to_json_binary, Binary, DepsMut, Env, MessageInfo, Response, Uint128};
 
 use crate::error::ContractError;
-use crate::state::{Bet, BetStatus, BETS, CONFIG, NEXT_BET_ID, USER_OPEN_BET_COUNT, VAULT_BALANCES};
+use crate::events;
+use crate::execute::bonus::lock_stake;
+use crate::execute::limits::check_limits;
+use crate::execute::promo::redeem_voucher;
+use crate::msg::{CreateBetResponse, PromoVoucher};
+use crate::state::{
+    save_balance, Bet, BetStatus, BETS, CONFIG, NEXT_BET_ID, USER_OPEN_BET_COUNT, VAULT_BALANCES,
+};
+use crate::stats;
 
 pub fn execute_create_bet(
     deps: DepsMut,
@@ -9,6 +17,7 @@ pub fn execute_create_bet(
     info: MessageInfo,
     amount: Uint128,
     commitment: Binary,
+    voucher: Option<PromoVoucher>,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
 
@@ -26,17 +35,31 @@ pub fn execute_create_bet(
         });
     }
 
-    // Check available balance
+    // Redeem the voucher first: a bonus can fund the stake
+    let bet_id = NEXT_BET_ID.load(deps.storage)?;
+    let mut promo_discounts = vec![];
+    let mut promo_events = vec![];
+    if let Some(voucher) = voucher {
+        let (discount, events) =
+            redeem_voucher(deps.storage, deps.api, &env, &config, &info.sender, bet_id, voucher)?;
+        promo_discounts.extend(discount);
+        promo_events = events;
+    }
+
+    // Check available balance; the bonus can be staked too
     let mut balance = VAULT_BALANCES
         .may_load(deps.storage, &info.sender)?
         .unwrap_or_default();
-    if balance.available < amount {
+    if balance.available + balance.bonus < amount {
         return Err(ContractError::InsufficientAvailableBalance {
             need: amount.to_string(),
-            have: balance.available.to_string(),
+            have: (balance.available + balance.bonus).to_string(),
         });
     }
 
+    // Check the limits the maker set on their own play
+    check_limits(deps.storage, &info.sender, env.block.time.seconds(), amount, false)?;
+
     // Check open bets count
     let open_count = USER_OPEN_BET_COUNT
         .may_load(deps.storage, &info.sender)?
@@ -48,15 +71,13 @@ pub fn execute_create_bet(
     }
 
     // Lock funds
-    balance.available -= amount;
-    balance.locked += amount;
-    VAULT_BALANCES.save(deps.storage, &info.sender, &balance)?;
+    let maker_bonus = lock_stake(deps.storage, &info.sender, &mut balance, amount)?;
+    let balance_event = save_balance(deps.storage, &info.sender, &balance)?;
 
     // Increment open bets count
     USER_OPEN_BET_COUNT.save(deps.storage, &info.sender, &(open_count + 1))?;
 
     // Create bet
-    let bet_id = NEXT_BET_ID.load(deps.storage)?;
     NEXT_BET_ID.save(deps.storage, &(bet_id + 1))?;
 
     let bet = Bet {
@@ -77,13 +98,24 @@ pub fn execute_create_bet(
         payout_winner: None,
         commission_paid: Uint128::zero(),
         payout_amount: Uint128::zero(),
+        commission_bps: None,
+        promo_discounts,
+        maker_bonus,
+        acceptor_bonus: Uint128::zero(),
+        non_qualifying: false,
+        stats_recorded: false,
     };
 
     BETS.save(deps.storage, bet_id, &bet)?;
+    stats::record_created(deps.storage, &bet.maker, bet.created_at_time)?;
 
     Ok(Response::new()
         .add_attribute("action", "coinflip.bet_created")
         .add_attribute("bet_id", bet_id.to_string())
         .add_attribute("maker", info.sender.to_string())
-        .add_attribute("amount", amount.to_string()))
+        .add_attribute("amount", amount.to_string())
+        .add_event(events::bet_created(&bet))
+        .add_events(promo_events)
+        .add_event(balance_event)
+        .set_data(to_json_binary(&CreateBetResponse { bet_id })?))
 }
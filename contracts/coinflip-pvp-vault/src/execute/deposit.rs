@@ -2,8 +2,9 @@ use cosmwasm_std::{from_json, DepsMut, Env, MessageInfo, Response};
 use cw20::Cw20ReceiveMsg;
 
 use crate::error::ContractError;
+use crate::events;
 use crate::msg::ReceiveMsg;
-use crate::state::{CONFIG, VAULT_BALANCES};
+use crate::state::{save_balance, CONFIG, VAULT_BALANCES};
 
 pub fn execute_receive(
     deps: DepsMut,
@@ -29,11 +30,13 @@ pub fn execute_receive(
         .may_load(deps.storage, &depositor)?
         .unwrap_or_default();
     balance.available += amount;
-    VAULT_BALANCES.save(deps.storage, &depositor, &balance)?;
+    let balance_event = save_balance(deps.storage, &depositor, &balance)?;
 
     Ok(Response::new()
         .add_attribute("action", "deposit")
         .add_attribute("depositor", depositor.to_string())
         .add_attribute("amount", amount.to_string())
-        .add_attribute("new_available", balance.available.to_string()))
+        .add_attribute("new_available", balance.available.to_string())
+        .add_event(events::transfer("coinflip_deposit", &depositor, amount))
+        .add_event(balance_event))
 }
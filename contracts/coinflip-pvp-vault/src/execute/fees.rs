@@ -0,0 +1,312 @@
+use std::collections::HashSet;
+
+use cosmwasm_std::{
+    Addr, DepsMut, Event, MessageInfo, Order, QuerierWrapper, Response, StdResult, Storage,
+    Uint128,
+};
+
+use crate::error::ContractError;
+use crate::execute::loyalty::apply_fee_discount;
+use crate::execute::stake_discounts::stake_discount;
+use crate::execute::withdraw::payout_msg;
+use crate::msg::CommissionShareMsg;
+use crate::state::{
+    save_balance, Bet, CommissionShare, Config, FeePromo, FeeTier, ACCRUED_FEES, CONFIG,
+    VAULT_BALANCES, VAULT_TOTALS,
+};
+
+pub const MAX_COMMISSION_SHARES: usize = 8;
+
+pub const MAX_FEE_TIERS: usize = 8;
+/// Cap of every commission rate, as enforced on `commission_bps`
+pub const MAX_COMMISSION_BPS: u16 = 5000;
+
+/// Commission bps of the fee schedule for a bet of `amount` per side at block time
+/// `now`: the promo rate while it runs, else the highest reached size tier, else
+/// `commission_bps`.
+pub fn schedule_bps(config: &Config, amount: Uint128, now: u64) -> u16 {
+    if let Some(promo) = config.fee_promo.as_ref().filter(|promo| promo.is_active(now)) {
+        return promo.bps;
+    }
+    config
+        .fee_tiers
+        .iter()
+        .rev()
+        .find(|tier| amount >= tier.min_amount)
+        .map_or(config.commission_bps, |tier| tier.bps)
+}
+
+/// Discounts never take the rate below `min_commission_bps`, nor raise it above the
+/// scheduled rate.
+pub fn floor_bps(config: &Config, scheduled: u16, discounted: u16) -> u16 {
+    discounted.max(config.min_commission_bps.min(scheduled))
+}
+
+/// Commission bps of a pot won by `winner`: the scheduled rate less the winner's fee
+/// discount, which this uses up, their LAUNCH stake discount and any promo voucher
+/// they redeemed on the bet.
+pub fn commission_bps_for(
+    storage: &mut dyn Storage,
+    querier: QuerierWrapper,
+    config: &Config,
+    now: u64,
+    bet: &Bet,
+    winner: &Addr,
+) -> StdResult<(u16, Vec<Event>)> {
+    let scheduled = schedule_bps(config, bet.amount, now);
+    let (mut commission_bps, mut events) = apply_fee_discount(storage, scheduled, bet.id, winner)?;
+    let (staked, discount_bps) = stake_discount(querier, config, winner);
+    if discount_bps > 0 && commission_bps > 0 {
+        commission_bps = commission_bps.saturating_sub(discount_bps);
+        events.push(
+            Event::new("coinflip_stake_discount_applied")
+                .add_attribute("bet_id", bet.id.to_string())
+                .add_attribute("user", winner.to_string())
+                .add_attribute("staked", staked.to_string())
+                .add_attribute("discount_bps", discount_bps.to_string())
+                .add_attribute("commission_bps", commission_bps.to_string()),
+        );
+    }
+    let mut commission_bps = floor_bps(config, scheduled, commission_bps);
+
+    // A promo voucher is signed by the admin, so it may go below the floor
+    if let Some(promo) = bet.promo_discounts.iter().find(|promo| promo.player == *winner) {
+        commission_bps = commission_bps.saturating_sub(promo.bps);
+        events.push(
+            Event::new("coinflip_promo_discount_applied")
+                .add_attribute("bet_id", bet.id.to_string())
+                .add_attribute("user", winner.to_string())
+                .add_attribute("voucher_id", &promo.voucher_id)
+                .add_attribute("discount_bps", promo.bps.to_string())
+                .add_attribute("commission_bps", commission_bps.to_string()),
+        );
+    }
+    Ok((commission_bps, events))
+}
+
+/// Admin-only: replace the size tiers, the promo window and the commission floor.
+///
+/// Every rate stays within the cap of `commission_bps`, and the floor cannot exceed
+/// any scheduled rate.
+pub fn execute_update_fee_schedule(
+    deps: DepsMut,
+    info: MessageInfo,
+    tiers: Vec<FeeTier>,
+    promo: Option<FeePromo>,
+    min_commission_bps: u16,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized);
+    }
+
+    let invalid = |reason: String| ContractError::InvalidFeeSchedule { reason };
+    if tiers.len() > MAX_FEE_TIERS {
+        return Err(invalid(format!("at most {MAX_FEE_TIERS} tiers")));
+    }
+    if tiers.iter().any(|tier| tier.min_amount.is_zero()) {
+        return Err(invalid("tiers need a non-zero min_amount".to_string()));
+    }
+    if tiers.windows(2).any(|pair| pair[0].min_amount >= pair[1].min_amount) {
+        return Err(invalid("min_amount must be strictly ascending".to_string()));
+    }
+    if promo.as_ref().is_some_and(|promo| promo.start >= promo.end) {
+        return Err(invalid("promo must end after it starts".to_string()));
+    }
+    let rates = tiers
+        .iter()
+        .map(|tier| tier.bps)
+        .chain(promo.as_ref().map(|promo| promo.bps))
+        .chain([config.commission_bps]);
+    for bps in rates {
+        if bps > MAX_COMMISSION_BPS {
+            return Err(ContractError::InvalidCommission {
+                max_bps: MAX_COMMISSION_BPS,
+            });
+        }
+        if bps < min_commission_bps {
+            return Err(invalid(format!("rate of {bps} bps below min_commission_bps")));
+        }
+    }
+
+    config.fee_tiers = tiers;
+    config.fee_promo = promo;
+    config.min_commission_bps = min_commission_bps;
+    CONFIG.save(deps.storage, &config)?;
+
+    let tiers = config
+        .fee_tiers
+        .iter()
+        .map(|tier| format!("{}:{}", tier.min_amount, tier.bps))
+        .collect::<Vec<_>>()
+        .join(",");
+    let promo = config
+        .fee_promo
+        .as_ref()
+        .map(|promo| format!("{}:{}:{}", promo.start, promo.end, promo.bps))
+        .unwrap_or_default();
+    Ok(Response::new()
+        .add_attribute("action", "update_fee_schedule")
+        .add_attribute("tiers", &tiers)
+        .add_attribute("promo", &promo)
+        .add_attribute("min_commission_bps", min_commission_bps.to_string())
+        .add_event(
+            Event::new("coinflip_fee_schedule_updated")
+                .add_attribute("tiers", tiers)
+                .add_attribute("promo", promo)
+                .add_attribute("min_commission_bps", min_commission_bps.to_string()),
+        ))
+}
+
+/// Credit the commission of a resolved bet.
+///
+/// Without a split it goes to the treasury's vault balance. Otherwise each share
+/// accrues in `ACCRUED_FEES` until `FlushFees`; rounding dust goes to the first share.
+pub fn credit_commission(
+    storage: &mut dyn Storage,
+    config: &Config,
+    commission: Uint128,
+) -> StdResult<Vec<Event>> {
+    if config.commission_split.is_empty() {
+        let mut treasury_bal = VAULT_BALANCES
+            .may_load(storage, &config.treasury)?
+            .unwrap_or_default();
+        treasury_bal.available += commission;
+        return Ok(vec![save_balance(storage, &config.treasury, &treasury_bal)?]);
+    }
+
+    let mut amounts: Vec<Uint128> = config
+        .commission_split
+        .iter()
+        .map(|share| commission.multiply_ratio(share.bps, 10_000u128))
+        .collect();
+    let dust = commission - amounts.iter().sum::<Uint128>();
+    amounts[0] += dust;
+
+    let mut events = vec![];
+    for (share, amount) in config.commission_split.iter().zip(amounts) {
+        if amount.is_zero() {
+            continue;
+        }
+        let accrued = ACCRUED_FEES.may_load(storage, &share.address)?.unwrap_or_default();
+        ACCRUED_FEES.save(storage, &share.address, &(accrued + amount))?;
+        events.push(
+            Event::new("coinflip_commission_accrued")
+                .add_attribute("label", &share.label)
+                .add_attribute("address", share.address.to_string())
+                .add_attribute("amount", amount.to_string()),
+        );
+    }
+
+    let mut totals = VAULT_TOTALS.may_load(storage)?.unwrap_or_default();
+    totals.total_accrued_fees += commission;
+    VAULT_TOTALS.save(storage, &totals)?;
+    Ok(events)
+}
+
+/// Admin-only: replace the commission split table.
+///
+/// Shares already accrued stay with their recipients and are still paid by `FlushFees`.
+pub fn execute_update_commission_split(
+    deps: DepsMut,
+    info: MessageInfo,
+    shares: Vec<CommissionShareMsg>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized);
+    }
+
+    let invalid = |reason: &str| ContractError::InvalidCommissionSplit {
+        reason: reason.to_string(),
+    };
+    if shares.len() > MAX_COMMISSION_SHARES {
+        return Err(invalid("too many shares"));
+    }
+
+    let mut split = vec![];
+    let mut labels = HashSet::new();
+    let mut addresses = HashSet::new();
+    for share in shares {
+        let address = deps.api.addr_validate(&share.address)?;
+        if share.bps == 0 {
+            return Err(invalid("share of 0 bps"));
+        }
+        if share.label.is_empty() || !labels.insert(share.label.clone()) {
+            return Err(invalid("labels must be non-empty and unique"));
+        }
+        if !addresses.insert(address.clone()) {
+            return Err(invalid("addresses must be unique"));
+        }
+        if share.distribute {
+            // Staking `Distribute {}` only takes the native denom
+            return Err(invalid("distribute is not supported for the CW20 vault"));
+        }
+        split.push(CommissionShare {
+            label: share.label,
+            address,
+            bps: share.bps,
+            distribute: share.distribute,
+        });
+    }
+    let total_bps: u32 = split.iter().map(|share| u32::from(share.bps)).sum();
+    if !split.is_empty() && total_bps != 10_000 {
+        return Err(invalid("shares must add up to 10000 bps"));
+    }
+
+    config.commission_split = split;
+    CONFIG.save(deps.storage, &config)?;
+
+    let shares = config
+        .commission_split
+        .iter()
+        .map(|share| format!("{}:{}:{}", share.label, share.address, share.bps))
+        .collect::<Vec<_>>()
+        .join(",");
+    Ok(Response::new()
+        .add_attribute("action", "update_commission_split")
+        .add_attribute("shares", &shares)
+        .add_event(
+            Event::new("coinflip_commission_split_updated").add_attribute("shares", shares),
+        ))
+}
+
+/// Permissionless: pay out every accrued commission share as a CW20 transfer.
+pub fn execute_flush_fees(deps: DepsMut, _info: MessageInfo) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let accrued = ACCRUED_FEES
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut messages = vec![];
+    let mut events = vec![];
+    let mut flushed = Uint128::zero();
+    for (address, amount) in accrued {
+        let share = config.commission_split.iter().find(|share| share.address == address);
+        let label = share.map(|share| share.label.clone()).unwrap_or_default();
+
+        ACCRUED_FEES.remove(deps.storage, &address);
+        flushed += amount;
+        messages.push(payout_msg(&config, &address, amount)?);
+        events.push(
+            Event::new("coinflip_fee_flushed")
+                .add_attribute("label", label)
+                .add_attribute("address", address.to_string())
+                .add_attribute("amount", amount.to_string())
+                .add_attribute("method", "transfer"),
+        );
+    }
+
+    if flushed.is_zero() {
+        return Err(ContractError::NothingToFlush);
+    }
+    let mut totals = VAULT_TOTALS.load(deps.storage)?;
+    totals.total_accrued_fees -= flushed;
+    VAULT_TOTALS.save(deps.storage, &totals)?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "flush_fees")
+        .add_attribute("flushed", flushed.to_string())
+        .add_events(events))
+}
@@ -0,0 +1,453 @@
+//! Tiered jackpot pools funded from every resolved pot.
+//!
+//! Each tier takes its bps of the pot out of the commission into its pool, and the
+//! stakes of players with enough finished games become draw entries for the cycle.
+//! A cycle is only funded once the admin has committed sha256(seed) for it. The
+//! resolution that brings the pool to its target freezes the cycle: its amount,
+//! entries and commitment wait for the draw, and later bets fund the next cycle.
+//! The resolutions of the next `JACKPOT_ENTROPY_BLOCKS` blocks are mixed into the
+//! fill's entropy, so nobody knows the final entropy when the pool fills. Then
+//! `DrawJackpot` reveals the seed: sha256(seed || entropy) picks the winner weighted
+//! by stake, so neither the admin nor the players choose the outcome alone. If the
+//! seed is not revealed by the deadline, anyone can draw the same entrants from the
+//! entropy alone, so withholding the seed cannot bring in other entrants.
+
+use cosmwasm_std::{
+    Addr, Binary, DepsMut, Env, Event, MessageInfo, Order, Response, StdError, StdResult, Storage,
+    Uint128,
+};
+use sha2::{Digest, Sha256};
+
+use crate::error::ContractError;
+use crate::state::{
+    save_balance, Bet, Config, JackpotDraw, JackpotFill, JackpotPool, JackpotRange, JackpotTier,
+    CONFIG, JACKPOT_DRAWS, JACKPOT_ENTRIES, JACKPOT_POOLS, JACKPOT_RANGES, PLAYER_STATS,
+    VAULT_BALANCES, VAULT_TOTALS,
+};
+
+pub const MAX_JACKPOT_TIERS: usize = 5;
+/// Blocks after a fill whose resolutions still feed the draw entropy
+pub const JACKPOT_ENTROPY_BLOCKS: u64 = 10;
+/// Time the admin has after a fill to reveal the seed
+pub const JACKPOT_REVEAL_SECS: u64 = 3 * 86_400;
+
+/// Admin-only: replace the jackpot tiers. Tier ids are positions in the list.
+///
+/// A tier can only be removed once its pool is empty and its last draw is done.
+pub fn execute_update_jackpot_tiers(
+    deps: DepsMut,
+    info: MessageInfo,
+    tiers: Vec<JackpotTier>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized);
+    }
+
+    let invalid = |reason: String| ContractError::InvalidJackpotTiers { reason };
+    if tiers.len() > MAX_JACKPOT_TIERS {
+        return Err(invalid(format!("at most {MAX_JACKPOT_TIERS} tiers")));
+    }
+    if tiers.iter().any(|tier| tier.name.is_empty() || tier.bps == 0 || tier.target.is_zero()) {
+        return Err(invalid("tiers need a name, a non-zero bps and a target".to_string()));
+    }
+    let jackpot_bps: u32 = tiers.iter().map(|tier| u32::from(tier.bps)).sum();
+    let referral_bps: u32 = config.referral_bps.iter().map(|level| u32::from(*level)).sum();
+    if jackpot_bps + referral_bps > u32::from(config.commission_bps) {
+        return Err(invalid(format!(
+            "jackpot and referral bps add up to more than commission_bps {}",
+            config.commission_bps
+        )));
+    }
+    for id in tiers.len()..config.jackpot_tiers.len() {
+        let pool = JACKPOT_POOLS.may_load(deps.storage, id as u8)?.unwrap_or_default();
+        if !pool.amount.is_zero() || pool.filled.is_some() {
+            return Err(invalid(format!("tier {id} still holds a pool")));
+        }
+    }
+
+    config.jackpot_tiers = tiers;
+    CONFIG.save(deps.storage, &config)?;
+
+    let tiers = config
+        .jackpot_tiers
+        .iter()
+        .map(|tier| format!("{}:{}:{}", tier.name, tier.bps, tier.target))
+        .collect::<Vec<_>>()
+        .join(",");
+    Ok(Response::new()
+        .add_attribute("action", "update_jackpot_tiers")
+        .add_attribute("tiers", &tiers)
+        .add_event(Event::new("coinflip_jackpot_tiers_updated").add_attribute("tiers", tiers)))
+}
+
+/// Admin-only: commit sha256(seed) for the current cycle of a tier.
+///
+/// A cycle is only funded with a seed committed, so the seed is fixed before
+/// any entropy of the cycle's resolutions is known. A filled cycle keeps its own
+/// commitment, so the next cycle's seed can be committed before the draw.
+pub fn execute_commit_jackpot_seed(
+    deps: DepsMut,
+    info: MessageInfo,
+    tier: u8,
+    commitment: Binary,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized);
+    }
+    if config.jackpot_tiers.get(tier as usize).is_none() {
+        return Err(ContractError::JackpotTierNotFound { tier });
+    }
+    if commitment.len() != 32 {
+        return Err(ContractError::InvalidCommitmentLength { len: commitment.len() });
+    }
+
+    let mut pool = JACKPOT_POOLS.may_load(deps.storage, tier)?.unwrap_or_default();
+    if pool.commitment.is_some() {
+        return Err(ContractError::JackpotSeedAlreadyCommitted { tier });
+    }
+    pool.commitment = Some(commitment);
+    JACKPOT_POOLS.save(deps.storage, tier, &pool)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "commit_jackpot_seed")
+        .add_attribute("tier", tier.to_string())
+        .add_event(
+            Event::new("coinflip_jackpot_seed_committed")
+                .add_attribute("tier", tier.to_string())
+                .add_attribute("cycle", pool.cycle.to_string()),
+        ))
+}
+
+/// Admin-only: reveal the committed seed of a filled cycle and pay its winner.
+///
+/// Accepted from the fill's `draw_from_height`, once its entropy is final, until its
+/// reveal deadline.
+pub fn execute_draw_jackpot(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    tier: u8,
+    seed: Binary,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized);
+    }
+
+    let mut pool = JACKPOT_POOLS
+        .may_load(deps.storage, tier)?
+        .ok_or(ContractError::JackpotNotFilled { tier })?;
+    let Some(fill) = pool.filled.take() else {
+        return Err(ContractError::JackpotNotFilled { tier });
+    };
+    if env.block.height < fill.draw_from_height {
+        return Err(ContractError::JackpotEntropyPending {
+            tier,
+            height: fill.draw_from_height,
+        });
+    }
+    if env.block.time.seconds() > fill.reveal_deadline {
+        return Err(ContractError::JackpotRevealExpired {
+            tier,
+            deadline: fill.reveal_deadline,
+        });
+    }
+    if fill.commitment.as_slice() != Sha256::digest(seed.as_slice()).as_slice() {
+        return Err(ContractError::CommitmentMismatch);
+    }
+
+    pay_draw(deps.storage, &env, tier, &pool, fill, Some(seed))
+}
+
+/// Permissionless: once a filled cycle's reveal deadline has passed without a draw,
+/// pay a winner drawn from the same entries with sha256("coinflip_jackpot_fallback" ||
+/// entropy).
+///
+/// The entropy is final long before the deadline, so the caller cannot pick the block
+/// that decides the outcome, and withholding the seed leaves only this fixed draw
+/// among the same entrants.
+pub fn execute_fallback_draw_jackpot(
+    deps: DepsMut,
+    env: Env,
+    tier: u8,
+) -> Result<Response, ContractError> {
+    let mut pool = JACKPOT_POOLS
+        .may_load(deps.storage, tier)?
+        .ok_or(ContractError::JackpotNotFilled { tier })?;
+    let Some(fill) = pool.filled.take() else {
+        return Err(ContractError::JackpotNotFilled { tier });
+    };
+    if env.block.time.seconds() <= fill.reveal_deadline {
+        return Err(ContractError::JackpotRevealPending {
+            tier,
+            deadline: fill.reveal_deadline,
+        });
+    }
+    if env.block.height < fill.draw_from_height {
+        return Err(ContractError::JackpotEntropyPending {
+            tier,
+            height: fill.draw_from_height,
+        });
+    }
+    pay_draw(deps.storage, &env, tier, &pool, fill, None)
+}
+
+/// Permissionless: delete up to `limit` entries of a cycle that was drawn.
+pub fn execute_prune_jackpot_entries(
+    deps: DepsMut,
+    tier: u8,
+    cycle: u64,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let pool = JACKPOT_POOLS.may_load(deps.storage, tier)?.unwrap_or_default();
+    let pending = pool.filled.as_ref().map(|fill| fill.cycle);
+    if cycle >= pool.cycle || pending == Some(cycle) {
+        return Err(ContractError::JackpotCycleNotFinished { tier, cycle });
+    }
+
+    let limit = limit.unwrap_or(100).clamp(1, 500) as usize;
+    let ranges = JACKPOT_RANGES
+        .prefix((tier, cycle))
+        .keys(deps.storage, None, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    for index in &ranges {
+        JACKPOT_RANGES.remove(deps.storage, (tier, cycle, *index));
+    }
+    let players = JACKPOT_ENTRIES
+        .prefix((tier, cycle))
+        .keys(deps.storage, None, None, Order::Ascending)
+        .take(limit - ranges.len())
+        .collect::<StdResult<Vec<_>>>()?;
+    for player in &players {
+        JACKPOT_ENTRIES.remove(deps.storage, (tier, cycle, player));
+    }
+    let pruned = ranges.len() + players.len();
+
+    Ok(Response::new()
+        .add_attribute("action", "prune_jackpot_entries")
+        .add_attribute("tier", tier.to_string())
+        .add_attribute("cycle", cycle.to_string())
+        .add_attribute("pruned", pruned.to_string())
+        .add_attribute("completed", (pruned < limit).to_string()))
+}
+
+/// Fund every tier's pool from a resolved bet and enter its eligible players.
+///
+/// Contributions come out of `commission`; what is left of it is returned. A tier
+/// whose current cycle has no seed committed is not funded. A player is eligible with
+/// at least `min_games` finished games before this bet, and enters with their stake.
+/// A non-qualifying bet neither funds nor enters.
+///
+/// Every resolution also feeds the entropy of fills still collecting it.
+pub fn fund_jackpots(
+    storage: &mut dyn Storage,
+    config: &Config,
+    env: &Env,
+    bet: &Bet,
+    acceptor: &Addr,
+    commission: Uint128,
+) -> StdResult<(Uint128, Vec<Event>)> {
+    if config.jackpot_tiers.is_empty() {
+        return Ok((commission, vec![]));
+    }
+
+    let pot = bet.amount + bet.amount;
+    let mut players = vec![];
+    for player in [&bet.maker, acceptor] {
+        let games = PLAYER_STATS.may_load(storage, player)?.unwrap_or_default().games;
+        players.push((player, games));
+    }
+
+    let mut left = commission;
+    let mut funded = Uint128::zero();
+    let mut events = vec![];
+    for (id, tier) in config.jackpot_tiers.iter().enumerate() {
+        let id = id as u8;
+        let Some(mut pool) = JACKPOT_POOLS.may_load(storage, id)? else {
+            continue;
+        };
+        let mut mixed = false;
+        if let Some(fill) = pool.filled.as_mut() {
+            if env.block.height < fill.draw_from_height {
+                let mut hasher = Sha256::new();
+                hasher.update(fill.entropy.as_slice());
+                hasher.update(resolution_entropy(env, bet, id).as_slice());
+                fill.entropy = Binary::from(hasher.finalize().to_vec());
+                mixed = true;
+            }
+        }
+
+        let contribution = pot.multiply_ratio(tier.bps, 10_000u128).min(left);
+        if bet.non_qualifying || pool.commitment.is_none() || contribution.is_zero() {
+            if mixed {
+                JACKPOT_POOLS.save(storage, id, &pool)?;
+            }
+            continue;
+        }
+        left -= contribution;
+        funded += contribution;
+
+        pool.amount += contribution;
+        for (player, games) in &players {
+            if *games < tier.min_games {
+                continue;
+            }
+            let key = (id, pool.cycle, *player);
+            let stake = JACKPOT_ENTRIES.may_load(storage, key)?.unwrap_or_default();
+            JACKPOT_ENTRIES.save(storage, key, &(stake + bet.amount))?;
+            pool.total_weight += bet.amount;
+            let range = JackpotRange {
+                player: (*player).clone(),
+                end: pool.total_weight,
+            };
+            JACKPOT_RANGES.save(storage, (id, pool.cycle, pool.entries), &range)?;
+            pool.entries += 1;
+        }
+        events.push(
+            Event::new("coinflip_jackpot_funded")
+                .add_attribute("tier", id.to_string())
+                .add_attribute("cycle", pool.cycle.to_string())
+                .add_attribute("amount", contribution.to_string())
+                .add_attribute("pool", pool.amount.to_string()),
+        );
+
+        // Freeze the cycle; one fill at a time, so the next waits for this draw
+        if pool.filled.is_none() && !pool.total_weight.is_zero() && pool.amount >= tier.target {
+            let fill = JackpotFill {
+                cycle: pool.cycle,
+                amount: pool.amount,
+                total_weight: pool.total_weight,
+                entries: pool.entries,
+                commitment: pool.commitment.take().unwrap_or_default(),
+                height: env.block.height,
+                entropy: resolution_entropy(env, bet, id),
+                draw_from_height: env.block.height + JACKPOT_ENTROPY_BLOCKS,
+                reveal_deadline: env.block.time.seconds() + JACKPOT_REVEAL_SECS,
+            };
+            events.push(
+                Event::new("coinflip_jackpot_filled")
+                    .add_attribute("tier", id.to_string())
+                    .add_attribute("cycle", fill.cycle.to_string())
+                    .add_attribute("amount", fill.amount.to_string())
+                    .add_attribute("draw_from_height", fill.draw_from_height.to_string())
+                    .add_attribute("reveal_deadline", fill.reveal_deadline.to_string()),
+            );
+            pool = JackpotPool {
+                cycle: pool.cycle + 1,
+                filled: Some(fill),
+                ..JackpotPool::default()
+            };
+        }
+        JACKPOT_POOLS.save(storage, id, &pool)?;
+    }
+
+    let mut totals = VAULT_TOTALS.may_load(storage)?.unwrap_or_default();
+    totals.total_jackpot += funded;
+    VAULT_TOTALS.save(storage, &totals)?;
+    Ok((left, events))
+}
+
+/// Pick the fill's winner from sha256(seed || entropy), or from the fallback hash
+/// without a seed, credit them the amount and record the draw.
+fn pay_draw(
+    storage: &mut dyn Storage,
+    env: &Env,
+    tier: u8,
+    pool: &JackpotPool,
+    fill: JackpotFill,
+    seed: Option<Binary>,
+) -> Result<Response, ContractError> {
+    let mut hasher = Sha256::new();
+    match &seed {
+        Some(seed) => hasher.update(seed.as_slice()),
+        None => hasher.update(b"coinflip_jackpot_fallback"),
+    }
+    hasher.update(fill.entropy.as_slice());
+    let random: [u8; 32] = hasher.finalize().into();
+    let mut high = [0u8; 16];
+    high.copy_from_slice(&random[..16]);
+    let target = Uint128::new(u128::from_be_bytes(high) % fill.total_weight.u128());
+
+    let winner = pick_entry(storage, tier, &fill, target)?;
+    let winner_weight = JACKPOT_ENTRIES.load(storage, (tier, fill.cycle, &winner))?;
+
+    let mut balance = VAULT_BALANCES.may_load(storage, &winner)?.unwrap_or_default();
+    balance.available += fill.amount;
+    let balance_event = save_balance(storage, &winner, &balance)?;
+    let mut totals = VAULT_TOTALS.load(storage)?;
+    totals.total_jackpot -= fill.amount;
+    VAULT_TOTALS.save(storage, &totals)?;
+
+    let fallback = seed.is_none();
+    let draw = JackpotDraw {
+        winner: winner.clone(),
+        amount: fill.amount,
+        winner_weight,
+        total_weight: fill.total_weight,
+        seed: seed.unwrap_or_default(),
+        entropy: fill.entropy,
+        drawn_at: env.block.time.seconds(),
+    };
+    JACKPOT_DRAWS.save(storage, (tier, fill.cycle), &draw)?;
+    JACKPOT_POOLS.save(storage, tier, pool)?;
+
+    let action = if fallback { "fallback_draw_jackpot" } else { "draw_jackpot" };
+    Ok(Response::new()
+        .add_attribute("action", action)
+        .add_attribute("tier", tier.to_string())
+        .add_attribute("winner", winner.to_string())
+        .add_attribute("amount", draw.amount.to_string())
+        .add_event(
+            Event::new("coinflip_jackpot_drawn")
+                .add_attribute("tier", tier.to_string())
+                .add_attribute("cycle", fill.cycle.to_string())
+                .add_attribute("winner", winner.to_string())
+                .add_attribute("amount", draw.amount.to_string())
+                .add_attribute("winner_weight", winner_weight.to_string())
+                .add_attribute("total_weight", draw.total_weight.to_string())
+                .add_attribute("fallback", fallback.to_string()),
+        )
+        .add_event(balance_event))
+}
+
+/// sha256("coinflip_jackpot" || height || time || bet id || bet commitment ||
+/// reveal secret || tier id)
+fn resolution_entropy(env: &Env, bet: &Bet, tier: u8) -> Binary {
+    let mut hasher = Sha256::new();
+    hasher.update(b"coinflip_jackpot");
+    hasher.update(env.block.height.to_be_bytes());
+    hasher.update(env.block.time.nanos().to_be_bytes());
+    hasher.update(bet.id.to_be_bytes());
+    hasher.update(bet.commitment.as_slice());
+    hasher.update(bet.reveal_secret.as_deref().unwrap_or_default());
+    hasher.update([tier]);
+    Binary::from(hasher.finalize().to_vec())
+}
+
+/// The entry whose stake range contains `target`, by binary search over the
+/// cycle's ranges.
+fn pick_entry(
+    storage: &dyn Storage,
+    tier: u8,
+    fill: &JackpotFill,
+    target: Uint128,
+) -> StdResult<Addr> {
+    // First range whose end is above the target
+    let (mut low, mut high) = (0u32, fill.entries);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        let range = JACKPOT_RANGES.load(storage, (tier, fill.cycle, mid))?;
+        if range.end > target {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+    if low == fill.entries {
+        return Err(StdError::generic_err("jackpot entries do not cover the total weight"));
+    }
+    Ok(JACKPOT_RANGES.load(storage, (tier, fill.cycle, low))?.player)
+}
@@ -0,0 +1,178 @@
+//! Loyalty XP earned from wagered volume and spent on fee discounts.
+//!
+//! Every played bet earns each player one XP per `xp_unit` of their stake, with the
+//! remainder carried to their next bet. Levels follow from the XP earned in total:
+//! level n takes `XP_PER_LEVEL * n^2` XP. Spending XP does not lower the level.
+
+use cosmwasm_std::{Addr, DepsMut, Event, MessageInfo, Response, StdResult, Storage, Uint128};
+
+use crate::error::ContractError;
+use crate::state::{CONFIG, LOYALTY};
+
+pub const XP_PER_LEVEL: u64 = 100;
+
+/// Level reached with `xp` earned in total.
+pub fn level_of(xp: u64) -> u64 {
+    isqrt(xp / XP_PER_LEVEL)
+}
+
+/// Largest `r` with `r * r <= n`, by Newton's method on integers.
+fn isqrt(n: u64) -> u64 {
+    if n < 2 {
+        return n;
+    }
+    // Start above the root; each step moves down until it stops decreasing
+    let mut x = n;
+    let mut y = n / 2 + n % 2;
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    x
+}
+
+/// XP earned in total at which `level` is reached.
+pub fn level_xp(level: u64) -> u64 {
+    XP_PER_LEVEL.saturating_mul(level.saturating_mul(level))
+}
+
+/// Admin-only: set how much wagered volume earns one XP, and the fee discount that
+/// XP can be redeemed for.
+pub fn execute_update_loyalty(
+    deps: DepsMut,
+    info: MessageInfo,
+    xp_unit: Uint128,
+    discount_bps: u16,
+    discount_xp_cost: u64,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized);
+    }
+    if discount_bps > 10_000 {
+        return Err(ContractError::InvalidLoyalty {
+            reason: "discount above 10000 bps".to_string(),
+        });
+    }
+    if discount_xp_cost > 0 && discount_bps == 0 {
+        return Err(ContractError::InvalidLoyalty {
+            reason: "redeemable discount of 0 bps".to_string(),
+        });
+    }
+
+    config.xp_unit = xp_unit;
+    config.discount_bps = discount_bps;
+    config.discount_xp_cost = discount_xp_cost;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_loyalty")
+        .add_attribute("xp_unit", xp_unit.to_string())
+        .add_attribute("discount_bps", discount_bps.to_string())
+        .add_attribute("discount_xp_cost", discount_xp_cost.to_string())
+        .add_event(
+            Event::new("coinflip_loyalty_updated")
+                .add_attribute("xp_unit", xp_unit.to_string())
+                .add_attribute("discount_bps", discount_bps.to_string())
+                .add_attribute("discount_xp_cost", discount_xp_cost.to_string()),
+        ))
+}
+
+/// Spend XP on fee discounts for the sender's next `bets` won bets.
+///
+/// Commission is only taken from the winner, so a discount is used up by a bet the
+/// sender wins. Discounts keep the rate they were redeemed at; more can only be added
+/// at the same rate.
+pub fn execute_redeem_fee_discount(
+    deps: DepsMut,
+    info: MessageInfo,
+    bets: u32,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.discount_xp_cost == 0 {
+        return Err(ContractError::FeeDiscountsDisabled);
+    }
+    if bets == 0 {
+        return Err(ContractError::ZeroDiscountBets);
+    }
+
+    let mut loyalty = LOYALTY.may_load(deps.storage, &info.sender)?.unwrap_or_default();
+    if loyalty.discount_bets > 0 && loyalty.discount_bps != config.discount_bps {
+        return Err(ContractError::FeeDiscountActive {
+            bps: loyalty.discount_bps,
+        });
+    }
+    let cost = config.discount_xp_cost.saturating_mul(u64::from(bets));
+    let balance = loyalty.xp - loyalty.xp_spent;
+    if cost > balance {
+        return Err(ContractError::InsufficientXp {
+            need: cost,
+            have: balance,
+        });
+    }
+    loyalty.xp_spent += cost;
+    loyalty.discount_bets += bets;
+    loyalty.discount_bps = config.discount_bps;
+    LOYALTY.save(deps.storage, &info.sender, &loyalty)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "redeem_fee_discount")
+        .add_attribute("user", info.sender.to_string())
+        .add_attribute("bets", bets.to_string())
+        .add_attribute("xp_spent", cost.to_string())
+        .add_event(
+            Event::new("coinflip_fee_discount_redeemed")
+                .add_attribute("user", info.sender.to_string())
+                .add_attribute("bets", bets.to_string())
+                .add_attribute("discount_bps", config.discount_bps.to_string())
+                .add_attribute("xp_spent", cost.to_string())
+                .add_attribute("discount_bets", loyalty.discount_bets.to_string()),
+        ))
+}
+
+/// Credit a player's XP for a played bet with `stake` wagered.
+pub fn award_xp(
+    storage: &mut dyn Storage,
+    xp_unit: Uint128,
+    player: &Addr,
+    stake: Uint128,
+) -> StdResult<()> {
+    if xp_unit.is_zero() {
+        return Ok(());
+    }
+    let mut loyalty = LOYALTY.may_load(storage, player)?.unwrap_or_default();
+    let wagered = loyalty.carry + stake;
+    let earned = u64::try_from((wagered / xp_unit).u128()).unwrap_or(u64::MAX);
+    loyalty.xp = loyalty.xp.saturating_add(earned);
+    loyalty.carry = wagered % xp_unit;
+    LOYALTY.save(storage, player, &loyalty)
+}
+
+/// `commission_bps` of a pot won by `winner` less their fee discount, using one up.
+pub fn apply_fee_discount(
+    storage: &mut dyn Storage,
+    commission_bps: u16,
+    bet_id: u64,
+    winner: &Addr,
+) -> StdResult<(u16, Vec<Event>)> {
+    let Some(mut loyalty) = LOYALTY.may_load(storage, winner)? else {
+        return Ok((commission_bps, vec![]));
+    };
+    if loyalty.discount_bets == 0 || commission_bps == 0 {
+        return Ok((commission_bps, vec![]));
+    }
+
+    let discounted = commission_bps.saturating_sub(loyalty.discount_bps);
+    let event = Event::new("coinflip_fee_discount_applied")
+        .add_attribute("bet_id", bet_id.to_string())
+        .add_attribute("user", winner.to_string())
+        .add_attribute("discount_bps", loyalty.discount_bps.to_string())
+        .add_attribute("commission_bps", discounted.to_string())
+        .add_attribute("discount_bets", (loyalty.discount_bets - 1).to_string());
+    loyalty.discount_bets -= 1;
+    if loyalty.discount_bets == 0 {
+        loyalty.discount_bps = 0;
+    }
+    LOYALTY.save(storage, winner, &loyalty)?;
+    Ok((discounted, vec![event]))
+}
@@ -0,0 +1,192 @@
+use cosmwasm_std::{Addr, DepsMut, Env, Event, MessageInfo, Response, StdResult, Storage, Uint128};
+
+use crate::error::ContractError;
+use crate::leaderboard::WEEK_SECS;
+use crate::state::{
+    save_balance, Bet, Config, CONFIG, RAKEBACK, RAKEBACK_EPOCHS, VAULT_BALANCES, VAULT_TOTALS,
+};
+
+/// Rakeback epochs are weeks of block time.
+pub const RAKEBACK_EPOCH_SECS: u64 = WEEK_SECS;
+
+pub fn epoch_of(time: u64) -> u64 {
+    time / RAKEBACK_EPOCH_SECS
+}
+
+/// Admin-only: set the rakeback rate, in bps of each bet's commission, and how many
+/// epochs past epochs stay claimable.
+pub fn execute_update_rakeback(
+    deps: DepsMut,
+    info: MessageInfo,
+    bps: u16,
+    claim_epochs: u64,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized);
+    }
+    if bps > 10_000 {
+        return Err(ContractError::InvalidRakeback {
+            reason: "bps above 10000".to_string(),
+        });
+    }
+    if claim_epochs == 0 {
+        return Err(ContractError::InvalidRakeback {
+            reason: "claim window of 0 epochs".to_string(),
+        });
+    }
+
+    config.rakeback_bps = bps;
+    config.rakeback_claim_epochs = claim_epochs;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_rakeback")
+        .add_attribute("bps", bps.to_string())
+        .add_attribute("claim_epochs", claim_epochs.to_string())
+        .add_event(
+            Event::new("coinflip_rakeback_updated")
+                .add_attribute("bps", bps.to_string())
+                .add_attribute("claim_epochs", claim_epochs.to_string()),
+        ))
+}
+
+/// Credit the sender's rakeback of a finished epoch to their vault balance.
+pub fn execute_claim_rakeback(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    epoch: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let current = epoch_of(env.block.time.seconds());
+    if epoch >= current {
+        return Err(ContractError::RakebackEpochNotOver { epoch });
+    }
+    if current > epoch + config.rakeback_claim_epochs {
+        return Err(ContractError::RakebackClaimExpired { epoch });
+    }
+
+    let mut entry = RAKEBACK
+        .may_load(deps.storage, (epoch, &info.sender))?
+        .filter(|entry| !entry.claimed && !entry.rebate.is_zero())
+        .ok_or(ContractError::NoRakeback { epoch })?;
+    entry.claimed = true;
+    RAKEBACK.save(deps.storage, (epoch, &info.sender), &entry)?;
+
+    let mut reserve = RAKEBACK_EPOCHS.load(deps.storage, epoch)?;
+    reserve.claimed += entry.rebate;
+    RAKEBACK_EPOCHS.save(deps.storage, epoch, &reserve)?;
+
+    let mut balance = VAULT_BALANCES.may_load(deps.storage, &info.sender)?.unwrap_or_default();
+    balance.available += entry.rebate;
+    let balance_event = save_balance(deps.storage, &info.sender, &balance)?;
+    let mut totals = VAULT_TOTALS.load(deps.storage)?;
+    totals.total_rakeback -= entry.rebate;
+    VAULT_TOTALS.save(deps.storage, &totals)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "claim_rakeback")
+        .add_attribute("user", info.sender.to_string())
+        .add_attribute("epoch", epoch.to_string())
+        .add_attribute("amount", entry.rebate.to_string())
+        .add_event(
+            Event::new("coinflip_rakeback_claimed")
+                .add_attribute("user", info.sender.to_string())
+                .add_attribute("epoch", epoch.to_string())
+                .add_attribute("amount", entry.rebate.to_string()),
+        )
+        .add_event(balance_event))
+}
+
+/// Permissionless: once an epoch's claim window has closed, return its unclaimed
+/// rakeback to the treasury's vault balance.
+pub fn execute_sweep_rakeback(
+    deps: DepsMut,
+    env: Env,
+    epoch: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if epoch_of(env.block.time.seconds()) <= epoch + config.rakeback_claim_epochs {
+        return Err(ContractError::RakebackClaimWindowOpen { epoch });
+    }
+    let mut reserve = RAKEBACK_EPOCHS
+        .may_load(deps.storage, epoch)?
+        .filter(|reserve| !reserve.swept)
+        .ok_or(ContractError::NoRakeback { epoch })?;
+    let unclaimed = reserve.reserved - reserve.claimed;
+    reserve.swept = true;
+    RAKEBACK_EPOCHS.save(deps.storage, epoch, &reserve)?;
+
+    let mut treasury_bal = VAULT_BALANCES
+        .may_load(deps.storage, &config.treasury)?
+        .unwrap_or_default();
+    treasury_bal.available += unclaimed;
+    let balance_event = save_balance(deps.storage, &config.treasury, &treasury_bal)?;
+    let mut totals = VAULT_TOTALS.load(deps.storage)?;
+    totals.total_rakeback -= unclaimed;
+    VAULT_TOTALS.save(deps.storage, &totals)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "sweep_rakeback")
+        .add_attribute("epoch", epoch.to_string())
+        .add_attribute("amount", unclaimed.to_string())
+        .add_event(
+            Event::new("coinflip_rakeback_swept")
+                .add_attribute("epoch", epoch.to_string())
+                .add_attribute("amount", unclaimed.to_string())
+                .add_attribute("treasury", config.treasury.to_string()),
+        )
+        .add_event(balance_event))
+}
+
+/// Reserve rakeback for both players of a resolved bet.
+///
+/// Each player generated half of the bet's `commission` and gets half of the reserve,
+/// `rakeback_bps` of the commission, taken out of what is left of it, `left`. A
+/// non-qualifying bet reserves none.
+pub fn fund_rakeback(
+    storage: &mut dyn Storage,
+    config: &Config,
+    env: &Env,
+    bet: &Bet,
+    acceptor: &Addr,
+    commission: Uint128,
+    left: Uint128,
+) -> StdResult<(Uint128, Vec<Event>)> {
+    if config.rakeback_bps == 0 || bet.non_qualifying {
+        return Ok((left, vec![]));
+    }
+
+    let generated = commission;
+    let reserved = generated.multiply_ratio(config.rakeback_bps, 10_000u128).min(left);
+    let epoch = epoch_of(env.block.time.seconds());
+
+    let maker_generated = generated.multiply_ratio(1u128, 2u128);
+    let maker_rebate = reserved.multiply_ratio(1u128, 2u128);
+    let shares = [
+        (&bet.maker, maker_generated, maker_rebate),
+        (acceptor, generated - maker_generated, reserved - maker_rebate),
+    ];
+    for (player, generated, rebate) in shares {
+        let mut entry = RAKEBACK.may_load(storage, (epoch, player))?.unwrap_or_default();
+        entry.generated += generated;
+        entry.rebate += rebate;
+        RAKEBACK.save(storage, (epoch, player), &entry)?;
+    }
+
+    let mut reserve = RAKEBACK_EPOCHS.may_load(storage, epoch)?.unwrap_or_default();
+    reserve.generated += generated;
+    reserve.reserved += reserved;
+    RAKEBACK_EPOCHS.save(storage, epoch, &reserve)?;
+
+    let mut totals = VAULT_TOTALS.may_load(storage)?.unwrap_or_default();
+    totals.total_rakeback += reserved;
+    VAULT_TOTALS.save(storage, &totals)?;
+
+    let event = Event::new("coinflip_rakeback_reserved")
+        .add_attribute("bet_id", bet.id.to_string())
+        .add_attribute("epoch", epoch.to_string())
+        .add_attribute("amount", reserved.to_string());
+    Ok((left - reserved, vec![event]))
+}
@@ -1,9 +1,11 @@
-use cosmwasm_std::{Binary, DepsMut, Env, MessageInfo, Response, Uint128};
+use cosmwasm_std::{to_json_binary, Binary, DepsMut, Env, MessageInfo, Response};
 use sha2::{Digest, Sha256};
 
 use crate::error::ContractError;
-use crate::msg::Side;
-use crate::state::{BetStatus, BETS, CONFIG, USER_OPEN_BET_COUNT, VAULT_BALANCES};
+use crate::events;
+use crate::execute::settle::settle_bet;
+use crate::msg::{BetOutcomeResponse, Side};
+use crate::state::{BetStatus, BETS, CONFIG, VAULT_BALANCES};
 
 pub fn execute_reveal(
     deps: DepsMut,
@@ -55,63 +57,32 @@ pub fn execute_reveal(
     }
 
     // Determine winner
+    let acceptor = bet.acceptor.clone().unwrap();
     let acceptor_guess = bet.acceptor_guess.clone().unwrap();
     let maker_wins = side != acceptor_guess;
     let winner = if maker_wins {
         bet.maker.clone()
     } else {
-        bet.acceptor.clone().unwrap()
+        acceptor.clone()
     };
-    // Compute payouts
-    let pot = bet.amount * Uint128::new(2);
-    let commission = pot * Uint128::from(config.commission_bps) / Uint128::new(10_000);
-    let payout = pot - commission;
 
-    // Update balances: unlock both, credit winner, credit treasury
-    let mut maker_bal = VAULT_BALANCES.load(deps.storage, &bet.maker)?;
-    let mut acceptor_bal = VAULT_BALANCES.load(deps.storage, &bet.acceptor.clone().unwrap())?;
-
-    maker_bal.locked -= bet.amount;
-    acceptor_bal.locked -= bet.amount;
-
-    if maker_wins {
-        maker_bal.available += payout;
-    } else {
-        acceptor_bal.available += payout;
-    }
-
-    VAULT_BALANCES.save(deps.storage, &bet.maker, &maker_bal)?;
-    VAULT_BALANCES.save(deps.storage, &bet.acceptor.clone().unwrap(), &acceptor_bal)?;
-
-    // Credit treasury
-    let mut treasury_bal = VAULT_BALANCES
-        .may_load(deps.storage, &config.treasury)?
-        .unwrap_or_default();
-    treasury_bal.available += commission;
-    VAULT_BALANCES.save(deps.storage, &config.treasury, &treasury_bal)?;
-
-    // Decrement maker's open bet count
-    let open_count = USER_OPEN_BET_COUNT
-        .may_load(deps.storage, &bet.maker)?
-        .unwrap_or(0);
-    USER_OPEN_BET_COUNT.save(deps.storage, &bet.maker, &open_count.saturating_sub(1))?;
-
-    // Update bet
-    bet.status = BetStatus::Revealed;
+    // Pay out and record the bet
     bet.reveal_secret = Some(secret);
     bet.reveal_side = Some(side.clone());
-    bet.resolved_at_height = Some(env.block.height);
-    bet.payout_winner = Some(winner.clone());
-    bet.commission_paid = commission;
-    bet.payout_amount = payout;
-    BETS.save(deps.storage, bet_id, &bet)?;
+    let acceptor_bal = VAULT_BALANCES.load(deps.storage, &acceptor)?;
+    let settlement =
+        settle_bet(deps, &env, &config, &mut bet, acceptor_bal, &winner, BetStatus::Revealed)?;
 
     Ok(Response::new()
         .add_attribute("action", "coinflip.bet_revealed")
         .add_attribute("bet_id", bet_id.to_string())
         .add_attribute("side", format!("{:?}", side))
         .add_attribute("winner", winner.to_string())
-        .add_attribute("payout", payout.to_string())
-        .add_attribute("commission", commission.to_string())
-        .add_attribute("treasury", config.treasury.to_string()))
+        .add_attribute("payout", settlement.payout.to_string())
+        .add_attribute("commission", settlement.commission.to_string())
+        .add_attribute("treasury", config.treasury.to_string())
+        .add_event(events::bet_revealed(&bet, &acceptor, &acceptor_guess, &side, &config.treasury))
+        .add_events(settlement.events)
+        .add_submessages(settlement.hooks)
+        .set_data(to_json_binary(&BetOutcomeResponse::from_bet(&bet))?))
 }
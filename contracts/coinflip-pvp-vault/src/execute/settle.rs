@@ -0,0 +1,114 @@
+use cosmwasm_std::{Addr, DepsMut, Env, Event, SubMsg, Uint128};
+
+use crate::error::ContractError;
+use crate::execute::bonus::release_stake;
+use crate::execute::fees::{commission_bps_for, credit_commission};
+use crate::execute::hooks::bet_resolved_hooks;
+use crate::execute::jackpot::fund_jackpots;
+use crate::execute::rakeback::fund_rakeback;
+use crate::execute::referrals::credit_referral_rewards;
+use crate::state::{
+    save_balance, Bet, BetStatus, Config, VaultBalance, BETS, USER_OPEN_BET_COUNT, VAULT_BALANCES,
+};
+use crate::stats;
+
+/// What a played bet paid out, with the events and hook calls for the response.
+pub struct Settlement {
+    pub commission: Uint128,
+    pub payout: Uint128,
+    pub events: Vec<Event>,
+    pub hooks: Vec<SubMsg>,
+}
+
+/// Pay out a played bet: `Reveal`, `AcceptAndReveal` and `ClaimTimeout` all end here.
+///
+/// Takes the commission from the pot, releases both stakes with the rest going to
+/// `winner`, then pays the winner's referrers, funds the jackpots, reserves rakeback
+/// and credits the treasury or accrues the commission split. The bet is recorded with
+/// `status` and saved.
+///
+/// `bet.acceptor` must be set and their stake locked in `acceptor_bal`, which the
+/// caller may not have saved yet.
+pub fn settle_bet(
+    deps: DepsMut,
+    env: &Env,
+    config: &Config,
+    bet: &mut Bet,
+    mut acceptor_bal: VaultBalance,
+    winner: &Addr,
+    status: BetStatus,
+) -> Result<Settlement, ContractError> {
+    let acceptor = bet.acceptor.clone().unwrap();
+    let pot = bet.amount * Uint128::new(2);
+    let now = env.block.time.seconds();
+    let (commission_bps, discount_events) =
+        commission_bps_for(deps.storage, deps.querier, config, now, bet, winner)?;
+    let commission = pot * Uint128::from(commission_bps) / Uint128::new(10_000);
+    let payout = pot - commission;
+
+    // Unlock both stakes and credit the winner
+    let mut maker_bal = VAULT_BALANCES.load(deps.storage, &bet.maker)?;
+    let (maker_returned, acceptor_returned) = if *winner == bet.maker {
+        (payout, Uint128::zero())
+    } else {
+        (Uint128::zero(), payout)
+    };
+    let mut events = vec![];
+    let mut bonus_events = release_stake(
+        deps.storage,
+        &bet.maker,
+        &mut maker_bal,
+        bet.amount,
+        bet.maker_bonus,
+        maker_returned,
+        true,
+    )?;
+    bonus_events.extend(release_stake(
+        deps.storage,
+        &acceptor,
+        &mut acceptor_bal,
+        bet.amount,
+        bet.acceptor_bonus,
+        acceptor_returned,
+        true,
+    )?);
+    events.push(save_balance(deps.storage, &bet.maker, &maker_bal)?);
+    events.push(save_balance(deps.storage, &acceptor, &acceptor_bal)?);
+    events.extend(bonus_events);
+    events.extend(discount_events);
+
+    // Pay the winner's referrers, fund the jackpots and reserve rakeback, then credit
+    // the treasury or accrue the commission split
+    let (commission_left, referral_events) =
+        credit_referral_rewards(deps.storage, config, bet, winner, pot, commission)?;
+    events.extend(referral_events);
+    let (commission_left, jackpot_events) =
+        fund_jackpots(deps.storage, config, env, bet, &acceptor, commission_left)?;
+    events.extend(jackpot_events);
+    let (commission_left, rakeback_events) =
+        fund_rakeback(deps.storage, config, env, bet, &acceptor, commission, commission_left)?;
+    events.extend(rakeback_events);
+    events.extend(credit_commission(deps.storage, config, commission_left)?);
+
+    // Decrement maker's open bet count
+    let open_count = USER_OPEN_BET_COUNT
+        .may_load(deps.storage, &bet.maker)?
+        .unwrap_or(0);
+    USER_OPEN_BET_COUNT.save(deps.storage, &bet.maker, &open_count.saturating_sub(1))?;
+
+    bet.status = status;
+    bet.resolved_at_height = Some(env.block.height);
+    bet.payout_winner = Some(winner.clone());
+    bet.commission_paid = commission;
+    bet.commission_bps = Some(commission_bps);
+    bet.payout_amount = payout;
+    stats::record_bet(deps.storage, bet, Some(now))?;
+    BETS.save(deps.storage, bet.id, bet)?;
+
+    Ok(Settlement {
+        commission,
+        payout,
+        events,
+        hooks: bet_resolved_hooks(deps.storage, bet)?,
+    })
+}
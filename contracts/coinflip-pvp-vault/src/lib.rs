@@ -1,9 +1,13 @@
 pub mod contract;
 pub mod error;
+pub mod events;
 pub mod execute;
+pub mod leaderboard;
+pub mod migrations;
 pub mod msg;
 pub mod query;
 pub mod state;
+pub mod stats;
 
 #[cfg(test)]
 pub mod testing;
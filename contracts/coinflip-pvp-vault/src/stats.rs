@@ -0,0 +1,132 @@
+use cosmwasm_std::{Addr, StdResult, Storage, Uint128};
+
+use crate::execute::{limits, loyalty};
+use crate::leaderboard::{self, DAY_SECS};
+use crate::state::{
+    ActivityStats, Bet, BetStatus, CONFIG, PLAYER_STATS, SERIES_PLAYERS, STATS_SERIES,
+};
+
+pub const HOUR_SECS: u64 = 3_600;
+
+/// Add a finished bet to the stats of its players and mark it recorded.
+///
+/// Called when a bet resolves, is canceled or is refunded. `resolved_at` is the block
+/// time of that transition; it drives `last_played`, the win streaks, the period
+/// leaderboards, the activity series, loyalty XP and daily loss limits. Bets that
+/// finished before live stats existed are recorded by `PruneBets` with `None`, which
+/// only adds to the all-time player totals since their order is unknown. A bet over
+/// the pair caps only counts toward daily loss limits.
+pub fn record_bet(
+    storage: &mut dyn Storage,
+    bet: &mut Bet,
+    resolved_at: Option<u64>,
+) -> StdResult<()> {
+    if bet.stats_recorded {
+        return Ok(());
+    }
+
+    let played = matches!(bet.status, BetStatus::Revealed | BetStatus::TimeoutClaimed);
+    if bet.non_qualifying {
+        if let Some(time) = resolved_at.filter(|_| played) {
+            let players = std::iter::once(&bet.maker).chain(bet.acceptor.as_ref());
+            for player in players {
+                let won = bet.payout_winner.as_ref() == Some(player);
+                let returned = if won { bet.payout_amount } else { Uint128::zero() };
+                limits::record_result(storage, player, time, bet.amount, returned)?;
+            }
+        }
+        bet.stats_recorded = true;
+        return Ok(());
+    }
+
+    if let Some(time) = resolved_at {
+        update_series(storage, time, |stats| {
+            if played {
+                stats.bets_resolved += 1;
+                stats.volume += bet.amount + bet.amount;
+                stats.commission += bet.commission_paid;
+            } else {
+                stats.bets_canceled += 1;
+            }
+        })?;
+    }
+
+    let xp_unit = match resolved_at {
+        Some(_) if played => CONFIG.load(storage)?.xp_unit,
+        _ => Uint128::zero(),
+    };
+    let players = std::iter::once(&bet.maker).chain(bet.acceptor.as_ref());
+    for player in players {
+        let mut stats = PLAYER_STATS.may_load(storage, player)?.unwrap_or_default();
+        if !played {
+            stats.canceled += 1;
+            PLAYER_STATS.save(storage, player, &stats)?;
+            continue;
+        }
+
+        let before = stats.clone();
+        stats.games += 1;
+        stats.wagered += bet.amount;
+        let won = bet.payout_winner.as_ref() == Some(player);
+        if won {
+            stats.wins += 1;
+            stats.won += bet.payout_amount;
+            // Commission comes out of the pot the winner collects
+            stats.commission_paid += bet.commission_paid;
+        } else {
+            stats.losses += 1;
+        }
+
+        if let Some(time) = resolved_at {
+            stats.current_streak = if won { stats.current_streak + 1 } else { 0 };
+            stats.best_streak = stats.best_streak.max(stats.current_streak);
+            stats.last_played = time;
+            loyalty::award_xp(storage, xp_unit, player, bet.amount)?;
+            let returned = if won { bet.payout_amount } else { Uint128::zero() };
+            limits::record_result(storage, player, time, bet.amount, returned)?;
+        }
+        PLAYER_STATS.save(storage, player, &stats)?;
+        leaderboard::record_game(storage, player, &before, &stats, resolved_at)?;
+    }
+
+    bet.stats_recorded = true;
+    Ok(())
+}
+
+/// Count a new bet, and its maker as a player, in the activity series.
+pub fn record_created(storage: &mut dyn Storage, maker: &Addr, time: u64) -> StdResult<()> {
+    update_series(storage, time, |stats| stats.bets_created += 1)?;
+    record_player(storage, maker, time)
+}
+
+/// Count `player` once per series bucket that covers `time`.
+pub fn record_player(storage: &mut dyn Storage, player: &Addr, time: u64) -> StdResult<()> {
+    for (granularity, bucket) in series_buckets(time) {
+        if SERIES_PLAYERS.has(storage, (granularity, bucket, player)) {
+            continue;
+        }
+        SERIES_PLAYERS.save(storage, (granularity, bucket, player), &())?;
+        let mut stats = STATS_SERIES.may_load(storage, (granularity, bucket))?.unwrap_or_default();
+        stats.unique_players += 1;
+        STATS_SERIES.save(storage, (granularity, bucket), &stats)?;
+    }
+    Ok(())
+}
+
+/// All-time totals, then the hour and day buckets of `time`.
+fn series_buckets(time: u64) -> [(&'static str, u64); 3] {
+    [("all", 0), ("hour", time / HOUR_SECS), ("day", time / DAY_SECS)]
+}
+
+fn update_series(
+    storage: &mut dyn Storage,
+    time: u64,
+    update: impl Fn(&mut ActivityStats),
+) -> StdResult<()> {
+    for key in series_buckets(time) {
+        let mut stats = STATS_SERIES.may_load(storage, key)?.unwrap_or_default();
+        update(&mut stats);
+        STATS_SERIES.save(storage, key, &stats)?;
+    }
+    Ok(())
+}
@@ -2,13 +2,21 @@
 pub mod helpers {
     use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage};
     use cosmwasm_std::{
-        from_json, to_json_binary, Binary, Env,
-        OwnedDeps, Response, Timestamp, Uint128,
+        from_json, to_json_binary, to_json_vec, Addr, Binary, ContractResult, Deps, DepsMut,
+        Empty, Env, MessageInfo, OwnedDeps, Response, StdError, StdResult, Storage, SystemError,
+        SystemResult, Timestamp, Uint128, WasmQuery,
     };
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use cw_multi_test::Executor;
     use sha2::{Digest, Sha256};
 
     use crate::contract::{execute, instantiate, query};
     use crate::msg::*;
+    use crate::state::{
+    VaultTotals, ACCRUED_FEES, BETS, CONFIG, JACKPOT_POOLS, NEXT_BET_ID, RAKEBACK_EPOCHS,
+    USER_OPEN_BET_COUNT, VAULT_BALANCES, VAULT_TOTALS,
+};
 
     pub const ADMIN: &str = "admin";
     pub const TREASURY: &str = "treasury";
@@ -42,6 +50,97 @@ pub mod helpers {
         (deps, env)
     }
 
+    /// Instantiate the vault in a cw-multi-test App, with the sudo and reply entry points wired up
+    pub fn setup_app() -> (cw_multi_test::App, Addr) {
+        let mut app = cw_multi_test::App::default();
+        let code = cw_multi_test::ContractWrapper::new(execute, instantiate, query)
+            .with_sudo(crate::contract::sudo)
+            .with_reply(crate::contract::reply);
+        let code_id = app.store_code(Box::new(code));
+        let vault = app
+            .instantiate_contract(
+                code_id,
+                Addr::unchecked(ADMIN),
+                &default_instantiate_msg(),
+                &[],
+                "vault",
+                None,
+            )
+            .unwrap();
+        (app, vault)
+    }
+
+    /// Deposit into the vault of a multi-test App, as a `Receive` from the token contract
+    pub fn app_deposit(app: &mut cw_multi_test::App, vault: &Addr, user: &str, amount: u128) {
+        let cw20_msg = cw20::Cw20ReceiveMsg {
+            sender: user.to_string(),
+            amount: Uint128::new(amount),
+            msg: to_json_binary(&ReceiveMsg::Deposit {}).unwrap(),
+        };
+        app.execute_contract(
+            Addr::unchecked(TOKEN_CW20),
+            vault.clone(),
+            &ExecuteMsg::Receive(cw20_msg),
+            &[],
+        )
+        .unwrap();
+    }
+
+    /// Hook contract for multi-test: stores the last `HookMsg` it received and returns
+    /// it from any query. Instantiated with `true`, every call fails instead.
+    pub fn hook_receiver() -> Box<dyn cw_multi_test::Contract<Empty>> {
+        Box::new(cw_multi_test::ContractWrapper::new(
+            |deps: DepsMut, _: Env, _: MessageInfo, msg: HookMsg| -> StdResult<Response> {
+                if deps.storage.get(b"fail").is_some() {
+                    return Err(StdError::generic_err("hook receiver failed"));
+                }
+                deps.storage.set(b"last", &to_json_vec(&msg)?);
+                Ok(Response::new())
+            },
+            |deps: DepsMut, _: Env, _: MessageInfo, fail: bool| -> StdResult<Response> {
+                if fail {
+                    deps.storage.set(b"fail", b"1");
+                }
+                Ok(Response::new())
+            },
+            |deps: Deps, _: Env, _: Empty| -> StdResult<Binary> {
+                let last: Option<HookMsg> = deps.storage.get(b"last").map(from_json).transpose()?;
+                to_json_binary(&last)
+            },
+        ))
+    }
+
+    /// Storage as written by a v0.6.0 deployment: Config without the withdraw policy,
+    /// VaultBalance without `pending_withdrawal`, no withdrawal id counter.
+    /// Values are raw JSON so the fixture does not depend on the current structs.
+    pub fn setup_v0_6_0_fixture() -> (OwnedDeps<MockStorage, MockApi, MockQuerier>, Env) {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        cw2::set_contract_version(&mut deps.storage, "crates.io:coinflip-pvp-vault", "0.6.0")
+            .unwrap();
+        deps.storage.set(
+            CONFIG.as_slice(),
+            br#"{"admin":"admin","token_cw20":"launch_token","treasury":"treasury","commission_bps":1000,"min_bet":"10","reveal_timeout_secs":300,"max_open_per_user":10,"max_daily_amount_per_user":"10000","bet_ttl_secs":10800}"#,
+        );
+        deps.storage.set(NEXT_BET_ID.as_slice(), b"2");
+        deps.storage.set(
+            &VAULT_BALANCES.key(&Addr::unchecked(MAKER)),
+            br#"{"available":"400","locked":"100"}"#,
+        );
+        deps.storage.set(
+            &VAULT_BALANCES.key(&Addr::unchecked(ACCEPTOR)),
+            br#"{"available":"250","locked":"0"}"#,
+        );
+        deps.storage.set(
+            &BETS.key(1),
+            br#"{"id":1,"maker":"maker_user","amount":"100","commitment":"AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=","status":"open","created_at_height":12345,"created_at_time":1571797419,"acceptor":null,"acceptor_guess":null,"accepted_at_height":null,"accepted_at_time":null,"reveal_secret":null,"reveal_side":null,"resolved_at_height":null,"payout_winner":null,"commission_paid":"0","payout_amount":"0"}"#,
+        );
+        deps.storage.set(&USER_OPEN_BET_COUNT.key(&Addr::unchecked(MAKER)), b"1");
+
+        (deps, env)
+    }
+
     /// Deposit LAUNCH tokens for a user (simulates CW20 Send)
     pub fn deposit(
         deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
@@ -75,6 +174,46 @@ pub mod helpers {
         Binary::from(hasher.finalize().to_vec())
     }
 
+    /// Key the tests sign promo vouchers with, and its compressed public key
+    pub fn promo_key() -> (k256::ecdsa::SigningKey, Binary) {
+        let key = k256::ecdsa::SigningKey::from_slice(&[7u8; 32]).unwrap();
+        let pubkey = Binary::from(key.verifying_key().to_encoded_point(true).as_bytes());
+        (key, pubkey)
+    }
+
+    /// A promo voucher for the vault at `env.contract.address`, signed with `key`.
+    /// `player: None` lets anyone redeem it.
+    pub fn sign_voucher(
+        key: &k256::ecdsa::SigningKey,
+        env: &Env,
+        id: &str,
+        grant: PromoGrant,
+        expires_at: u64,
+        max_uses: u32,
+        player: Option<&str>,
+    ) -> PromoVoucher {
+        use k256::ecdsa::signature::hazmat::PrehashSigner;
+
+        let payload = PromoVoucherPayload {
+            contract: env.contract.address.to_string(),
+            id: id.to_string(),
+            grant: grant.clone(),
+            expires_at,
+            max_uses,
+            player: player.map(str::to_string),
+        };
+        let hash = Sha256::digest(to_json_vec(&payload).unwrap());
+        let signature: k256::ecdsa::Signature = key.sign_prehash(&hash).unwrap();
+        PromoVoucher {
+            id: id.to_string(),
+            grant,
+            expires_at,
+            max_uses,
+            player: player.map(str::to_string),
+            signature: Binary::from(signature.to_bytes().as_slice()),
+        }
+    }
+
     pub fn create_bet(
         deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
         env: &Env,
@@ -90,6 +229,7 @@ pub mod helpers {
             ExecuteMsg::CreateBet {
                 amount: Uint128::new(amount),
                 commitment,
+                voucher: None,
             },
         )
     }
@@ -106,7 +246,11 @@ pub mod helpers {
             deps.as_mut(),
             env.clone(),
             info,
-            ExecuteMsg::AcceptBet { bet_id, guess },
+            ExecuteMsg::AcceptBet {
+                bet_id,
+                guess,
+                voucher: None,
+            },
         )
     }
 
@@ -161,6 +305,103 @@ pub mod helpers {
         )
     }
 
+    pub fn withdraw(
+        deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+        env: &Env,
+        user: &str,
+        amount: u128,
+    ) -> Result<Response, crate::error::ContractError> {
+        let info = mock_info(user, &[]);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            ExecuteMsg::Withdraw { amount: Uint128::new(amount) },
+        )
+    }
+
+    pub fn claim_withdrawal(
+        deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+        env: &Env,
+        sender: &str,
+        withdrawal_id: u64,
+    ) -> Result<Response, crate::error::ContractError> {
+        let info = mock_info(sender, &[]);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            ExecuteMsg::ClaimWithdrawal { withdrawal_id },
+        )
+    }
+
+    pub fn process_shutdown(
+        deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+        env: &Env,
+        limit: u32,
+    ) -> Result<Response, crate::error::ContractError> {
+        let info = mock_info(RANDOM_USER, &[]);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            ExecuteMsg::ProcessShutdown { limit: Some(limit) },
+        )
+    }
+
+    pub fn prune_bets(
+        deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+        env: &Env,
+        before_id: u64,
+        limit: u32,
+    ) -> Result<Response, crate::error::ContractError> {
+        let info = mock_info(RANDOM_USER, &[]);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            ExecuteMsg::PruneBets {
+                before_id,
+                limit: Some(limit),
+            },
+        )
+    }
+
+    pub fn continue_migration(
+        deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+        env: &Env,
+        limit: u32,
+    ) -> Result<Response, crate::error::ContractError> {
+        let info = mock_info(RANDOM_USER, &[]);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            ExecuteMsg::ContinueMigration { limit: Some(limit) },
+        )
+    }
+
+    /// Admin: delay withdrawals above `threshold` by `delay_secs`, with an optional guardian
+    pub fn set_withdraw_policy(
+        deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+        env: &Env,
+        threshold: u128,
+        delay_secs: u64,
+        guardian: Option<&str>,
+    ) {
+        let info = mock_info(ADMIN, &[]);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            ExecuteMsg::UpdateWithdrawPolicy {
+                threshold: Uint128::new(threshold),
+                delay_secs,
+                guardian: guardian.map(|g| g.to_string()),
+            },
+        ).unwrap();
+    }
+
     pub fn query_config(
         deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>,
         env: &Env,
@@ -203,6 +444,168 @@ pub mod helpers {
         from_json(&res).unwrap()
     }
 
+    pub fn query_player_stats(
+        deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>,
+        env: &Env,
+        address: &str,
+    ) -> PlayerStatsResponse {
+        let res = query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::PlayerStats { address: address.to_string() },
+        ).unwrap();
+        from_json(&res).unwrap()
+    }
+
+    pub fn query_leaderboard(
+        deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>,
+        env: &Env,
+        metric: LeaderboardMetric,
+        period: LeaderboardPeriod,
+        start_after: Option<&str>,
+        limit: u32,
+    ) -> LeaderboardResponse {
+        let res = query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Leaderboard {
+                metric,
+                period,
+                start_after: start_after.map(|a| a.to_string()),
+                limit: Some(limit),
+            },
+        ).unwrap();
+        from_json(&res).unwrap()
+    }
+
+    pub fn query_stats_series(
+        deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>,
+        env: &Env,
+        granularity: StatsGranularity,
+        from: u64,
+        to: u64,
+    ) -> StatsSeriesResponse {
+        let res = query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::StatsSeries { granularity, from, to },
+        ).unwrap();
+        from_json(&res).unwrap()
+    }
+
+    pub fn query_pending_withdrawals(
+        deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>,
+        env: &Env,
+        address: Option<&str>,
+    ) -> PendingWithdrawalsResponse {
+        let res = query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::PendingWithdrawals {
+                address: address.map(|a| a.to_string()),
+                start_after: None,
+                limit: None,
+            },
+        ).unwrap();
+        from_json(&res).unwrap()
+    }
+
+    pub fn query_export_state(
+        deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>,
+        env: &Env,
+        start_after: Option<String>,
+        limit: u32,
+    ) -> ExportStateResponse {
+        let res = query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::ExportState { start_after, limit: Some(limit) },
+        ).unwrap();
+        from_json(&res).unwrap()
+    }
+
+    pub type SharedDeps = Rc<RefCell<OwnedDeps<MockStorage, MockApi, MockQuerier>>>;
+
+    /// Answer smart queries to `addr` from the vault state in `source`, standing in for
+    /// another vault deployed there.
+    pub fn mock_source_vault(
+        deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+        env: &Env,
+        addr: &'static str,
+        source: &SharedDeps,
+    ) {
+        let env = env.clone();
+        let source = source.clone();
+        deps.querier.update_wasm(move |request| match request {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == addr => {
+                let res = query(source.borrow().as_ref(), env.clone(), from_json(msg).unwrap());
+                SystemResult::Ok(ContractResult::from(res))
+            }
+            _ => SystemResult::Err(SystemError::NoSuchContract { addr: addr.to_string() }),
+        });
+    }
+
+    /// Set the CW20 balance the contract holds, as seen by balance queries
+    pub fn set_contract_balance(
+        deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+        _env: &Env,
+        amount: u128,
+    ) {
+        deps.querier.update_wasm(move |_| {
+            let res = cw20::BalanceResponse {
+                balance: Uint128::new(amount),
+            };
+            SystemResult::Ok(ContractResult::Ok(to_json_binary(&res).unwrap()))
+        });
+    }
+
+    /// Check the running vault totals against a full pass over VAULT_BALANCES, ACCRUED_FEES,
+    /// JACKPOT_POOLS and RAKEBACK_EPOCHS
+    pub fn assert_totals_consistent(deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>) {
+        let mut expected = VaultTotals::default();
+        for item in VAULT_BALANCES.range(&deps.storage, None, None, cosmwasm_std::Order::Ascending) {
+            let (_, balance) = item.unwrap();
+            expected.total_available += balance.available;
+            expected.total_locked += balance.locked;
+            expected.total_pending_withdrawal += balance.pending_withdrawal;
+            expected.total_bonus += balance.bonus;
+        }
+        for item in ACCRUED_FEES.range(&deps.storage, None, None, cosmwasm_std::Order::Ascending) {
+            expected.total_accrued_fees += item.unwrap().1;
+        }
+        for item in JACKPOT_POOLS.range(&deps.storage, None, None, cosmwasm_std::Order::Ascending) {
+            let pool = item.unwrap().1;
+            expected.total_jackpot += pool.amount;
+            expected.total_jackpot += pool.filled.map(|fill| fill.amount).unwrap_or_default();
+        }
+        for item in RAKEBACK_EPOCHS.range(&deps.storage, None, None, cosmwasm_std::Order::Ascending) {
+            let reserve = item.unwrap().1;
+            if !reserve.swept {
+                expected.total_rakeback += reserve.reserved - reserve.claimed;
+            }
+        }
+        assert_eq!(VAULT_TOTALS.load(&deps.storage).unwrap(), expected);
+    }
+
+    /// Attributes of every event of type `ty`, in emission order
+    pub fn events_of(res: &Response, ty: &str) -> Vec<Vec<(String, String)>> {
+        res.events
+            .iter()
+            .filter(|e| e.ty == ty)
+            .map(|e| {
+                e.attributes
+                    .iter()
+                    .map(|a| (a.key.clone(), a.value.clone()))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Value of `key` in a single event's attributes
+    pub fn attr<'a>(attrs: &'a [(String, String)], key: &str) -> &'a str {
+        &attrs.iter().find(|(k, _)| k == key).unwrap().1
+    }
+
     /// Create an env with a specific block time
     pub fn env_at_time(secs: u64) -> Env {
         let mut env = mock_env();
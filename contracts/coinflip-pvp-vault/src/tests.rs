@@ -1,6 +1,7 @@
 use cosmwasm_std::{Uint128, Binary};
 use crate::error::ContractError;
 use crate::msg::Side;
+use crate::state::BetStatus;
 use crate::testing::helpers::*;
 
 // ============================================================
@@ -109,6 +110,191 @@ fn test_withdraw_insufficient_balance() {
     }
 }
 
+// ============================================================
+// Delayed withdrawals
+// ============================================================
+
+#[test]
+fn test_withdraw_below_threshold_is_instant() {
+    let (mut deps, env) = setup_contract();
+    set_withdraw_policy(&mut deps, &env, 1_000, 3600, None);
+    deposit(&mut deps, &env, MAKER, 5_000).unwrap();
+
+    let res = withdraw(&mut deps, &env, MAKER, 1_000).unwrap();
+    assert_eq!(res.messages.len(), 1);
+
+    let balance = query_vault_balance(&deps, &env, MAKER);
+    assert_eq!(balance.available, Uint128::new(4_000));
+    assert_eq!(balance.pending_withdrawal, Uint128::zero());
+}
+
+#[test]
+fn test_large_withdraw_is_delayed_then_claimed() {
+    let (mut deps, _) = setup_contract();
+    let env = env_at_time(1000);
+    set_withdraw_policy(&mut deps, &env, 1_000, 3600, None);
+    deposit(&mut deps, &env, MAKER, 5_000).unwrap();
+
+    let res = withdraw(&mut deps, &env, MAKER, 3_000).unwrap();
+    assert!(res.messages.is_empty());
+    assert!(res.attributes.iter().any(|a| a.key == "action" && a.value == "withdraw_requested"));
+    assert!(res.attributes.iter().any(|a| a.key == "unlocks_at" && a.value == "4600"));
+
+    let balance = query_vault_balance(&deps, &env, MAKER);
+    assert_eq!(balance.available, Uint128::new(2_000));
+    assert_eq!(balance.pending_withdrawal, Uint128::new(3_000));
+
+    let pending = query_pending_withdrawals(&deps, &env, Some(MAKER));
+    assert_eq!(pending.withdrawals.len(), 1);
+    assert_eq!(pending.withdrawals[0].amount, Uint128::new(3_000));
+
+    // Too early
+    let err = claim_withdrawal(&mut deps, &env_at_time(4599), MAKER, 1).unwrap_err();
+    assert!(matches!(err, ContractError::WithdrawalLocked { id: 1, unlocks_at: 4600 }));
+
+    // Only the owner can claim
+    let err = claim_withdrawal(&mut deps, &env_at_time(4600), RANDOM_USER, 1).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized));
+
+    let res = claim_withdrawal(&mut deps, &env_at_time(4600), MAKER, 1).unwrap();
+    assert_eq!(res.messages.len(), 1);
+
+    let balance = query_vault_balance(&deps, &env, MAKER);
+    assert_eq!(balance.available, Uint128::new(2_000));
+    assert_eq!(balance.pending_withdrawal, Uint128::zero());
+    assert!(query_pending_withdrawals(&deps, &env, None).withdrawals.is_empty());
+}
+
+#[test]
+fn test_split_withdrawals_add_up_over_the_delay_window() {
+    let (mut deps, _) = setup_contract();
+    let env = env_at_time(1000);
+    set_withdraw_policy(&mut deps, &env, 1_000, 3600, None);
+    deposit(&mut deps, &env, MAKER, 5_000).unwrap();
+
+    // Several withdrawals below the threshold cannot pay out more than it
+    assert_eq!(withdraw(&mut deps, &env, MAKER, 600).unwrap().messages.len(), 1);
+    assert_eq!(withdraw(&mut deps, &env, MAKER, 400).unwrap().messages.len(), 1);
+    let res = withdraw(&mut deps, &env_at_time(4599), MAKER, 1).unwrap();
+    assert!(res.messages.is_empty());
+    assert_eq!(query_vault_balance(&deps, &env, MAKER).pending_withdrawal, Uint128::new(1));
+
+    // Each instant withdrawal stops counting once the delay window has passed
+    let res = withdraw(&mut deps, &env_at_time(4600), MAKER, 1_000).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    let balance = query_vault_balance(&deps, &env, MAKER);
+    assert_eq!(balance.available, Uint128::new(2_999));
+}
+
+#[test]
+fn test_cancel_pending_withdrawal() {
+    let (mut deps, env) = setup_contract();
+    set_withdraw_policy(&mut deps, &env, 1_000, 3600, None);
+    deposit(&mut deps, &env, MAKER, 5_000).unwrap();
+    withdraw(&mut deps, &env, MAKER, 3_000).unwrap();
+
+    let info = cosmwasm_std::testing::mock_info(MAKER, &[]);
+    crate::contract::execute(
+        deps.as_mut(), env.clone(), info,
+        crate::msg::ExecuteMsg::CancelWithdrawal { withdrawal_id: 1 },
+    ).unwrap();
+
+    let balance = query_vault_balance(&deps, &env, MAKER);
+    assert_eq!(balance.available, Uint128::new(5_000));
+    assert_eq!(balance.pending_withdrawal, Uint128::zero());
+
+    let err = claim_withdrawal(&mut deps, &env, MAKER, 1).unwrap_err();
+    assert!(matches!(err, ContractError::WithdrawalNotFound { id: 1 }));
+}
+
+#[test]
+fn test_guardian_freezes_pending_withdrawal() {
+    let (mut deps, _) = setup_contract();
+    let env = env_at_time(1000);
+    set_withdraw_policy(&mut deps, &env, 1_000, 3600, Some("guardian"));
+    deposit(&mut deps, &env, MAKER, 5_000).unwrap();
+    withdraw(&mut deps, &env, MAKER, 3_000).unwrap();
+
+    // Random users cannot freeze
+    let info = cosmwasm_std::testing::mock_info(RANDOM_USER, &[]);
+    let err = crate::contract::execute(
+        deps.as_mut(), env.clone(), info,
+        crate::msg::ExecuteMsg::FreezeWithdrawal { withdrawal_id: 1 },
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized));
+
+    // Not once the withdrawal has unlocked (at 4600)
+    let info = cosmwasm_std::testing::mock_info("guardian", &[]);
+    let err = crate::contract::execute(
+        deps.as_mut(), env_at_time(4600), info.clone(),
+        crate::msg::ExecuteMsg::FreezeWithdrawal { withdrawal_id: 1 },
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::WithdrawalUnlocked { id: 1, unlocks_at: 4600 }));
+
+    crate::contract::execute(
+        deps.as_mut(), env_at_time(4599), info.clone(),
+        crate::msg::ExecuteMsg::FreezeWithdrawal { withdrawal_id: 1 },
+    ).unwrap();
+
+    let err = claim_withdrawal(&mut deps, &env_at_time(5000), MAKER, 1).unwrap_err();
+    assert!(matches!(err, ContractError::WithdrawalFrozen { id: 1 }));
+
+    // Nor can the owner take the frozen funds back to withdraw them in small amounts
+    let err = crate::contract::execute(
+        deps.as_mut(), env.clone(), cosmwasm_std::testing::mock_info(MAKER, &[]),
+        crate::msg::ExecuteMsg::CancelWithdrawal { withdrawal_id: 1 },
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::WithdrawalFrozen { id: 1 }));
+
+    // Guardian cannot unfreeze — only the admin can
+    let err = crate::contract::execute(
+        deps.as_mut(), env.clone(), info,
+        crate::msg::ExecuteMsg::UnfreezeWithdrawal { withdrawal_id: 1 },
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized));
+
+    let info = cosmwasm_std::testing::mock_info(ADMIN, &[]);
+    crate::contract::execute(
+        deps.as_mut(), env.clone(), info,
+        crate::msg::ExecuteMsg::UnfreezeWithdrawal { withdrawal_id: 1 },
+    ).unwrap();
+
+    claim_withdrawal(&mut deps, &env_at_time(5000), MAKER, 1).unwrap();
+}
+
+#[test]
+fn test_update_withdraw_policy_validation() {
+    let (mut deps, env) = setup_contract();
+
+    let info = cosmwasm_std::testing::mock_info(MAKER, &[]);
+    let err = crate::contract::execute(
+        deps.as_mut(), env.clone(), info,
+        crate::msg::ExecuteMsg::UpdateWithdrawPolicy {
+            threshold: Uint128::new(1_000),
+            delay_secs: 3600,
+            guardian: None,
+        },
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized));
+
+    let info = cosmwasm_std::testing::mock_info(ADMIN, &[]);
+    let err = crate::contract::execute(
+        deps.as_mut(), env.clone(), info,
+        crate::msg::ExecuteMsg::UpdateWithdrawPolicy {
+            threshold: Uint128::new(1_000),
+            delay_secs: 10,
+            guardian: None,
+        },
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidTimeout { .. }));
+
+    set_withdraw_policy(&mut deps, &env, 1_000, 7200, Some("guardian"));
+    let config = query_config(&deps, &env);
+    assert_eq!(config.withdraw_delay_threshold, Uint128::new(1_000));
+    assert_eq!(config.withdraw_delay_secs, 7200);
+    assert_eq!(config.guardian.unwrap().as_str(), "guardian");
+}
+
 // ============================================================
 // Create Bet
 // ============================================================
@@ -134,7 +320,7 @@ fn test_create_bet_success() {
     let bet = query_bet(&deps, &env, 1);
     assert_eq!(bet.id, 1);
     assert_eq!(bet.amount, Uint128::new(100));
-    assert_eq!(bet.status, "open");
+    assert_eq!(bet.status, BetStatus::Open);
 }
 
 #[test]
@@ -208,7 +394,7 @@ fn test_cancel_bet_success() {
 
     // Bet status
     let bet = query_bet(&deps, &env, 1);
-    assert_eq!(bet.status, "canceled");
+    assert_eq!(bet.status, BetStatus::Canceled);
 }
 
 #[test]
@@ -265,7 +451,7 @@ fn test_accept_bet_success() {
     assert_eq!(balance.locked, Uint128::new(100));
 
     let bet = query_bet(&deps, &env, 1);
-    assert_eq!(bet.status, "accepted");
+    assert_eq!(bet.status, BetStatus::Accepted);
     assert_eq!(bet.acceptor_guess, Some(Side::Tails));
 }
 
@@ -359,7 +545,7 @@ fn test_reveal_maker_wins() {
     assert_eq!(treasury_bal.available, Uint128::new(20));
 
     let bet = query_bet(&deps, &env, 1);
-    assert_eq!(bet.status, "revealed");
+    assert_eq!(bet.status, BetStatus::Revealed);
     assert_eq!(bet.winner, Some(cosmwasm_std::Addr::unchecked(MAKER)));
 }
 
@@ -540,7 +726,7 @@ fn test_claim_timeout_success() {
     assert_eq!(maker_bal.locked, Uint128::zero());
 
     let bet = query_bet(&deps, &env, 1);
-    assert_eq!(bet.status, "timeoutclaimed");
+    assert_eq!(bet.status, BetStatus::TimeoutClaimed);
 }
 
 #[test]
@@ -612,6 +798,93 @@ fn test_query_open_bets() {
     assert_eq!(open.bets.len(), 2);
 }
 
+#[test]
+fn test_query_open_bets_paging() {
+    let (mut deps, env) = setup_contract();
+    deposit(&mut deps, &env, MAKER, 5000).unwrap();
+
+    for i in 0..5 {
+        let commitment = compute_commitment(MAKER, &Side::Heads, format!("secret_{:032}", i).as_bytes());
+        create_bet(&mut deps, &env, MAKER, 100, commitment).unwrap();
+    }
+
+    let page = |start_after| -> crate::msg::BetsResponse {
+        let res = crate::contract::query(
+            deps.as_ref(),
+            env.clone(),
+            crate::msg::QueryMsg::OpenBets { start_after, limit: Some(2) },
+        ).unwrap();
+        cosmwasm_std::from_json(res).unwrap()
+    };
+
+    let first = page(None);
+    assert_eq!(first.bets.iter().map(|b| b.id).collect::<Vec<_>>(), vec![1, 2]);
+    assert_eq!(first.next_start_after, Some(2));
+
+    let second = page(first.next_start_after);
+    assert_eq!(second.bets.iter().map(|b| b.id).collect::<Vec<_>>(), vec![3, 4]);
+
+    // Short page: nothing left
+    let last = page(second.next_start_after);
+    assert_eq!(last.bets.iter().map(|b| b.id).collect::<Vec<_>>(), vec![5]);
+    assert_eq!(last.next_start_after, None);
+}
+
+#[test]
+fn test_bet_response_exposes_deadlines_and_secret() {
+    let (mut deps, _) = setup_contract();
+    let mut env = env_at_time(1000);
+    env.block.height = 50;
+    deposit(&mut deps, &env, MAKER, 500).unwrap();
+    deposit(&mut deps, &env, ACCEPTOR, 500).unwrap();
+
+    let secret = b"secret_32_bytes_exactly_here!!!!";
+    let commitment = compute_commitment(MAKER, &Side::Heads, secret);
+    create_bet(&mut deps, &env, MAKER, 100, commitment.clone()).unwrap();
+
+    let bet = query_bet(&deps, &env, 1);
+    assert_eq!(bet.created_at_height, 50);
+    assert_eq!(bet.created_at_time, 1000);
+    assert_eq!(bet.expires_at, Some(1000 + 10800));
+    assert_eq!(bet.reveal_deadline, None);
+    assert_eq!(bet.accepted_at_height, None);
+
+    env.block.height = 60;
+    env.block.time = cosmwasm_std::Timestamp::from_seconds(1100);
+    accept_bet(&mut deps, &env, ACCEPTOR, 1, Side::Tails).unwrap();
+
+    let bet = query_bet(&deps, &env, 1);
+    assert_eq!(bet.status, BetStatus::Accepted);
+    assert_eq!(bet.accepted_at_height, Some(60));
+    assert_eq!(bet.reveal_deadline, Some(1100 + 300));
+
+    env.block.height = 70;
+    reveal_bet(&mut deps, &env, MAKER, 1, Side::Heads, Binary::from(secret.to_vec())).unwrap();
+
+    // Everything needed to check the commitment comes from the one query
+    let bet = query_bet(&deps, &env, 1);
+    assert_eq!(bet.status, BetStatus::Revealed);
+    assert_eq!(bet.resolved_at_height, Some(70));
+    assert_eq!(bet.reveal_secret, Some(Binary::from(secret.to_vec())));
+    let side = bet.reveal_side.unwrap();
+    let secret = bet.reveal_secret.unwrap();
+    assert_eq!(compute_commitment(bet.maker.as_str(), &side, secret.as_slice()), bet.commitment);
+
+    // TTL disabled: no expiry
+    let info = cosmwasm_std::testing::mock_info(ADMIN, &[]);
+    let msg = crate::msg::ExecuteMsg::UpdateConfig {
+        treasury: None,
+        commission_bps: None,
+        min_bet: None,
+        reveal_timeout_secs: None,
+        max_open_per_user: None,
+        max_daily_amount_per_user: None,
+        bet_ttl_secs: Some(0),
+    };
+    crate::contract::execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(query_bet(&deps, &env, 1).expires_at, None);
+}
+
 // ============================================================
 // Full game flow end-to-end
 // ============================================================
@@ -642,7 +915,7 @@ fn test_full_game_flow_maker_wins() {
 
     // 5. Verify final state
     let bet = query_bet(&deps, &env, 1);
-    assert_eq!(bet.status, "revealed");
+    assert_eq!(bet.status, BetStatus::Revealed);
     assert_eq!(bet.winner, Some(cosmwasm_std::Addr::unchecked(MAKER)));
     assert_eq!(bet.payout_amount, Some(Uint128::new(360))); // 400 pot - 40 commission
     assert_eq!(bet.commission_paid, Some(Uint128::new(40)));
@@ -687,271 +960,3030 @@ fn test_full_game_flow_timeout_claim() {
 }
 
 // ============================================================
-// UpdateConfig
+// Events and response data
 // ============================================================
 
 #[test]
-fn test_update_config_admin_success() {
+fn test_balance_events_report_before_and_after() {
     let (mut deps, env) = setup_contract();
-    let info = cosmwasm_std::testing::mock_info(ADMIN, &[]);
-
-    let res = crate::contract::execute(
-        deps.as_mut(),
-        env.clone(),
-        info,
-        crate::msg::ExecuteMsg::UpdateConfig {
-            treasury: Some("new_treasury".to_string()),
-            commission_bps: Some(500),
-            min_bet: Some(Uint128::new(100)),
-            reveal_timeout_secs: Some(600),
-            max_open_per_user: Some(20),
-            max_daily_amount_per_user: None,
-            bet_ttl_secs: None,
-        },
-    ).unwrap();
-    assert!(res.attributes.iter().any(|a| a.key == "action" && a.value == "update_config"));
+    deposit(&mut deps, &env, MAKER, 500).unwrap();
 
-    let config = query_config(&deps, &env);
-    assert_eq!(config.treasury.as_str(), "new_treasury");
-    assert_eq!(config.commission_bps, 500);
-    assert_eq!(config.min_bet, Uint128::new(100));
-    assert_eq!(config.reveal_timeout_secs, 600);
-    assert_eq!(config.max_open_per_user, 20);
-    // unchanged
-    assert_eq!(config.max_daily_amount_per_user, Uint128::new(10_000));
+    let res = deposit(&mut deps, &env, MAKER, 200).unwrap();
+    let deposits = events_of(&res, "coinflip_deposit");
+    assert_eq!(deposits.len(), 1);
+    assert_eq!(attr(&deposits[0], "address"), MAKER);
+    assert_eq!(attr(&deposits[0], "amount"), "200");
+
+    let balances = events_of(&res, "coinflip_balance_changed");
+    assert_eq!(balances.len(), 1);
+    assert_eq!(attr(&balances[0], "address"), MAKER);
+    assert_eq!(attr(&balances[0], "available_before"), "500");
+    assert_eq!(attr(&balances[0], "available_after"), "700");
+    assert_eq!(attr(&balances[0], "locked_before"), "0");
+    assert_eq!(attr(&balances[0], "locked_after"), "0");
+
+    let commitment = compute_commitment(MAKER, &Side::Heads, b"secret");
+    let res = create_bet(&mut deps, &env, MAKER, 100, commitment).unwrap();
+    let balances = events_of(&res, "coinflip_balance_changed");
+    assert_eq!(attr(&balances[0], "available_after"), "600");
+    assert_eq!(attr(&balances[0], "locked_before"), "0");
+    assert_eq!(attr(&balances[0], "locked_after"), "100");
 }
 
 #[test]
-fn test_update_config_non_admin_rejected() {
+fn test_create_bet_returns_bet_id() {
     let (mut deps, env) = setup_contract();
-    let info = cosmwasm_std::testing::mock_info(MAKER, &[]);
+    deposit(&mut deps, &env, MAKER, 500).unwrap();
 
-    let err = crate::contract::execute(
-        deps.as_mut(),
-        env.clone(),
-        info,
-        crate::msg::ExecuteMsg::UpdateConfig {
-            treasury: None,
-            commission_bps: Some(200),
-            min_bet: None,
-            reveal_timeout_secs: None,
-            max_open_per_user: None,
-            max_daily_amount_per_user: None,
-            bet_ttl_secs: None,
-        },
-    ).unwrap_err();
-    assert!(matches!(err, ContractError::Unauthorized));
+    for expected_id in 1..=2u64 {
+        let commitment = compute_commitment(MAKER, &Side::Heads, b"secret");
+        let res = create_bet(&mut deps, &env, MAKER, 100, commitment).unwrap();
+
+        let data: crate::msg::CreateBetResponse =
+            cosmwasm_std::from_json(res.data.clone().unwrap()).unwrap();
+        assert_eq!(data.bet_id, expected_id);
+
+        let created = events_of(&res, "coinflip_bet_created");
+        assert_eq!(attr(&created[0], "bet_id"), expected_id.to_string());
+        assert_eq!(attr(&created[0], "maker"), MAKER);
+        assert_eq!(attr(&created[0], "amount"), "100");
+    }
 }
 
 #[test]
-fn test_update_config_commission_too_high() {
+fn test_reveal_returns_outcome() {
     let (mut deps, env) = setup_contract();
-    let info = cosmwasm_std::testing::mock_info(ADMIN, &[]);
+    deposit(&mut deps, &env, MAKER, 500).unwrap();
+    deposit(&mut deps, &env, ACCEPTOR, 500).unwrap();
 
-    let err = crate::contract::execute(
-        deps.as_mut(),
-        env.clone(),
-        info,
-        crate::msg::ExecuteMsg::UpdateConfig {
-            treasury: None,
-            commission_bps: Some(6000), // > 50%
-            min_bet: None,
-            reveal_timeout_secs: None,
-            max_open_per_user: None,
-            max_daily_amount_per_user: None,
-            bet_ttl_secs: None,
-        },
-    ).unwrap_err();
-    assert!(matches!(err, ContractError::InvalidCommission { .. }));
+    let secret = b"secret_32_bytes_exactly_here!!!!";
+    let commitment = compute_commitment(MAKER, &Side::Heads, secret);
+    create_bet(&mut deps, &env, MAKER, 100, commitment).unwrap();
+    let res = accept_bet(&mut deps, &env, ACCEPTOR, 1, Side::Tails).unwrap();
+    let accepted = events_of(&res, "coinflip_bet_accepted");
+    assert_eq!(attr(&accepted[0], "acceptor"), ACCEPTOR);
+    assert_eq!(attr(&accepted[0], "guess"), "tails");
+
+    let res = reveal_bet(
+        &mut deps, &env, MAKER, 1,
+        Side::Heads, Binary::from(secret.to_vec()),
+    ).unwrap();
+
+    let outcome: crate::msg::BetOutcomeResponse =
+        cosmwasm_std::from_json(res.data.clone().unwrap()).unwrap();
+    assert_eq!(outcome.bet_id, 1);
+    assert_eq!(outcome.status, BetStatus::Revealed);
+    assert_eq!(outcome.winner, cosmwasm_std::Addr::unchecked(MAKER));
+    assert_eq!(outcome.payout, Uint128::new(180));
+    assert_eq!(outcome.commission, Uint128::new(20));
+    assert_eq!(outcome.side, Some(Side::Heads));
+
+    let revealed = events_of(&res, "coinflip_bet_revealed");
+    assert_eq!(attr(&revealed[0], "winner"), MAKER);
+    assert_eq!(attr(&revealed[0], "side"), "heads");
+    assert_eq!(attr(&revealed[0], "guess"), "tails");
+    assert_eq!(attr(&revealed[0], "payout"), "180");
+
+    // Maker, acceptor and treasury
+    let balances = events_of(&res, "coinflip_balance_changed");
+    let addresses: Vec<&str> = balances.iter().map(|b| attr(b, "address")).collect();
+    assert_eq!(addresses, vec![MAKER, ACCEPTOR, TREASURY]);
+    assert_eq!(attr(&balances[0], "available_after"), "580");
+    assert_eq!(attr(&balances[1], "locked_after"), "0");
+    assert_eq!(attr(&balances[2], "available_after"), "20");
 }
 
 #[test]
-fn test_update_config_timeout_invalid() {
-    let (mut deps, env) = setup_contract();
-    let info = cosmwasm_std::testing::mock_info(ADMIN, &[]);
+fn test_claim_timeout_emits_single_action() {
+    let (mut deps, _) = setup_contract();
+    let env = env_at_time(1000);
+    deposit(&mut deps, &env, MAKER, 500).unwrap();
+    deposit(&mut deps, &env, ACCEPTOR, 500).unwrap();
 
-    // Too short
-    let err = crate::contract::execute(
-        deps.as_mut(),
-        env.clone(),
-        info.clone(),
-        crate::msg::ExecuteMsg::UpdateConfig {
-            treasury: None,
-            commission_bps: None,
-            min_bet: None,
-            reveal_timeout_secs: Some(10), // < 60
-            max_open_per_user: None,
-            max_daily_amount_per_user: None,
-            bet_ttl_secs: None,
-        },
-    ).unwrap_err();
-    assert!(matches!(err, ContractError::InvalidTimeout { .. }));
+    let commitment = compute_commitment(MAKER, &Side::Heads, b"secret");
+    create_bet(&mut deps, &env, MAKER, 100, commitment).unwrap();
+    accept_bet(&mut deps, &env, ACCEPTOR, 1, Side::Tails).unwrap();
 
-    // Too long
-    let err = crate::contract::execute(
-        deps.as_mut(),
-        env.clone(),
-        info,
-        crate::msg::ExecuteMsg::UpdateConfig {
-            treasury: None,
-            commission_bps: None,
-            min_bet: None,
-            reveal_timeout_secs: Some(100000), // > 86400
-            max_open_per_user: None,
-            max_daily_amount_per_user: None,
-            bet_ttl_secs: None,
-        },
-    ).unwrap_err();
-    assert!(matches!(err, ContractError::InvalidTimeout { .. }));
+    let res = claim_timeout(&mut deps, &env_at_time(1400), ACCEPTOR, 1).unwrap();
+    let actions: Vec<_> = res.attributes.iter().filter(|a| a.key == "action").collect();
+    assert_eq!(actions.len(), 1);
+    assert_eq!(actions[0].value, "coinflip.bet_timeout_claimed");
+
+    let outcome: crate::msg::BetOutcomeResponse =
+        cosmwasm_std::from_json(res.data.clone().unwrap()).unwrap();
+    assert_eq!(outcome.status, BetStatus::TimeoutClaimed);
+    assert_eq!(outcome.winner, cosmwasm_std::Addr::unchecked(ACCEPTOR));
+    assert_eq!(outcome.side, None);
+
+    let claimed = events_of(&res, "coinflip_bet_timeout_claimed");
+    assert_eq!(attr(&claimed[0], "winner"), ACCEPTOR);
+    assert_eq!(attr(&claimed[0], "commission"), "20");
+    assert_eq!(events_of(&res, "coinflip_balance_changed").len(), 3);
 }
 
 // ============================================================
-// TransferAdmin / AcceptAdmin
+// Emergency shutdown
 // ============================================================
 
 #[test]
-fn test_transfer_admin_success() {
+fn test_emergency_shutdown_refunds_live_bets() {
+    use crate::msg::{ExecuteMsg, PromoGrant, PromoVoucherResponse, QueryMsg};
+
     let (mut deps, env) = setup_contract();
+    deposit(&mut deps, &env, MAKER, 1_000).unwrap();
+    deposit(&mut deps, &env, ACCEPTOR, 1_000).unwrap();
+    let (key, pubkey) = promo_key();
+    crate::contract::execute(
+        deps.as_mut(), env.clone(), cosmwasm_std::testing::mock_info(ADMIN, &[]),
+        ExecuteMsg::UpdatePromoKey { pubkey: Some(pubkey) },
+    ).unwrap();
+    let redemptions = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| {
+        let msg = QueryMsg::PromoVoucher { id: "vip".to_string(), player: None };
+        let res = crate::contract::query(deps.as_ref(), env.clone(), msg).unwrap();
+        cosmwasm_std::from_json::<PromoVoucherResponse>(res).unwrap().redemptions
+    };
+
+    // Bet 1 open with a discount voucher, bet 2 accepted, bet 3 canceled
+    let grant = PromoGrant::CommissionDiscount { bps: 5_000 };
+    let now = env.block.time.seconds();
+    for i in 0..3 {
+        let commitment = compute_commitment(MAKER, &Side::Heads, format!("secret_{:032}", i).as_bytes());
+        let voucher = (i == 0)
+            .then(|| sign_voucher(&key, &env, "vip", grant.clone(), now + 100, 1, Some(MAKER)));
+        let msg = ExecuteMsg::CreateBet { amount: Uint128::new(100), commitment, voucher };
+        let info = cosmwasm_std::testing::mock_info(MAKER, &[]);
+        crate::contract::execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    }
+    assert_eq!(redemptions(&deps), 1);
+    accept_bet(&mut deps, &env, ACCEPTOR, 2, Side::Tails).unwrap();
+    cancel_bet(&mut deps, &env, MAKER, 3).unwrap();
+
+    // Only admin can trigger
+    let info = cosmwasm_std::testing::mock_info(MAKER, &[]);
+    let err = crate::contract::execute(
+        deps.as_mut(), env.clone(), info,
+        crate::msg::ExecuteMsg::EmergencyShutdown {},
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized));
 
-    // Step 1: current admin proposes new admin
     let info = cosmwasm_std::testing::mock_info(ADMIN, &[]);
-    let res = crate::contract::execute(
-        deps.as_mut(),
-        env.clone(),
-        info,
-        crate::msg::ExecuteMsg::TransferAdmin {
-            new_admin: MAKER.to_string(),
-        },
+    crate::contract::execute(
+        deps.as_mut(), env.clone(), info,
+        crate::msg::ExecuteMsg::EmergencyShutdown {},
     ).unwrap();
-    assert!(res.attributes.iter().any(|a| a.key == "action" && a.value == "transfer_admin"));
 
-    // Config still shows old admin
-    let config = query_config(&deps, &env);
-    assert_eq!(config.admin.as_str(), ADMIN);
+    // New bets and accepts are blocked right away
+    let commitment = compute_commitment(MAKER, &Side::Heads, b"secret_32_bytes_exactly_here!!!!");
+    let err = create_bet(&mut deps, &env, MAKER, 100, commitment).unwrap_err();
+    assert!(matches!(err, ContractError::ContractShutdown));
+    let err = accept_bet(&mut deps, &env, ACCEPTOR, 1, Side::Tails).unwrap_err();
+    assert!(matches!(err, ContractError::ContractShutdown));
 
-    // Step 2: new admin accepts
-    let info = cosmwasm_std::testing::mock_info(MAKER, &[]);
-    let res = crate::contract::execute(
-        deps.as_mut(),
-        env.clone(),
-        info,
-        crate::msg::ExecuteMsg::AcceptAdmin {},
+    // First batch covers bets 1 and 2; bet 1 never played, so its voucher use comes back
+    let res = process_shutdown(&mut deps, &env, 2).unwrap();
+    assert!(res.attributes.iter().any(|a| a.key == "refunded" && a.value == "2"));
+    assert!(res.attributes.iter().any(|a| a.key == "completed" && a.value == "false"));
+    let refunded = &events_of(&res, "coinflip_promo_refunded")[0];
+    assert_eq!((attr(refunded, "voucher_id"), attr(refunded, "bet_id")), ("vip", "1"));
+    assert_eq!(redemptions(&deps), 0);
+
+    // Second batch reaches the end
+    let res = process_shutdown(&mut deps, &env, 2).unwrap();
+    assert!(res.attributes.iter().any(|a| a.key == "completed" && a.value == "true"));
+
+    let err = process_shutdown(&mut deps, &env, 2).unwrap_err();
+    assert!(matches!(err, ContractError::ShutdownAlreadyCompleted));
+
+    assert_eq!(query_bet(&deps, &env, 1).status, BetStatus::Refunded);
+    assert_eq!(query_bet(&deps, &env, 2).status, BetStatus::Refunded);
+    assert_eq!(query_bet(&deps, &env, 3).status, BetStatus::Canceled);
+
+    let maker_bal = query_vault_balance(&deps, &env, MAKER);
+    assert_eq!(maker_bal.available, Uint128::new(1_000));
+    assert_eq!(maker_bal.locked, Uint128::zero());
+    let acceptor_bal = query_vault_balance(&deps, &env, ACCEPTOR);
+    assert_eq!(acceptor_bal.available, Uint128::new(1_000));
+    assert_eq!(acceptor_bal.locked, Uint128::zero());
+
+    // Withdrawals still work, deposits do not
+    withdraw(&mut deps, &env, MAKER, 1_000).unwrap();
+    let err = deposit(&mut deps, &env, MAKER, 100).unwrap_err();
+    assert!(matches!(err, ContractError::ContractShutdown));
+
+    let res = crate::contract::query(
+        deps.as_ref(), env.clone(),
+        crate::msg::QueryMsg::ShutdownStatus {},
     ).unwrap();
-    assert!(res.attributes.iter().any(|a| a.key == "action" && a.value == "accept_admin"));
+    let status: crate::msg::ShutdownStatusResponse = cosmwasm_std::from_json(res).unwrap();
+    assert!(status.active);
+    assert!(status.completed);
+    assert_eq!(status.refunded_bets, 2);
+    assert_eq!(status.last_bet_id, 3);
+}
 
-    // Config now shows new admin
-    let config = query_config(&deps, &env);
-    assert_eq!(config.admin.as_str(), MAKER);
+#[test]
+fn test_process_shutdown_requires_active_shutdown() {
+    let (mut deps, env) = setup_contract();
+
+    let err = process_shutdown(&mut deps, &env, 10).unwrap_err();
+    assert!(matches!(err, ContractError::ShutdownNotActive));
 }
 
+// ============================================================
+// Balance export / import
+// ============================================================
+
 #[test]
-fn test_transfer_admin_non_admin_rejected() {
+fn test_export_state_pagination() {
     let (mut deps, env) = setup_contract();
+    deposit(&mut deps, &env, MAKER, 500).unwrap();
+    deposit(&mut deps, &env, ACCEPTOR, 300).unwrap();
+    deposit(&mut deps, &env, RANDOM_USER, 200).unwrap();
 
-    let info = cosmwasm_std::testing::mock_info(MAKER, &[]);
-    let err = crate::contract::execute(
-        deps.as_mut(),
-        env.clone(),
-        info,
-        crate::msg::ExecuteMsg::TransferAdmin {
-            new_admin: MAKER.to_string(),
-        },
-    ).unwrap_err();
-    assert!(matches!(err, ContractError::Unauthorized));
+    // Locked funds are still owed and must be exported
+    let commitment = compute_commitment(MAKER, &Side::Heads, b"secret_32_bytes_exactly_here!!!!");
+    create_bet(&mut deps, &env, MAKER, 100, commitment).unwrap();
+
+    let page1 = query_export_state(&deps, &env, None, 2);
+    assert_eq!(page1.entries.len(), 2);
+    assert_eq!(page1.entries[0].address, ACCEPTOR);
+    assert_eq!(page1.entries[1].address, MAKER);
+    assert_eq!(page1.entries[1].amount, Uint128::new(500));
+    assert_eq!(page1.page_total, Uint128::new(800));
+    assert_eq!(page1.checksum, crate::msg::balance_entries_checksum(&page1.entries));
+    assert_eq!(page1.next_start_after, Some(MAKER.to_string()));
+
+    let page2 = query_export_state(&deps, &env, page1.next_start_after, 2);
+    assert_eq!(page2.entries.len(), 1);
+    assert_eq!(page2.entries[0].address, RANDOM_USER);
+    assert_eq!(page2.next_start_after, None);
+
+    let summary = |start_after: Option<String>| {
+        let msg = crate::msg::QueryMsg::ExportSummary { start_after, limit: Some(2) };
+        let res = crate::contract::query(deps.as_ref(), env.clone(), msg).unwrap();
+        cosmwasm_std::from_json::<crate::msg::ExportSummaryResponse>(res).unwrap()
+    };
+    let first = summary(None);
+    assert_eq!((first.total_entries, first.total_amount), (2, Uint128::new(800)));
+    assert_eq!(first.next_start_after, Some(MAKER.to_string()));
+    let second = summary(first.next_start_after);
+    assert_eq!((second.total_entries, second.total_amount), (1, Uint128::new(200)));
+    assert_eq!(second.next_start_after, None);
 }
 
 #[test]
-fn test_accept_admin_wrong_user_rejected() {
+fn test_import_balances_with_rate() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let (old_deps, env) = setup_contract();
+    let old_vault: SharedDeps = Rc::new(RefCell::new(old_deps));
+    deposit(&mut old_vault.borrow_mut(), &env, MAKER, 500).unwrap();
+    deposit(&mut old_vault.borrow_mut(), &env, ACCEPTOR, 300).unwrap();
+    let export = query_export_state(&old_vault.borrow(), &env, None, 10);
+
     let (mut deps, env) = setup_contract();
+    mock_source_vault(&mut deps, &env, "old_vault", &old_vault);
+    let admin = cosmwasm_std::testing::mock_info(ADMIN, &[]);
+    let import_msg = crate::msg::ExecuteMsg::ImportBalances {
+        source_contract: "old_vault".to_string(),
+        start_after: None,
+        entries: export.entries.clone(),
+        checksum: export.checksum.clone(),
+    };
+
+    // Source must be configured first
+    let err = crate::contract::execute(deps.as_mut(), env.clone(), admin.clone(), import_msg.clone())
+        .unwrap_err();
+    assert!(matches!(err, ContractError::ImportSourceNotConfigured { .. }));
 
-    // Admin proposes MAKER
-    let info = cosmwasm_std::testing::mock_info(ADMIN, &[]);
     crate::contract::execute(
-        deps.as_mut(),
-        env.clone(),
-        info,
-        crate::msg::ExecuteMsg::TransferAdmin {
-            new_admin: MAKER.to_string(),
+        deps.as_mut(), env.clone(), admin.clone(),
+        crate::msg::ExecuteMsg::ConfigureImport {
+            source_contract: "old_vault".to_string(),
+            rate_num: 3,
+            rate_denom: 2,
         },
     ).unwrap();
 
-    // ACCEPTOR tries to accept — should fail
-    let info = cosmwasm_std::testing::mock_info(ACCEPTOR, &[]);
+    // Non-admin rejected
+    let info = cosmwasm_std::testing::mock_info(MAKER, &[]);
+    let err = crate::contract::execute(deps.as_mut(), env.clone(), info, import_msg.clone())
+        .unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized));
+
+    // Tampered page rejected
+    let mut tampered = export.entries.clone();
+    tampered[0].amount = Uint128::new(999_999);
     let err = crate::contract::execute(
-        deps.as_mut(),
-        env.clone(),
-        info,
-        crate::msg::ExecuteMsg::AcceptAdmin {},
+        deps.as_mut(), env.clone(), admin.clone(),
+        crate::msg::ExecuteMsg::ImportBalances {
+            source_contract: "old_vault".to_string(),
+            start_after: None,
+            entries: tampered.clone(),
+            checksum: export.checksum.clone(),
+        },
     ).unwrap_err();
-    assert!(matches!(err, ContractError::Unauthorized));
-}
+    assert!(matches!(err, ContractError::ChecksumMismatch { .. }));
 
-#[test]
-fn test_accept_admin_without_proposal_rejected() {
-    let (mut deps, env) = setup_contract();
+    // A running source can still move the balances
+    let err = crate::contract::execute(deps.as_mut(), env.clone(), admin.clone(), import_msg.clone())
+        .unwrap_err();
+    assert!(matches!(err, ContractError::ImportSourceNotFinalized { .. }));
+    let finalize = crate::msg::ExecuteMsg::FinalizeExport {};
+    let err = crate::contract::execute(
+        old_vault.borrow_mut().as_mut(), env.clone(), admin.clone(), finalize.clone(),
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::ShutdownNotActive));
+    crate::contract::execute(
+        old_vault.borrow_mut().as_mut(), env.clone(), admin.clone(),
+        crate::msg::ExecuteMsg::EmergencyShutdown {},
+    ).unwrap();
 
-    // No TransferAdmin was called — AcceptAdmin should fail
+    // A shut-down source still pays out withdrawals until its export is finalized
+    let err = crate::contract::execute(deps.as_mut(), env.clone(), admin.clone(), import_msg.clone())
+        .unwrap_err();
+    assert!(matches!(err, ContractError::ImportSourceNotFinalized { .. }));
     let info = cosmwasm_std::testing::mock_info(MAKER, &[]);
     let err = crate::contract::execute(
-        deps.as_mut(),
-        env.clone(),
-        info,
-        crate::msg::ExecuteMsg::AcceptAdmin {},
+        old_vault.borrow_mut().as_mut(), env.clone(), info, finalize.clone(),
     ).unwrap_err();
     assert!(matches!(err, ContractError::Unauthorized));
+    crate::contract::execute(old_vault.borrow_mut().as_mut(), env.clone(), admin.clone(), finalize)
+        .unwrap();
+
+    // A page with a matching checksum must still be the source's own
+    let err = crate::contract::execute(
+        deps.as_mut(), env.clone(), admin.clone(),
+        crate::msg::ExecuteMsg::ImportBalances {
+            source_contract: "old_vault".to_string(),
+            start_after: None,
+            checksum: crate::msg::balance_entries_checksum(&tampered),
+            entries: tampered,
+        },
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::ImportEntryMismatch { index: 0 }));
+
+    // Pages are imported one at a time, each after the last address of the one before
+    let page = |start_after: Option<&str>, entries: &[crate::msg::BalanceEntry]| {
+        crate::msg::ExecuteMsg::ImportBalances {
+            source_contract: "old_vault".to_string(),
+            start_after: start_after.map(str::to_string),
+            checksum: crate::msg::balance_entries_checksum(entries),
+            entries: entries.to_vec(),
+        }
+    };
+    let err = crate::contract::execute(
+        deps.as_mut(), env.clone(), admin.clone(),
+        page(Some(ACCEPTOR), &export.entries[..1]),
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::ImportEntryMismatch { index: 0 }));
+    crate::contract::execute(
+        deps.as_mut(), env.clone(), admin.clone(),
+        page(None, &export.entries[..1]),
+    ).unwrap();
+    crate::contract::execute(
+        deps.as_mut(), env.clone(), admin.clone(),
+        page(Some(ACCEPTOR), &export.entries[1..]),
+    ).unwrap();
+
+    assert_eq!(query_vault_balance(&deps, &env, MAKER).available, Uint128::new(750));
+    assert_eq!(query_vault_balance(&deps, &env, ACCEPTOR).available, Uint128::new(450));
+
+    // Importing the same page twice is rejected
+    let err = crate::contract::execute(deps.as_mut(), env.clone(), admin, import_msg).unwrap_err();
+    assert!(matches!(err, ContractError::AlreadyImported { .. }));
+
+    let res = crate::contract::query(
+        deps.as_ref(), env.clone(),
+        crate::msg::QueryMsg::ImportStatus { source_contract: "old_vault".to_string() },
+    ).unwrap();
+    let status: crate::msg::ImportStatusResponse = cosmwasm_std::from_json(res).unwrap();
+    assert_eq!(status.imported_entries, 2);
+    assert_eq!(status.total_source_amount, Uint128::new(800));
+    assert_eq!(status.total_credited, Uint128::new(1_200));
+
+    // The imported balances can no longer leave the source
+    let err = withdraw(&mut old_vault.borrow_mut(), &env, MAKER, 500).unwrap_err();
+    assert!(matches!(err, ContractError::ExportFinalized));
+    let err = withdraw(&mut old_vault.borrow_mut(), &env, ACCEPTOR, 1).unwrap_err();
+    assert!(matches!(err, ContractError::ExportFinalized));
 }
 
+// ============================================================
+// Solvency
+// ============================================================
+
 #[test]
-fn test_new_admin_can_update_config() {
+fn test_vault_totals_follow_every_balance_change() {
+    let (mut deps, env) = setup_contract();
+    deposit(&mut deps, &env, MAKER, 500).unwrap();
+    deposit(&mut deps, &env, ACCEPTOR, 500).unwrap();
+    assert_totals_consistent(&deps);
+
+    // Played game: stakes locked, then paid out with commission to the treasury
+    let secret = b"secret_32_bytes_exactly_here!!!!";
+    let commitment = compute_commitment(MAKER, &Side::Heads, secret);
+    create_bet(&mut deps, &env, MAKER, 100, commitment.clone()).unwrap();
+    accept_bet(&mut deps, &env, ACCEPTOR, 1, Side::Tails).unwrap();
+    assert_totals_consistent(&deps);
+    reveal_bet(&mut deps, &env, MAKER, 1, Side::Heads, Binary::from(secret.to_vec())).unwrap();
+    assert_totals_consistent(&deps);
+
+    // Canceled bet
+    create_bet(&mut deps, &env, MAKER, 50, commitment).unwrap();
+    cancel_bet(&mut deps, &env, MAKER, 2).unwrap();
+    assert_totals_consistent(&deps);
+
+    // Delayed withdrawal: pending, then claimed
+    set_withdraw_policy(&mut deps, &env, 100, 3600, None);
+    withdraw(&mut deps, &env, MAKER, 200).unwrap();
+    assert_totals_consistent(&deps);
+    let later = env_at_time(env.block.time.seconds() + 3600);
+    claim_withdrawal(&mut deps, &later, MAKER, 1).unwrap();
+    assert_totals_consistent(&deps);
+
+    withdraw(&mut deps, &env, ACCEPTOR, 50).unwrap();
+    assert_totals_consistent(&deps);
+
+    // 1000 deposited - 200 - 50 withdrawn
+    set_contract_balance(&mut deps, &env, 750);
+    let res = crate::contract::query(
+        deps.as_ref(), env.clone(),
+        crate::msg::QueryMsg::Solvency {},
+    ).unwrap();
+    let solvency: crate::msg::SolvencyResponse = cosmwasm_std::from_json(res).unwrap();
+    assert_eq!(solvency.total_owed, Uint128::new(750));
+    assert_eq!(solvency.total_locked, Uint128::zero());
+    assert!(solvency.solvent);
+}
+
+#[test]
+fn test_solvency_reports_surplus_and_deficit() {
     let (mut deps, env) = setup_contract();
+    deposit(&mut deps, &env, MAKER, 500).unwrap();
+    deposit(&mut deps, &env, ACCEPTOR, 300).unwrap();
+    let commitment = compute_commitment(MAKER, &Side::Heads, b"secret_32_bytes_exactly_here!!!!");
+    create_bet(&mut deps, &env, MAKER, 100, commitment).unwrap();
 
-    // Transfer admin to MAKER
-    let info = cosmwasm_std::testing::mock_info(ADMIN, &[]);
-    crate::contract::execute(
-        deps.as_mut(), env.clone(), info,
-        crate::msg::ExecuteMsg::TransferAdmin { new_admin: MAKER.to_string() },
+    let query_solvency = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| -> crate::msg::SolvencyResponse {
+        let res = crate::contract::query(
+            deps.as_ref(), env.clone(),
+            crate::msg::QueryMsg::Solvency {},
+        ).unwrap();
+        cosmwasm_std::from_json(res).unwrap()
+    };
+
+    set_contract_balance(&mut deps, &env, 850);
+    let solvency = query_solvency(&deps);
+    assert_eq!(solvency.total_available, Uint128::new(700));
+    assert_eq!(solvency.total_locked, Uint128::new(100));
+    assert_eq!(solvency.total_owed, Uint128::new(800));
+    assert_eq!(solvency.surplus, Uint128::new(50));
+    assert_eq!(solvency.deficit, Uint128::zero());
+    assert!(solvency.solvent);
+
+    set_contract_balance(&mut deps, &env, 700);
+    let solvency = query_solvency(&deps);
+    assert_eq!(solvency.surplus, Uint128::zero());
+    assert_eq!(solvency.deficit, Uint128::new(100));
+    assert!(!solvency.solvent);
+}
+
+#[test]
+fn test_admin_sweep_uses_vault_totals() {
+    let (mut deps, env) = setup_contract();
+    deposit(&mut deps, &env, MAKER, 500).unwrap();
+    let admin = cosmwasm_std::testing::mock_info(ADMIN, &[]);
+    let sweep = crate::msg::ExecuteMsg::AdminSweep { recipient: None };
+
+    set_contract_balance(&mut deps, &env, 500);
+    let err = crate::contract::execute(deps.as_mut(), env.clone(), admin.clone(), sweep.clone())
+        .unwrap_err();
+    assert!(matches!(err, ContractError::NothingToSweep));
+
+    set_contract_balance(&mut deps, &env, 620);
+    let res = crate::contract::execute(deps.as_mut(), env.clone(), admin, sweep).unwrap();
+    assert!(res.attributes.iter().any(|a| a.key == "orphaned_amount" && a.value == "120"));
+    assert!(res.attributes.iter().any(|a| a.key == "total_vault" && a.value == "500"));
+    assert_eq!(res.messages.len(), 1);
+}
+
+// ============================================================
+// Bet pruning
+// ============================================================
+
+#[test]
+fn test_prune_bets_archives_resolved_bets() {
+    let (mut deps, env) = setup_contract();
+    deposit(&mut deps, &env, MAKER, 500).unwrap();
+    deposit(&mut deps, &env, ACCEPTOR, 500).unwrap();
+
+    // Bet 1 revealed (maker wins), bet 2 canceled, bet 3 still open
+    let secret = b"secret_32_bytes_exactly_here!!!!";
+    let commitment = compute_commitment(MAKER, &Side::Heads, secret);
+    create_bet(&mut deps, &env, MAKER, 100, commitment.clone()).unwrap();
+    accept_bet(&mut deps, &env, ACCEPTOR, 1, Side::Tails).unwrap();
+    reveal_bet(&mut deps, &env, MAKER, 1, Side::Heads, Binary::from(secret.to_vec())).unwrap();
+    create_bet(&mut deps, &env, MAKER, 50, commitment.clone()).unwrap();
+    cancel_bet(&mut deps, &env, MAKER, 2).unwrap();
+    create_bet(&mut deps, &env, MAKER, 50, commitment).unwrap();
+
+    // Nothing is old enough yet
+    let res = prune_bets(&mut deps, &env, 100, 10).unwrap();
+    assert!(res.attributes.iter().any(|a| a.key == "pruned" && a.value == "0"));
+
+    // After the default 30-day retention, scan from the newest bet in pages of 2
+    let later = env_at_time(env.block.time.seconds() + 2_592_000);
+    let res = prune_bets(&mut deps, &later, 4, 2).unwrap();
+    assert!(res.attributes.iter().any(|a| a.key == "scanned" && a.value == "2"));
+    assert!(res.attributes.iter().any(|a| a.key == "pruned" && a.value == "1"));
+    assert!(res.attributes.iter().any(|a| a.key == "next_before_id" && a.value == "2"));
+
+    let res = prune_bets(&mut deps, &later, 2, 2).unwrap();
+    assert!(res.attributes.iter().any(|a| a.key == "pruned" && a.value == "1"));
+    assert!(res.attributes.iter().any(|a| a.key == "next_before_id" && a.value == "0"));
+    let event = res.events.iter().find(|e| e.ty == "coinflip_bet_pruned").unwrap();
+    assert!(event.attributes.iter().any(|a| a.key == "bet_id" && a.value == "1"));
+    assert!(event.attributes.iter().any(|a| a.key == "status" && a.value == "revealed"));
+    assert!(event.attributes.iter().any(|a| a.key == "winner" && a.value == MAKER));
+    assert!(event.attributes.iter().any(|a| a.key == "reveal_secret"));
+
+    // Resolved bets are gone, the open one stays
+    for bet_id in [1, 2] {
+        let res = crate::contract::query(
+            deps.as_ref(), env.clone(),
+            crate::msg::QueryMsg::Bet { bet_id },
+        );
+        assert!(res.is_err());
+    }
+    assert_eq!(query_bet(&deps, &env, 3).status, BetStatus::Open);
+
+    let res = crate::contract::query(
+        deps.as_ref(), env.clone(),
+        crate::msg::QueryMsg::PlayerStats { address: MAKER.to_string() },
     ).unwrap();
-    let info = cosmwasm_std::testing::mock_info(MAKER, &[]);
-    crate::contract::execute(
-        deps.as_mut(), env.clone(), info.clone(),
-        crate::msg::ExecuteMsg::AcceptAdmin {},
+    let maker: crate::msg::PlayerStatsResponse = cosmwasm_std::from_json(res).unwrap();
+    assert_eq!((maker.games, maker.wins, maker.losses, maker.canceled), (1, 1, 0, 1));
+    assert_eq!(maker.wagered, Uint128::new(100));
+    assert_eq!(maker.won, Uint128::new(180));
+
+    let res = crate::contract::query(
+        deps.as_ref(), env.clone(),
+        crate::msg::QueryMsg::PlayerStats { address: ACCEPTOR.to_string() },
     ).unwrap();
+    let acceptor: crate::msg::PlayerStatsResponse = cosmwasm_std::from_json(res).unwrap();
+    assert_eq!((acceptor.games, acceptor.wins, acceptor.losses), (1, 0, 1));
 
-    // New admin (MAKER) can update config
-    crate::contract::execute(
-        deps.as_mut(),
-        env.clone(),
-        info,
-        crate::msg::ExecuteMsg::UpdateConfig {
-            treasury: None,
-            commission_bps: Some(200),
-            min_bet: None,
-            reveal_timeout_secs: None,
-            max_open_per_user: None,
-            max_daily_amount_per_user: None,
-            bet_ttl_secs: None,
-        },
+    let res = crate::contract::query(
+        deps.as_ref(), env.clone(),
+        crate::msg::QueryMsg::DailyStats { start_after: None, limit: None },
     ).unwrap();
+    let daily: crate::msg::DailyStatsResponse = cosmwasm_std::from_json(res).unwrap();
+    assert_eq!(daily.days.len(), 1);
+    assert_eq!(daily.days[0].day, env.block.time.seconds() / 86400);
+    assert_eq!((daily.days[0].bets, daily.days[0].games, daily.days[0].canceled), (2, 1, 1));
+    assert_eq!(daily.days[0].volume, Uint128::new(200));
+    assert_eq!(daily.days[0].commission, Uint128::new(20));
+    assert_eq!(daily.days[0].payout, Uint128::new(180));
+}
 
-    let config = query_config(&deps, &env);
-    assert_eq!(config.commission_bps, 200);
-    assert_eq!(config.admin.as_str(), MAKER);
+#[test]
+fn test_player_stats_update_on_resolution() {
+    let (mut deps, env) = setup_contract();
+    deposit(&mut deps, &env, MAKER, 1_000).unwrap();
+    deposit(&mut deps, &env, ACCEPTOR, 1_000).unwrap();
 
-    // Old admin can no longer update
-    let old_admin_info = cosmwasm_std::testing::mock_info(ADMIN, &[]);
+    // Maker wins bets 1 and 2, then loses bet 3 on timeout; bet 4 stays open
+    let secret = b"secret_32_bytes_exactly_here!!!!";
+    let commitment = compute_commitment(MAKER, &Side::Heads, secret);
+    for bet_id in [1, 2] {
+        create_bet(&mut deps, &env, MAKER, 100, commitment.clone()).unwrap();
+        accept_bet(&mut deps, &env, ACCEPTOR, bet_id, Side::Tails).unwrap();
+        reveal_bet(&mut deps, &env, MAKER, bet_id, Side::Heads, Binary::from(secret.to_vec()))
+            .unwrap();
+    }
+    assert_eq!(query_player_stats(&deps, &env, MAKER).current_streak, 2);
+
+    create_bet(&mut deps, &env, MAKER, 100, commitment.clone()).unwrap();
+    accept_bet(&mut deps, &env, ACCEPTOR, 3, Side::Tails).unwrap();
+    let later = env_at_time(env.block.time.seconds() + 301);
+    claim_timeout(&mut deps, &later, ACCEPTOR, 3).unwrap();
+    create_bet(&mut deps, &later, MAKER, 100, commitment).unwrap();
+
+    // Stats are live without pruning
+    let maker = query_player_stats(&deps, &later, MAKER);
+    assert_eq!((maker.games, maker.wins, maker.losses, maker.canceled), (3, 2, 1, 0));
+    assert_eq!(maker.wagered, Uint128::new(300));
+    assert_eq!(maker.won, Uint128::new(360));
+    assert_eq!(maker.commission_paid, Uint128::new(40));
+    assert_eq!((maker.current_streak, maker.best_streak), (0, 2));
+    assert_eq!(maker.last_played, later.block.time.seconds());
+    assert_eq!(maker.balance.available, Uint128::new(960));
+    assert_eq!(maker.balance.locked, Uint128::new(100));
+    assert_eq!(maker.open_bets, 1);
+
+    let acceptor = query_player_stats(&deps, &later, ACCEPTOR);
+    assert_eq!((acceptor.games, acceptor.wins, acceptor.losses), (3, 1, 2));
+    assert_eq!(acceptor.commission_paid, Uint128::new(20));
+    assert_eq!((acceptor.current_streak, acceptor.best_streak), (1, 1));
+    assert_eq!(acceptor.open_bets, 0);
+
+    // Pruning does not count recorded bets twice
+    let archive = env_at_time(later.block.time.seconds() + 2_592_000);
+    prune_bets(&mut deps, &archive, 5, 10).unwrap();
+    assert_eq!(query_player_stats(&deps, &archive, MAKER).games, 3);
+}
+
+#[test]
+fn test_prune_records_stats_of_legacy_bets() {
+    let (mut deps, env) = setup_contract();
+    deposit(&mut deps, &env, MAKER, 500).unwrap();
+    deposit(&mut deps, &env, ACCEPTOR, 500).unwrap();
+
+    let secret = b"secret_32_bytes_exactly_here!!!!";
+    let commitment = compute_commitment(MAKER, &Side::Heads, secret);
+    create_bet(&mut deps, &env, MAKER, 100, commitment).unwrap();
+    accept_bet(&mut deps, &env, ACCEPTOR, 1, Side::Tails).unwrap();
+    reveal_bet(&mut deps, &env, MAKER, 1, Side::Heads, Binary::from(secret.to_vec())).unwrap();
+
+    // Rewind to a bet resolved before live stats existed
+    let mut bet = crate::state::BETS.load(&deps.storage, 1).unwrap();
+    bet.stats_recorded = false;
+    crate::state::BETS.save(&mut deps.storage, 1, &bet).unwrap();
+    crate::state::PLAYER_STATS.remove(&mut deps.storage, &bet.maker);
+    crate::state::PLAYER_STATS.remove(&mut deps.storage, bet.acceptor.as_ref().unwrap());
+
+    let later = env_at_time(env.block.time.seconds() + 2_592_000);
+    prune_bets(&mut deps, &later, 2, 10).unwrap();
+
+    // Totals are recovered; the order is unknown, so streaks stay untouched
+    let maker = query_player_stats(&deps, &later, MAKER);
+    assert_eq!((maker.games, maker.wins), (1, 1));
+    assert_eq!(maker.won, Uint128::new(180));
+    assert_eq!((maker.current_streak, maker.last_played), (0, 0));
+    assert_eq!(query_player_stats(&deps, &later, ACCEPTOR).losses, 1);
+}
+
+#[test]
+fn test_leaderboard_rankings() {
+    use crate::msg::{LeaderboardMetric as Metric, LeaderboardPeriod as Period, LeaderboardResponse};
+
+    fn ranked(board: &LeaderboardResponse) -> Vec<(&str, i128)> {
+        board.entries.iter().map(|e| (e.address.as_str(), e.score.i128())).collect()
+    }
+
+    let (mut deps, env) = setup_contract();
+    for user in [MAKER, ACCEPTOR, RANDOM_USER] {
+        deposit(&mut deps, &env, user, 1_000).unwrap();
+    }
+    let secret = b"secret_32_bytes_exactly_here!!!!";
+    let play = |deps: &mut _, env: &_, maker: &str, amount, bet_id, guess| {
+        let commitment = compute_commitment(maker, &Side::Heads, secret);
+        create_bet(deps, env, maker, amount, commitment).unwrap();
+        accept_bet(deps, env, ACCEPTOR, bet_id, guess).unwrap();
+        reveal_bet(deps, env, maker, bet_id, Side::Heads, Binary::from(secret.to_vec())).unwrap();
+    };
+
+    // Day 1: maker and random user both beat the acceptor
+    play(&mut deps, &env, MAKER, 100, 1, Side::Tails);
+    play(&mut deps, &env, RANDOM_USER, 200, 2, Side::Tails);
+
+    let volume = |deps: &_, start_after| {
+        query_leaderboard(deps, &env, Metric::Volume, Period::AllTime, start_after, 2)
+    };
+    let page = volume(&deps, None);
+    assert_eq!(ranked(&page), vec![(ACCEPTOR, 300), (RANDOM_USER, 200)]);
+    assert_eq!(page.next_start_after.as_deref(), Some(RANDOM_USER));
+    let page = volume(&deps, Some(RANDOM_USER));
+    assert_eq!(page.entries.len(), 1);
+    assert_eq!(page.entries[0].address, MAKER);
+    assert_eq!(page.next_start_after, None);
+
+    // Day 2: the acceptor wins one back
+    let day2 = env_at_time(env.block.time.seconds() + 86_400);
+    play(&mut deps, &day2, MAKER, 100, 3, Side::Heads);
+
+    // All-time P&L ranks negative scores last
+    let pnl = query_leaderboard(&deps, &day2, Metric::Pnl, Period::AllTime, None, 10);
+    assert_eq!(ranked(&pnl), vec![(RANDOM_USER, 160), (MAKER, -20), (ACCEPTOR, -220)]);
+
+    // The current day only counts today's game
+    let today = Period::Day { day: None };
+    let wins = query_leaderboard(&deps, &day2, Metric::Wins, today, None, 10);
+    let day = day2.block.time.seconds() / 86_400;
+    assert_eq!(wins.period, Period::Day { day: Some(day) });
+    assert_eq!(ranked(&wins), vec![(ACCEPTOR, 1), (MAKER, 0)]);
+    assert_eq!((wins.entries[0].games, wins.entries[0].won), (1, Uint128::new(180)));
+
+    // Past buckets stay readable
+    let yesterday = Period::Day { day: Some(day - 1) };
+    let board = query_leaderboard(&deps, &day2, Metric::Volume, yesterday, None, 10);
+    assert_eq!(board.entries[0].address, ACCEPTOR);
+    assert_eq!(board.entries[0].score.i128(), 300);
+    assert_eq!(board.entries.len(), 3);
+
+    // Day 2 of the mock clock starts a new week
+    let week = day2.block.time.seconds() / 604_800;
+    assert_eq!(week, env.block.time.seconds() / 604_800 + 1);
+    let last_week = Period::Week { week: Some(week - 1) };
+    let board = query_leaderboard(&deps, &day2, Metric::Wins, last_week, None, 10);
+    assert_eq!(ranked(&board), vec![(RANDOM_USER, 1), (MAKER, 1), (ACCEPTOR, 0)]);
+
+    // 30 days later day 1 falls out of retention; day 2 and the weeks are kept
+    let later = env_at_time(day2.block.time.seconds() + 30 * 86_400);
+    let prune = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>| {
+        let msg = crate::msg::ExecuteMsg::PrunePeriodStats { limit: Some(2) };
+        let info = cosmwasm_std::testing::mock_info(RANDOM_USER, &[]);
+        let res = crate::contract::execute(deps.as_mut(), later.clone(), info, msg).unwrap();
+        let value = |key| res.attributes.iter().find(|a| a.key == key).unwrap().value.clone();
+        (value("pruned"), value("completed") == "true")
+    };
+    assert_eq!(prune(&mut deps), ("2".to_string(), false));
+    assert_eq!(prune(&mut deps), ("1".to_string(), true));
+    assert_eq!(prune(&mut deps), ("0".to_string(), true));
+    for metric in [Metric::Volume, Metric::Wins, Metric::Pnl] {
+        let yesterday = Period::Day { day: Some(day - 1) };
+        assert!(query_leaderboard(&deps, &later, metric, yesterday, None, 10).entries.is_empty());
+    }
+    let today = Period::Day { day: Some(day) };
+    let board = query_leaderboard(&deps, &later, Metric::Wins, today, None, 10);
+    assert_eq!(ranked(&board), vec![(ACCEPTOR, 1), (MAKER, 0)]);
+    let last_week = Period::Week { week: Some(week - 1) };
+    let board = query_leaderboard(&deps, &later, Metric::Wins, last_week, None, 10);
+    assert_eq!(board.entries.len(), 3);
+}
+
+#[test]
+fn test_global_stats_and_series() {
+    use crate::msg::StatsGranularity;
+
+    let (mut deps, env) = setup_contract();
+    deposit(&mut deps, &env, MAKER, 1_000).unwrap();
+    deposit(&mut deps, &env, ACCEPTOR, 1_000).unwrap();
+    let secret = b"secret_32_bytes_exactly_here!!!!";
+    let commitment = compute_commitment(MAKER, &Side::Heads, secret);
+
+    // First hour: one bet played, one canceled
+    create_bet(&mut deps, &env, MAKER, 100, commitment.clone()).unwrap();
+    accept_bet(&mut deps, &env, ACCEPTOR, 1, Side::Tails).unwrap();
+    reveal_bet(&mut deps, &env, MAKER, 1, Side::Heads, Binary::from(secret.to_vec())).unwrap();
+    create_bet(&mut deps, &env, MAKER, 50, commitment.clone()).unwrap();
+    cancel_bet(&mut deps, &env, MAKER, 2).unwrap();
+
+    // Next hour, same day: one more bet played
+    let t0 = env.block.time.seconds();
+    let next = env_at_time(t0 + 3_600);
+    create_bet(&mut deps, &next, MAKER, 200, commitment).unwrap();
+    accept_bet(&mut deps, &next, ACCEPTOR, 3, Side::Tails).unwrap();
+    reveal_bet(&mut deps, &next, MAKER, 3, Side::Heads, Binary::from(secret.to_vec())).unwrap();
+
+    let res = crate::contract::query(
+        deps.as_ref(), next.clone(),
+        crate::msg::QueryMsg::GlobalStats {},
+    ).unwrap();
+    let global: crate::msg::GlobalStatsResponse = cosmwasm_std::from_json(res).unwrap();
+    assert_eq!((global.bets_created, global.bets_resolved, global.bets_canceled), (3, 2, 1));
+    assert_eq!(global.volume, Uint128::new(600));
+    assert_eq!(global.commission, Uint128::new(60));
+    assert_eq!(global.unique_players, 2);
+
+    let hourly = query_stats_series(&deps, &next, StatsGranularity::Hour, t0, t0 + 3_600);
+    assert_eq!(hourly.buckets.len(), 2);
+    let first = &hourly.buckets[0];
+    assert_eq!(first.start, t0 / 3_600 * 3_600);
+    assert_eq!((first.bets_created, first.bets_resolved, first.bets_canceled), (2, 1, 1));
+    assert_eq!((first.volume, first.commission), (Uint128::new(200), Uint128::new(20)));
+    assert_eq!(first.unique_players, 2);
+    let second = &hourly.buckets[1];
+    assert_eq!((second.bets_created, second.volume), (1, Uint128::new(400)));
+    assert_eq!(hourly.next_from, None);
+
+    // Range bounds select buckets by the time they cover
+    let hourly = query_stats_series(&deps, &next, StatsGranularity::Hour, t0 + 3_600, t0 + 7_200);
+    assert_eq!(hourly.buckets.len(), 1);
+    assert_eq!(hourly.buckets[0].commission, Uint128::new(40));
+
+    let daily = query_stats_series(&deps, &next, StatsGranularity::Day, 0, t0 + 3_600);
+    assert_eq!(daily.buckets.len(), 1);
+    assert_eq!(daily.buckets[0].start, t0 / 86_400 * 86_400);
+    assert_eq!((daily.buckets[0].bets_created, daily.buckets[0].unique_players), (3, 2));
+    assert_eq!(daily.buckets[0].volume, Uint128::new(600));
+}
+
+#[test]
+fn test_update_bet_retention() {
+    let (mut deps, env) = setup_contract();
+    assert_eq!(query_config(&deps, &env).bet_retention_secs, 2_592_000);
+
+    let info = cosmwasm_std::testing::mock_info(MAKER, &[]);
     let err = crate::contract::execute(
-        deps.as_mut(),
-        env.clone(),
-        old_admin_info,
-        crate::msg::ExecuteMsg::UpdateConfig {
-            treasury: None,
-            commission_bps: Some(300),
-            min_bet: None,
-            reveal_timeout_secs: None,
-            max_open_per_user: None,
-            max_daily_amount_per_user: None,
-            bet_ttl_secs: None,
-        },
+        deps.as_mut(), env.clone(), info,
+        crate::msg::ExecuteMsg::UpdateBetRetention { bet_retention_secs: 86400 },
     ).unwrap_err();
     assert!(matches!(err, ContractError::Unauthorized));
+
+    let info = cosmwasm_std::testing::mock_info(ADMIN, &[]);
+    let err = crate::contract::execute(
+        deps.as_mut(), env.clone(), info.clone(),
+        crate::msg::ExecuteMsg::UpdateBetRetention { bet_retention_secs: 3600 },
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidRetention { min: 86400 }));
+
+    crate::contract::execute(
+        deps.as_mut(), env.clone(), info,
+        crate::msg::ExecuteMsg::UpdateBetRetention { bet_retention_secs: 86400 },
+    ).unwrap();
+    assert_eq!(query_config(&deps, &env).bet_retention_secs, 86400);
+}
+
+// ============================================================
+// Batched state reset
+// ============================================================
+
+#[test]
+fn test_migrate_reset_state_runs_in_batches() {
+    let (mut deps, env) = setup_contract();
+    deposit(&mut deps, &env, MAKER, 1_000).unwrap();
+    deposit(&mut deps, &env, ACCEPTOR, 1_000).unwrap();
+    for i in 0..3 {
+        let commitment = compute_commitment(MAKER, &Side::Heads, format!("secret_{:032}", i).as_bytes());
+        create_bet(&mut deps, &env, MAKER, 100, commitment).unwrap();
+    }
+
+    let res = crate::contract::migrate(
+        deps.as_mut(), env.clone(),
+        crate::msg::MigrateMsg { token_cw20: None, reset_state: true },
+    ).unwrap();
+    assert!(res.attributes.iter().any(|a| a.key == "state_reset" && a.value == "true"));
+
+    // Nothing has been deleted yet, and normal operations are blocked
+    assert_eq!(query_vault_balance(&deps, &env, MAKER).locked, Uint128::new(300));
+    let err = deposit(&mut deps, &env, MAKER, 100).unwrap_err();
+    assert!(matches!(err, ContractError::MigrationInProgress));
+    let err = withdraw(&mut deps, &env, ACCEPTOR, 100).unwrap_err();
+    assert!(matches!(err, ContractError::MigrationInProgress));
+
+    // A second reset cannot start on top of the first one
+    let err = crate::contract::migrate(
+        deps.as_mut(), env.clone(),
+        crate::msg::MigrateMsg { token_cw20: None, reset_state: true },
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::MigrationInProgress));
+
+    // 2 balances + 3 bets + 1 open count + 3 series buckets + 3 series players = 12 entries
+    let res = continue_migration(&mut deps, &env, 4).unwrap();
+    assert!(res.attributes.iter().any(|a| a.key == "cleared" && a.value == "4"));
+    assert!(res.attributes.iter().any(|a| a.key == "completed" && a.value == "false"));
+
+    let res = crate::contract::query(
+        deps.as_ref(), env.clone(),
+        crate::msg::QueryMsg::MigrationStatus {},
+    ).unwrap();
+    let status: crate::msg::MigrationStatusResponse = cosmwasm_std::from_json(res).unwrap();
+    assert!(status.in_progress);
+    assert_eq!(status.phase, Some(crate::state::ResetPhase::Bets));
+    assert_eq!(status.cleared_entries, 4);
+
+    let res = continue_migration(&mut deps, &env, 10).unwrap();
+    assert!(res.attributes.iter().any(|a| a.key == "cleared" && a.value == "8"));
+    assert!(res.attributes.iter().any(|a| a.key == "completed" && a.value == "true"));
+
+    let err = continue_migration(&mut deps, &env, 4).unwrap_err();
+    assert!(matches!(err, ContractError::NoMigrationInProgress));
+
+    // State is empty and the contract is usable again
+    assert_totals_consistent(&deps);
+    let balance = query_vault_balance(&deps, &env, MAKER);
+    assert_eq!(balance.available, Uint128::zero());
+    assert_eq!(balance.locked, Uint128::zero());
+    deposit(&mut deps, &env, MAKER, 500).unwrap();
+    let commitment = compute_commitment(MAKER, &Side::Heads, b"secret_32_bytes_exactly_here!!!!");
+    let res = create_bet(&mut deps, &env, MAKER, 100, commitment).unwrap();
+    assert!(res.attributes.iter().any(|a| a.key == "bet_id" && a.value == "1"));
+}
+
+// ============================================================
+// Versioned migrations
+// ============================================================
+
+#[test]
+fn test_migrations_are_ordered() {
+    let current: semver::Version = env!("CARGO_PKG_VERSION").parse().unwrap();
+    let versions: Vec<semver::Version> = crate::migrations::MIGRATIONS
+        .iter()
+        .map(|m| m.version.parse().unwrap())
+        .collect();
+    assert!(versions.windows(2).all(|w| w[0] < w[1]));
+    assert!(versions.iter().all(|v| *v <= current));
+}
+
+#[test]
+fn test_migrate_v0_7_0_from_v0_6_0_fixture() {
+    let (mut deps, env) = setup_v0_6_0_fixture();
+
+    let res = crate::contract::migrate(
+        deps.as_mut(), env.clone(),
+        crate::msg::MigrateMsg { token_cw20: None, reset_state: false },
+    ).unwrap();
+    assert!(res.attributes.iter().any(|a| a.key == "from_version" && a.value == "0.6.0"));
+    assert!(res.attributes.iter().any(|a| a.key == "migrations" && a.value == "0.7.0"));
+
+    // Config is stored in its full shape
+    let raw = cosmwasm_std::Storage::get(&deps.storage, b"config").unwrap();
+    let raw = String::from_utf8(raw).unwrap();
+    assert!(raw.contains(r#""withdraw_delay_threshold":"0""#));
+    assert!(raw.contains(r#""withdraw_delay_secs":86400"#));
+    assert!(raw.contains(r#""guardian":null"#));
+
+    let version = cw2::get_contract_version(&deps.storage).unwrap();
+    assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+
+    // The totals are counted in batches; nothing else runs until they are done
+    let err = deposit(&mut deps, &env, MAKER, 100).unwrap_err();
+    assert!(matches!(err, ContractError::MigrationInProgress));
+    let res = continue_migration(&mut deps, &env, 1).unwrap();
+    assert!(res.attributes.iter().any(|a| a.key == "counted_balances" && a.value == "1"));
+    assert!(res.attributes.iter().any(|a| a.key == "completed" && a.value == "false"));
+
+    let res = crate::contract::query(
+        deps.as_ref(), env.clone(),
+        crate::msg::QueryMsg::MigrationStatus {},
+    ).unwrap();
+    let status: crate::msg::MigrationStatusResponse = cosmwasm_std::from_json(res).unwrap();
+    assert!(status.in_progress);
+    assert_eq!(status.phase, None);
+    assert_eq!(status.counted_balances, Some(1));
+
+    let res = continue_migration(&mut deps, &env, 1).unwrap();
+    assert!(res.attributes.iter().any(|a| a.key == "completed" && a.value == "false"));
+    let res = continue_migration(&mut deps, &env, 1).unwrap();
+    assert!(res.attributes.iter().any(|a| a.key == "counted_balances" && a.value == "2"));
+    assert!(res.attributes.iter().any(|a| a.key == "completed" && a.value == "true"));
+    let err = continue_migration(&mut deps, &env, 1).unwrap_err();
+    assert!(matches!(err, ContractError::NoMigrationInProgress));
+
+    // Old records still load, and the withdrawal counter starts at 1
+    let balance = query_vault_balance(&deps, &env, MAKER);
+    assert_eq!(balance.available, Uint128::new(400));
+    assert_eq!(balance.locked, Uint128::new(100));
+    assert_eq!(balance.pending_withdrawal, Uint128::zero());
+    assert_eq!(query_bet(&deps, &env, 1).status, BetStatus::Open);
+    assert_totals_consistent(&deps);
+    let totals = crate::state::VAULT_TOTALS.load(&deps.storage).unwrap();
+    assert_eq!(totals.total_available, Uint128::new(650));
+    assert_eq!(totals.total_locked, Uint128::new(100));
+
+    set_withdraw_policy(&mut deps, &env, 100, 3600, None);
+    let res = withdraw(&mut deps, &env, MAKER, 300).unwrap();
+    assert!(res.attributes.iter().any(|a| a.key == "withdrawal_id" && a.value == "1"));
+}
+
+#[test]
+fn test_migrate_skips_applied_steps() {
+    let (mut deps, env) = setup_contract();
+    set_withdraw_policy(&mut deps, &env, 100, 3600, None);
+    deposit(&mut deps, &env, MAKER, 1_000).unwrap();
+    withdraw(&mut deps, &env, MAKER, 300).unwrap();
+
+    // Same version: no steps run
+    let res = crate::contract::migrate(
+        deps.as_mut(), env.clone(),
+        crate::msg::MigrateMsg { token_cw20: None, reset_state: false },
+    ).unwrap();
+    assert!(res.attributes.iter().any(|a| a.key == "migrations" && a.value == "none"));
+
+    // Re-running a step on already-upgraded state keeps the withdrawal counter
+    cw2::set_contract_version(&mut deps.storage, "crates.io:coinflip-pvp-vault", "0.6.0")
+        .unwrap();
+    crate::contract::migrate(
+        deps.as_mut(), env.clone(),
+        crate::msg::MigrateMsg { token_cw20: None, reset_state: false },
+    ).unwrap();
+    let res = withdraw(&mut deps, &env, MAKER, 300).unwrap();
+    assert!(res.attributes.iter().any(|a| a.key == "withdrawal_id" && a.value == "2"));
+}
+
+// ============================================================
+// Governance sudo
+// ============================================================
+
+#[test]
+fn test_sudo_replace_admin() {
+    use cw_multi_test::Executor;
+    let (mut app, vault) = setup_app();
+
+    // A pending transfer started by the old admin is dropped
+    app.execute_contract(
+        cosmwasm_std::Addr::unchecked(ADMIN), vault.clone(),
+        &crate::msg::ExecuteMsg::TransferAdmin { new_admin: RANDOM_USER.to_string() },
+        &[],
+    ).unwrap();
+
+    let res = app.wasm_sudo(
+        vault.clone(),
+        &crate::msg::SudoMsg::ReplaceAdmin { new_admin: "gov_admin".to_string() },
+    ).unwrap();
+    let event = res.events.iter().find(|e| e.ty == "wasm-coinflip_sudo_admin_replaced").unwrap();
+    assert!(event.attributes.iter().any(|a| a.key == "old_admin" && a.value == ADMIN));
+    assert!(event.attributes.iter().any(|a| a.key == "new_admin" && a.value == "gov_admin"));
+
+    let config: crate::msg::ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(vault.clone(), &crate::msg::QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.admin, "gov_admin");
+
+    let err = app.execute_contract(
+        cosmwasm_std::Addr::unchecked(RANDOM_USER), vault.clone(),
+        &crate::msg::ExecuteMsg::AcceptAdmin {},
+        &[],
+    ).unwrap_err();
+    assert!(matches!(err.downcast_ref::<ContractError>(), Some(ContractError::Unauthorized)));
+
+    let err = app.execute_contract(
+        cosmwasm_std::Addr::unchecked(ADMIN), vault,
+        &crate::msg::ExecuteMsg::UpdateWithdrawPolicy {
+            threshold: Uint128::new(100),
+            delay_secs: 3600,
+            guardian: None,
+        },
+        &[],
+    ).unwrap_err();
+    assert!(matches!(err.downcast_ref::<ContractError>(), Some(ContractError::Unauthorized)));
+}
+
+#[test]
+fn test_sudo_pause_and_unpause() {
+    use cw_multi_test::Executor;
+    let (mut app, vault) = setup_app();
+    let create = crate::msg::ExecuteMsg::CreateBet {
+        amount: Uint128::new(100),
+        commitment: compute_commitment(MAKER, &Side::Heads, b"secret_32_bytes_exactly_here!!!!"),
+        voucher: None,
+    };
+
+    let res = app.wasm_sudo(vault.clone(), &crate::msg::SudoMsg::Pause {}).unwrap();
+    assert!(res.events.iter().any(|e| e.ty == "wasm-coinflip_sudo_paused"));
+
+    let err = app.execute_contract(
+        cosmwasm_std::Addr::unchecked(MAKER), vault.clone(), &create, &[],
+    ).unwrap_err();
+    assert!(matches!(err.downcast_ref::<ContractError>(), Some(ContractError::Paused)));
+
+    // Exits stay open: these reach normal validation instead of failing with Paused
+    let exits = [
+        crate::msg::ExecuteMsg::Withdraw { amount: Uint128::new(1) },
+        crate::msg::ExecuteMsg::CancelBet { bet_id: 1 },
+        crate::msg::ExecuteMsg::ClaimTimeout { bet_id: 1 },
+        crate::msg::ExecuteMsg::ClaimWithdrawal { withdrawal_id: 1 },
+        crate::msg::ExecuteMsg::CancelWithdrawal { withdrawal_id: 1 },
+    ];
+    for exit in &exits {
+        let err = app.execute_contract(
+            cosmwasm_std::Addr::unchecked(MAKER), vault.clone(), exit, &[],
+        ).unwrap_err();
+        assert!(!matches!(err.downcast_ref::<ContractError>(), Some(ContractError::Paused)));
+    }
+
+    let config: crate::msg::ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(vault.clone(), &crate::msg::QueryMsg::Config {})
+        .unwrap();
+    assert!(config.paused);
+
+    let res = app.wasm_sudo(vault.clone(), &crate::msg::SudoMsg::Unpause {}).unwrap();
+    assert!(res.events.iter().any(|e| e.ty == "wasm-coinflip_sudo_unpaused"));
+
+    // Back to normal validation
+    let err = app.execute_contract(
+        cosmwasm_std::Addr::unchecked(MAKER), vault, &create, &[],
+    ).unwrap_err();
+    assert!(!matches!(err.downcast_ref::<ContractError>(), Some(ContractError::Paused)));
+}
+
+#[test]
+fn test_sudo_update_config() {
+    let (mut app, vault) = setup_app();
+    let update = |commission_bps| crate::msg::SudoMsg::UpdateConfig {
+        treasury: Some("new_treasury".to_string()),
+        commission_bps: Some(commission_bps),
+        min_bet: None,
+        reveal_timeout_secs: None,
+        max_open_per_user: None,
+        max_daily_amount_per_user: None,
+        bet_ttl_secs: None,
+    };
+
+    // Same bounds as the admin path
+    let err = app.wasm_sudo(vault.clone(), &update(6000)).unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<ContractError>(),
+        Some(ContractError::InvalidCommission { max_bps: 5000 })
+    ));
+
+    let res = app.wasm_sudo(vault.clone(), &update(500)).unwrap();
+    assert!(res.events.iter().any(|e| e.ty == "wasm-coinflip_sudo_config_updated"));
+
+    let config: crate::msg::ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(vault, &crate::msg::QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.commission_bps, 500);
+    assert_eq!(config.treasury, "new_treasury");
+    assert_eq!(config.admin, ADMIN);
+}
+
+// ============================================================
+// UpdateConfig
+// ============================================================
+
+#[test]
+fn test_update_config_admin_success() {
+    let (mut deps, env) = setup_contract();
+    let info = cosmwasm_std::testing::mock_info(ADMIN, &[]);
+
+    let res = crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        crate::msg::ExecuteMsg::UpdateConfig {
+            treasury: Some("new_treasury".to_string()),
+            commission_bps: Some(500),
+            min_bet: Some(Uint128::new(100)),
+            reveal_timeout_secs: Some(600),
+            max_open_per_user: Some(20),
+            max_daily_amount_per_user: None,
+            bet_ttl_secs: None,
+        },
+    ).unwrap();
+    assert!(res.attributes.iter().any(|a| a.key == "action" && a.value == "update_config"));
+
+    let config = query_config(&deps, &env);
+    assert_eq!(config.treasury.as_str(), "new_treasury");
+    assert_eq!(config.commission_bps, 500);
+    assert_eq!(config.min_bet, Uint128::new(100));
+    assert_eq!(config.reveal_timeout_secs, 600);
+    assert_eq!(config.max_open_per_user, 20);
+    // unchanged
+    assert_eq!(config.max_daily_amount_per_user, Uint128::new(10_000));
+}
+
+#[test]
+fn test_update_config_non_admin_rejected() {
+    let (mut deps, env) = setup_contract();
+    let info = cosmwasm_std::testing::mock_info(MAKER, &[]);
+
+    let err = crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        crate::msg::ExecuteMsg::UpdateConfig {
+            treasury: None,
+            commission_bps: Some(200),
+            min_bet: None,
+            reveal_timeout_secs: None,
+            max_open_per_user: None,
+            max_daily_amount_per_user: None,
+            bet_ttl_secs: None,
+        },
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized));
+}
+
+#[test]
+fn test_update_config_commission_too_high() {
+    let (mut deps, env) = setup_contract();
+    let info = cosmwasm_std::testing::mock_info(ADMIN, &[]);
+
+    let err = crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        crate::msg::ExecuteMsg::UpdateConfig {
+            treasury: None,
+            commission_bps: Some(6000), // > 50%
+            min_bet: None,
+            reveal_timeout_secs: None,
+            max_open_per_user: None,
+            max_daily_amount_per_user: None,
+            bet_ttl_secs: None,
+        },
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidCommission { .. }));
+}
+
+#[test]
+fn test_update_config_timeout_invalid() {
+    let (mut deps, env) = setup_contract();
+    let info = cosmwasm_std::testing::mock_info(ADMIN, &[]);
+
+    // Too short
+    let err = crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        crate::msg::ExecuteMsg::UpdateConfig {
+            treasury: None,
+            commission_bps: None,
+            min_bet: None,
+            reveal_timeout_secs: Some(10), // < 60
+            max_open_per_user: None,
+            max_daily_amount_per_user: None,
+            bet_ttl_secs: None,
+        },
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidTimeout { .. }));
+
+    // Too long
+    let err = crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        crate::msg::ExecuteMsg::UpdateConfig {
+            treasury: None,
+            commission_bps: None,
+            min_bet: None,
+            reveal_timeout_secs: Some(100000), // > 86400
+            max_open_per_user: None,
+            max_daily_amount_per_user: None,
+            bet_ttl_secs: None,
+        },
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidTimeout { .. }));
+}
+
+// ============================================================
+// TransferAdmin / AcceptAdmin
+// ============================================================
+
+#[test]
+fn test_transfer_admin_success() {
+    let (mut deps, env) = setup_contract();
+
+    // Step 1: current admin proposes new admin
+    let info = cosmwasm_std::testing::mock_info(ADMIN, &[]);
+    let res = crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        crate::msg::ExecuteMsg::TransferAdmin {
+            new_admin: MAKER.to_string(),
+        },
+    ).unwrap();
+    assert!(res.attributes.iter().any(|a| a.key == "action" && a.value == "transfer_admin"));
+
+    // Config still shows old admin
+    let config = query_config(&deps, &env);
+    assert_eq!(config.admin.as_str(), ADMIN);
+
+    // Step 2: new admin accepts
+    let info = cosmwasm_std::testing::mock_info(MAKER, &[]);
+    let res = crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        crate::msg::ExecuteMsg::AcceptAdmin {},
+    ).unwrap();
+    assert!(res.attributes.iter().any(|a| a.key == "action" && a.value == "accept_admin"));
+
+    // Config now shows new admin
+    let config = query_config(&deps, &env);
+    assert_eq!(config.admin.as_str(), MAKER);
+}
+
+#[test]
+fn test_transfer_admin_non_admin_rejected() {
+    let (mut deps, env) = setup_contract();
+
+    let info = cosmwasm_std::testing::mock_info(MAKER, &[]);
+    let err = crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        crate::msg::ExecuteMsg::TransferAdmin {
+            new_admin: MAKER.to_string(),
+        },
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized));
+}
+
+#[test]
+fn test_accept_admin_wrong_user_rejected() {
+    let (mut deps, env) = setup_contract();
+
+    // Admin proposes MAKER
+    let info = cosmwasm_std::testing::mock_info(ADMIN, &[]);
+    crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        crate::msg::ExecuteMsg::TransferAdmin {
+            new_admin: MAKER.to_string(),
+        },
+    ).unwrap();
+
+    // ACCEPTOR tries to accept — should fail
+    let info = cosmwasm_std::testing::mock_info(ACCEPTOR, &[]);
+    let err = crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        crate::msg::ExecuteMsg::AcceptAdmin {},
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized));
+}
+
+#[test]
+fn test_accept_admin_without_proposal_rejected() {
+    let (mut deps, env) = setup_contract();
+
+    // No TransferAdmin was called — AcceptAdmin should fail
+    let info = cosmwasm_std::testing::mock_info(MAKER, &[]);
+    let err = crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        crate::msg::ExecuteMsg::AcceptAdmin {},
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized));
+}
+
+#[test]
+fn test_new_admin_can_update_config() {
+    let (mut deps, env) = setup_contract();
+
+    // Transfer admin to MAKER
+    let info = cosmwasm_std::testing::mock_info(ADMIN, &[]);
+    crate::contract::execute(
+        deps.as_mut(), env.clone(), info,
+        crate::msg::ExecuteMsg::TransferAdmin { new_admin: MAKER.to_string() },
+    ).unwrap();
+    let info = cosmwasm_std::testing::mock_info(MAKER, &[]);
+    crate::contract::execute(
+        deps.as_mut(), env.clone(), info.clone(),
+        crate::msg::ExecuteMsg::AcceptAdmin {},
+    ).unwrap();
+
+    // New admin (MAKER) can update config
+    crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        crate::msg::ExecuteMsg::UpdateConfig {
+            treasury: None,
+            commission_bps: Some(200),
+            min_bet: None,
+            reveal_timeout_secs: None,
+            max_open_per_user: None,
+            max_daily_amount_per_user: None,
+            bet_ttl_secs: None,
+        },
+    ).unwrap();
+
+    let config = query_config(&deps, &env);
+    assert_eq!(config.commission_bps, 200);
+    assert_eq!(config.admin.as_str(), MAKER);
+
+    // Old admin can no longer update
+    let old_admin_info = cosmwasm_std::testing::mock_info(ADMIN, &[]);
+    let err = crate::contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        old_admin_info,
+        crate::msg::ExecuteMsg::UpdateConfig {
+            treasury: None,
+            commission_bps: Some(300),
+            min_bet: None,
+            reveal_timeout_secs: None,
+            max_open_per_user: None,
+            max_daily_amount_per_user: None,
+            bet_ttl_secs: None,
+        },
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized));
+}
+
+// ============================================================
+// Resolution hooks
+// ============================================================
+
+#[test]
+fn test_hooks_receive_resolutions_and_failures_do_not_block() {
+    use crate::msg::{ExecuteMsg, HookMsg, HooksResponse, QueryMsg};
+    use cosmwasm_std::Addr;
+    use cw_multi_test::Executor;
+
+    let (mut app, vault) = setup_app();
+    let code_id = app.store_code(hook_receiver());
+    let receiver = app
+        .instantiate_contract(code_id, Addr::unchecked(ADMIN), &false, &[], "receiver", None)
+        .unwrap();
+    let failing = app
+        .instantiate_contract(code_id, Addr::unchecked(ADMIN), &true, &[], "failing", None)
+        .unwrap();
+
+    let register = |contract: &Addr, gas_limit| ExecuteMsg::RegisterHook {
+        contract: contract.to_string(),
+        gas_limit,
+    };
+    let msg = register(&receiver, 200_000);
+    let err = app
+        .execute_contract(Addr::unchecked(RANDOM_USER), vault.clone(), &msg, &[])
+        .unwrap_err();
+    assert!(matches!(err.downcast_ref::<ContractError>(), Some(ContractError::Unauthorized)));
+    let err = app
+        .execute_contract(Addr::unchecked(ADMIN), vault.clone(), &register(&receiver, 10), &[])
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<ContractError>(),
+        Some(ContractError::InvalidHookGasLimit { .. })
+    ));
+    for hook in [&receiver, &failing] {
+        app.execute_contract(Addr::unchecked(ADMIN), vault.clone(), &register(hook, 200_000), &[])
+            .unwrap();
+    }
+    let hooks: HooksResponse = app.wrap().query_wasm_smart(&vault, &QueryMsg::Hooks {}).unwrap();
+    assert_eq!(hooks.hooks.len(), 2);
+
+    // Play a bet: the failing hook is reported, the payout still goes through
+    app_deposit(&mut app, &vault, MAKER, 500);
+    app_deposit(&mut app, &vault, ACCEPTOR, 500);
+    let secret = b"secret_32_bytes_exactly_here!!!!";
+    app.execute_contract(
+        Addr::unchecked(MAKER), vault.clone(),
+        &ExecuteMsg::CreateBet {
+            amount: Uint128::new(100),
+            commitment: compute_commitment(MAKER, &Side::Heads, secret),
+            voucher: None,
+        },
+        &[],
+    ).unwrap();
+    let res = app.execute_contract(
+        Addr::unchecked(ACCEPTOR), vault.clone(),
+        &ExecuteMsg::AcceptAndReveal {
+            bet_id: 1,
+            guess: Side::Tails,
+            side: Side::Heads,
+            secret: Binary::from(secret.to_vec()),
+            voucher: None,
+        },
+        &[],
+    ).unwrap();
+    let failed: Vec<_> =
+        res.events.iter().filter(|e| e.ty == "wasm-coinflip_hook_failed").collect();
+    assert_eq!(failed.len(), 1);
+    assert!(failed[0].attributes.iter().any(|a| a.key == "error"));
+    assert!(failed[0]
+        .attributes
+        .iter()
+        .any(|a| a.key == "contract" && a.value == failing.as_str()));
+    let outcome: crate::msg::BetOutcomeResponse =
+        cosmwasm_std::from_json(res.data.unwrap()).unwrap();
+    assert_eq!(outcome.winner, MAKER);
+
+    let last: Option<HookMsg> =
+        app.wrap().query_wasm_smart(&receiver, &cosmwasm_std::Empty {}).unwrap();
+    assert_eq!(
+        last,
+        Some(HookMsg::BetResolvedHook {
+            bet_id: 1,
+            maker: MAKER.to_string(),
+            acceptor: ACCEPTOR.to_string(),
+            winner: MAKER.to_string(),
+            amount: Uint128::new(100),
+            commission: Uint128::new(20),
+        })
+    );
+    let balance: crate::msg::VaultBalanceResponse = app
+        .wrap()
+        .query_wasm_smart(&vault, &QueryMsg::VaultBalance { address: MAKER.to_string() })
+        .unwrap();
+    assert_eq!(balance.available, Uint128::new(580));
+
+    // Removing a hook
+    let remove = ExecuteMsg::RemoveHook { contract: failing.to_string() };
+    app.execute_contract(Addr::unchecked(ADMIN), vault.clone(), &remove, &[]).unwrap();
+    let err = app
+        .execute_contract(Addr::unchecked(ADMIN), vault.clone(), &remove, &[])
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<ContractError>(),
+        Some(ContractError::HookNotFound { .. })
+    ));
+    let hooks: HooksResponse = app.wrap().query_wasm_smart(&vault, &QueryMsg::Hooks {}).unwrap();
+    let expected = crate::msg::HookResponse { contract: receiver, gas_limit: 200_000 };
+    assert_eq!(hooks.hooks, vec![expected]);
+}
+
+// ============================================================
+// Commission split
+// ============================================================
+
+#[test]
+fn test_commission_split_accrues_and_flushes() {
+    use crate::msg::{AccruedFeesResponse, CommissionShareMsg, ExecuteMsg, QueryMsg};
+    use cosmwasm_std::testing::mock_info;
+    use cosmwasm_std::{from_json, CosmosMsg, WasmMsg};
+
+    let (mut deps, env) = setup_contract();
+    let share = |label: &str, address: &str, bps, distribute| CommissionShareMsg {
+        label: label.to_string(),
+        address: address.to_string(),
+        bps,
+        distribute,
+    };
+    let mut update = |sender: &str, shares| {
+        let msg = ExecuteMsg::UpdateCommissionSplit { shares };
+        crate::contract::execute(deps.as_mut(), env.clone(), mock_info(sender, &[]), msg)
+    };
+
+    let split = vec![
+        share("treasury", TREASURY, 7000, false),
+        share("jackpot", "jackpot", 3000, false),
+    ];
+    let err = update(MAKER, split.clone()).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized));
+    for invalid in [
+        vec![share("treasury", TREASURY, 7000, false)],
+        vec![share("treasury", TREASURY, 7000, false), share("treasury", "jackpot", 3000, false)],
+        vec![share("treasury", TREASURY, 10_000, false), share("jackpot", "jackpot", 0, false)],
+        // Staking only distributes the native denom
+        vec![share("stakers", "staking", 10_000, true)],
+    ] {
+        let err = update(ADMIN, invalid).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidCommissionSplit { .. }));
+    }
+    update(ADMIN, split).unwrap();
+    assert_eq!(query_config(&deps, &env).commission_split.len(), 2);
+
+    // Resolve a bet: commission 20 accrues 14 / 6 instead of crediting the treasury balance
+    deposit(&mut deps, &env, MAKER, 500).unwrap();
+    deposit(&mut deps, &env, ACCEPTOR, 500).unwrap();
+    let secret = b"secret_32_bytes_exactly_here!!!!";
+    create_bet(&mut deps, &env, MAKER, 100, compute_commitment(MAKER, &Side::Heads, secret))
+        .unwrap();
+    accept_bet(&mut deps, &env, ACCEPTOR, 1, Side::Tails).unwrap();
+    let res = reveal_bet(&mut deps, &env, MAKER, 1, Side::Heads, Binary::from(secret.to_vec()))
+        .unwrap();
+    let accrued = events_of(&res, "coinflip_commission_accrued");
+    assert_eq!(accrued.len(), 2);
+    assert_eq!(attr(&accrued[0], "label"), "treasury");
+    assert_eq!(attr(&accrued[0], "amount"), "14");
+    assert_eq!(attr(&accrued[1], "amount"), "6");
+    assert_eq!(query_vault_balance(&deps, &env, TREASURY).available, Uint128::zero());
+    assert_totals_consistent(&deps);
+
+    let fees: AccruedFeesResponse = from_json(
+        crate::contract::query(deps.as_ref(), env.clone(), QueryMsg::AccruedFees {}).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(fees.total, Uint128::new(20));
+    set_contract_balance(&mut deps, &env, 1000);
+    let solvency: crate::msg::SolvencyResponse = from_json(
+        crate::contract::query(deps.as_ref(), env.clone(), QueryMsg::Solvency {}).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(solvency.total_accrued_fees, Uint128::new(20));
+    assert_eq!(solvency.total_owed, Uint128::new(1000));
+
+    // Anyone can flush; each share is transferred to its recipient
+    let flush = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>| {
+        let info = mock_info(RANDOM_USER, &[]);
+        crate::contract::execute(deps.as_mut(), env.clone(), info, ExecuteMsg::FlushFees {})
+    };
+    let res = flush(&mut deps).unwrap();
+    let sends: Vec<cw20::Cw20ExecuteMsg> = res
+        .messages
+        .iter()
+        .map(|sub| match &sub.msg {
+            CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, msg, .. }) => {
+                assert_eq!(contract_addr, TOKEN_CW20);
+                from_json(msg).unwrap()
+            }
+            other => panic!("unexpected message {other:?}"),
+        })
+        .collect();
+    let transfer = |recipient: &str, amount| cw20::Cw20ExecuteMsg::Transfer {
+        recipient: recipient.to_string(),
+        amount: Uint128::new(amount),
+    };
+    assert_eq!(sends, vec![transfer("jackpot", 6), transfer(TREASURY, 14)]);
+    assert_totals_consistent(&deps);
+
+    let err = flush(&mut deps).unwrap_err();
+    assert!(matches!(err, ContractError::NothingToFlush));
+}
+
+// ============================================================
+// Referrals
+// ============================================================
+
+#[test]
+fn test_referral_registry_and_rewards() {
+    use crate::msg::{ExecuteMsg, QueryMsg, ReferralResponse};
+    use cosmwasm_std::testing::mock_info;
+    use cosmwasm_std::{from_json, Addr};
+
+    let (mut deps, env) = setup_contract();
+    let mut set_referrer = |player: &str, referrer: &str| {
+        let msg = ExecuteMsg::SetReferrer { referrer: referrer.to_string() };
+        crate::contract::execute(deps.as_mut(), env.clone(), mock_info(player, &[]), msg)
+    };
+
+    // MAKER <- ref_1 <- ref_2 <- ref_3 <- ref_4, bound bottom-up so existing
+    // referees carry over into the trees above
+    set_referrer(MAKER, "ref_1").unwrap();
+    set_referrer("ref_1", "ref_2").unwrap();
+    set_referrer("ref_2", "ref_3").unwrap();
+    set_referrer("ref_3", "ref_4").unwrap();
+
+    let err = set_referrer(MAKER, "ref_2").unwrap_err();
+    assert!(matches!(err, ContractError::ReferrerAlreadySet));
+    let err = set_referrer(ACCEPTOR, ACCEPTOR).unwrap_err();
+    assert!(matches!(err, ContractError::ReferralCycle));
+    let err = set_referrer("ref_4", MAKER).unwrap_err();
+    assert!(matches!(err, ContractError::ReferralCycle));
+
+    let query_referral = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, address: &str| {
+        let msg = QueryMsg::Referral { address: address.to_string() };
+        let res = crate::contract::query(deps.as_ref(), env.clone(), msg).unwrap();
+        from_json::<ReferralResponse>(res).unwrap()
+    };
+    let ref_1 = query_referral(&deps, "ref_1");
+    assert_eq!(ref_1.referrer, Some(Addr::unchecked("ref_2")));
+    assert_eq!(ref_1.tree, vec![1, 0, 0]);
+    assert_eq!(query_referral(&deps, "ref_3").tree, vec![1, 1, 1]);
+    // MAKER is four levels below ref_4
+    assert_eq!(query_referral(&deps, "ref_4").tree_size, 3);
+
+    // Rewards: 150 / 70 / 30 bps of the pot, within the 1000 bps commission
+    let mut update = |bps: Vec<u16>| {
+        let msg = ExecuteMsg::UpdateReferralBps { bps };
+        crate::contract::execute(deps.as_mut(), env.clone(), mock_info(ADMIN, &[]), msg)
+    };
+    let err = update(vec![500, 300, 300]).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidReferralBps { .. }));
+    let err = update(vec![10, 10, 10, 10]).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidReferralBps { .. }));
+    update(vec![150, 70, 30]).unwrap();
+    assert_eq!(query_config(&deps, &env).referral_bps, vec![150, 70, 30]);
+
+    // MAKER wins a pot of 2000: commission 200, of which 30 + 14 + 6 go to referrers
+    deposit(&mut deps, &env, MAKER, 5000).unwrap();
+    deposit(&mut deps, &env, ACCEPTOR, 5000).unwrap();
+    let secret = b"secret_32_bytes_exactly_here!!!!";
+    create_bet(&mut deps, &env, MAKER, 1000, compute_commitment(MAKER, &Side::Heads, secret))
+        .unwrap();
+    accept_bet(&mut deps, &env, ACCEPTOR, 1, Side::Tails).unwrap();
+    let res = reveal_bet(&mut deps, &env, MAKER, 1, Side::Heads, Binary::from(secret.to_vec()))
+        .unwrap();
+    let rewards = events_of(&res, "coinflip_referral_reward");
+    assert_eq!(rewards.len(), 3);
+    assert_eq!(attr(&rewards[2], "referrer"), "ref_3");
+    assert_eq!(attr(&rewards[2], "level"), "3");
+
+    for (referrer, amount) in [("ref_1", 30), ("ref_2", 14), ("ref_3", 6), ("ref_4", 0)] {
+        assert_eq!(query_vault_balance(&deps, &env, referrer).available, Uint128::new(amount));
+    }
+    assert_eq!(query_vault_balance(&deps, &env, TREASURY).available, Uint128::new(150));
+    assert_eq!(query_vault_balance(&deps, &env, MAKER).available, Uint128::new(5800));
+    let ref_2 = query_referral(&deps, "ref_2");
+    assert_eq!(ref_2.earned, vec![Uint128::zero(), Uint128::new(14), Uint128::zero()]);
+    assert_eq!(ref_2.total_earned, Uint128::new(14));
+    assert_totals_consistent(&deps);
+}
+
+// ============================================================
+// Jackpots
+// ============================================================
+
+#[test]
+fn test_jackpot_pools_fill_and_draw() {
+    use crate::execute::jackpot::JACKPOT_ENTROPY_BLOCKS;
+    use crate::msg::{ExecuteMsg, JackpotDrawsResponse, JackpotPoolsResponse, QueryMsg};
+    use crate::state::JackpotTier;
+    use cosmwasm_std::testing::mock_info;
+    use cosmwasm_std::{from_json, Env};
+    use sha2::{Digest, Sha256};
+
+    let (mut deps, env) = setup_contract();
+    let mut later = env.clone();
+    later.block.height += JACKPOT_ENTROPY_BLOCKS;
+    let admin = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, env: &Env, msg| {
+        crate::contract::execute(deps.as_mut(), env.clone(), mock_info(ADMIN, &[]), msg)
+    };
+    let tier = |name: &str, bps, target, min_games| JackpotTier {
+        name: name.to_string(),
+        bps,
+        target: Uint128::new(target),
+        min_games,
+    };
+
+    let tiers = vec![tier("mini", 1200, 30, 0)];
+    let err = admin(&mut deps, &env, ExecuteMsg::UpdateJackpotTiers { tiers }).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidJackpotTiers { .. }));
+    // mini: 100 bps of every pot, drawn at 30. mega: 50 bps, only for players with a game
+    let tiers = vec![tier("mini", 100, 30, 0), tier("mega", 50, 1_000_000, 1)];
+    admin(&mut deps, &env, ExecuteMsg::UpdateJackpotTiers { tiers }).unwrap();
+
+    let seed = Binary::from(b"jackpot seed".to_vec());
+    let commit = |seed: &Binary| ExecuteMsg::CommitJackpotSeed {
+        tier: 0,
+        commitment: Binary::from(Sha256::digest(seed.as_slice()).to_vec()),
+    };
+    admin(&mut deps, &env, commit(&seed)).unwrap();
+    let err = admin(&mut deps, &env, commit(&seed)).unwrap_err();
+    assert!(matches!(err, ContractError::JackpotSeedAlreadyCommitted { tier: 0 }));
+    let draw = |seed: &Binary| ExecuteMsg::DrawJackpot { tier: 0, seed: seed.clone() };
+    let err = admin(&mut deps, &env, draw(&seed)).unwrap_err();
+    assert!(matches!(err, ContractError::JackpotNotFilled { tier: 0 }));
+
+    // Two pots of 2000: each adds 20 to mini out of the 200 commission. Mega has no
+    // seed committed, so it is not funded.
+    deposit(&mut deps, &env, MAKER, 5000).unwrap();
+    deposit(&mut deps, &env, ACCEPTOR, 5000).unwrap();
+    let secret = b"secret_32_bytes_exactly_here!!!!";
+    let play = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, bet_id| {
+        create_bet(deps, &env, MAKER, 1000, compute_commitment(MAKER, &Side::Heads, secret))
+            .unwrap();
+        accept_bet(deps, &env, ACCEPTOR, bet_id, Side::Tails).unwrap();
+        let secret = Binary::from(secret.to_vec());
+        reveal_bet(deps, &env, MAKER, bet_id, Side::Heads, secret).unwrap()
+    };
+    let mut filled = vec![];
+    for bet_id in 1..=2 {
+        let res = play(&mut deps, bet_id);
+        filled.push(events_of(&res, "coinflip_jackpot_filled").len());
+    }
+    assert_eq!(filled, vec![0, 1]);
+    assert_eq!(query_vault_balance(&deps, &env, TREASURY).available, Uint128::new(360));
+    assert_totals_consistent(&deps);
+
+    let query_pools = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| {
+        let res = crate::contract::query(deps.as_ref(), env.clone(), QueryMsg::JackpotPools {});
+        from_json::<JackpotPoolsResponse>(res.unwrap()).unwrap().pools
+    };
+    // The filled cycle is frozen; the next one starts empty and unfunded
+    let pools = query_pools(&deps);
+    let pending = pools[0].pending_draw.clone().unwrap();
+    assert_eq!((pending.cycle, pending.amount), (0, Uint128::new(40)));
+    assert_eq!(pending.total_weight, Uint128::new(4000));
+    assert_eq!(pending.draw_from_height, env.block.height + JACKPOT_ENTROPY_BLOCKS);
+    assert_eq!((pools[0].cycle, pools[0].amount), (1, Uint128::zero()));
+    assert!(!pools[0].seed_committed);
+    assert_eq!(pools[1].amount, Uint128::zero());
+
+    // Bets after the fill enter the next cycle and leave the filled one untouched
+    let next_seed = Binary::from(b"next seed".to_vec());
+    admin(&mut deps, &env, commit(&next_seed)).unwrap();
+    let res = play(&mut deps, 3);
+    let funded = &events_of(&res, "coinflip_jackpot_funded")[0];
+    assert_eq!(attr(funded, "cycle"), "1");
+    let pools = query_pools(&deps);
+    let pending = pools[0].pending_draw.clone().unwrap();
+    assert_eq!((pending.amount, pending.total_weight), (Uint128::new(40), Uint128::new(4000)));
+    assert_eq!((pools[0].amount, pools[0].total_weight), (Uint128::new(20), Uint128::new(2000)));
+
+    // Draw: not before the entropy window closes, and the seed must match
+    let err = admin(&mut deps, &env, draw(&seed)).unwrap_err();
+    assert!(matches!(err, ContractError::JackpotEntropyPending { tier: 0, .. }));
+    let wrong = Binary::from(b"other seed".to_vec());
+    let err = admin(&mut deps, &later, draw(&wrong)).unwrap_err();
+    assert!(matches!(err, ContractError::CommitmentMismatch));
+    let before = [MAKER, ACCEPTOR].map(|p| query_vault_balance(&deps, &env, p).available);
+    let res = admin(&mut deps, &later, draw(&seed)).unwrap();
+    let drawn = &events_of(&res, "coinflip_jackpot_drawn")[0];
+    let winner = attr(drawn, "winner").to_string();
+    let index = if winner == MAKER { 0 } else { 1 };
+    assert_eq!(
+        query_vault_balance(&deps, &env, &winner).available,
+        before[index] + Uint128::new(40)
+    );
+    assert_totals_consistent(&deps);
+
+    let res = crate::contract::query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::JackpotDraws { tier: 0, start_after: None, limit: None },
+    )
+    .unwrap();
+    let draws = from_json::<JackpotDrawsResponse>(res).unwrap().draws;
+    assert_eq!(draws.len(), 1);
+    assert_eq!((draws[0].cycle, draws[0].amount), (0, Uint128::new(40)));
+    assert_eq!(draws[0].total_weight, Uint128::new(4000));
+    assert_eq!(draws[0].winner.as_str(), winner);
+    assert_eq!(draws[0].seed, seed);
+    let pools = query_pools(&deps);
+    assert_eq!((pools[0].cycle, pools[0].amount), (1, Uint128::new(20)));
+    assert!(pools[0].pending_draw.is_none());
+    assert!(pools[0].seed_committed);
+
+    // Mega never got funded and can go; mini still holds a pool and cannot
+    let tiers = vec![tier("mini", 100, 30, 0)];
+    admin(&mut deps, &env, ExecuteMsg::UpdateJackpotTiers { tiers }).unwrap();
+    let tiers = vec![];
+    let err = admin(&mut deps, &env, ExecuteMsg::UpdateJackpotTiers { tiers }).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidJackpotTiers { .. }));
+
+    // Entries of the drawn cycle can be pruned, those of the current one cannot
+    let prune = |cycle| ExecuteMsg::PruneJackpotEntries { tier: 0, cycle, limit: Some(4) };
+    let err = admin(&mut deps, &env, prune(1)).unwrap_err();
+    assert!(matches!(err, ContractError::JackpotCycleNotFinished { tier: 0, cycle: 1 }));
+    // 4 stake ranges and 2 player entries
+    let res = admin(&mut deps, &env, prune(0)).unwrap();
+    assert_eq!(res.attributes.iter().find(|a| a.key == "completed").unwrap().value, "false");
+    let res = admin(&mut deps, &env, prune(0)).unwrap();
+    assert_eq!(res.attributes.iter().find(|a| a.key == "pruned").unwrap().value, "2");
+    assert_eq!(res.attributes.iter().find(|a| a.key == "completed").unwrap().value, "true");
+    let entries = crate::state::JACKPOT_ENTRIES
+        .prefix((0, 0))
+        .keys(&deps.storage, None, None, cosmwasm_std::Order::Ascending)
+        .count();
+    assert_eq!(entries, 0);
+}
+
+#[test]
+fn test_jackpot_draw_weights_entrants_and_falls_back_after_deadline() {
+    use crate::execute::jackpot::{JACKPOT_ENTROPY_BLOCKS, JACKPOT_REVEAL_SECS};
+    use crate::msg::{ExecuteMsg, JackpotDrawsResponse, JackpotPoolsResponse, QueryMsg};
+    use crate::state::JackpotTier;
+    use cosmwasm_std::testing::mock_info;
+    use cosmwasm_std::{from_json, Env};
+    use sha2::{Digest, Sha256};
+
+    const PLAYER: &str = "fourth_player";
+    let (mut deps, env) = setup_contract();
+    let execute = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, env: &Env, sender, msg| {
+        crate::contract::execute(deps.as_mut(), env.clone(), mock_info(sender, &[]), msg)
+    };
+    let tiers = vec![JackpotTier {
+        name: "mini".to_string(),
+        bps: 100,
+        target: Uint128::new(8),
+        min_games: 0,
+    }];
+    execute(&mut deps, &env, ADMIN, ExecuteMsg::UpdateJackpotTiers { tiers }).unwrap();
+    let seed = Binary::from(b"jackpot seed".to_vec());
+    let commit = ExecuteMsg::CommitJackpotSeed {
+        tier: 0,
+        commitment: Binary::from(Sha256::digest(seed.as_slice()).to_vec()),
+    };
+    execute(&mut deps, &env, ADMIN, commit.clone()).unwrap();
+    for player in [MAKER, ACCEPTOR, RANDOM_USER, PLAYER] {
+        deposit(&mut deps, &env, player, 5000).unwrap();
+    }
+    let secret = b"secret_32_bytes_exactly_here!!!!";
+    let mut next_bet = 1;
+    let mut play =
+        |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, env: &Env, maker, acceptor, amount| {
+            let commitment = compute_commitment(maker, &Side::Heads, secret);
+            create_bet(deps, env, maker, amount, commitment).unwrap();
+            accept_bet(deps, env, acceptor, next_bet, Side::Tails).unwrap();
+            let secret = Binary::from(secret.to_vec());
+            reveal_bet(deps, env, maker, next_bet, Side::Heads, secret).unwrap();
+            next_bet += 1;
+        };
+
+    // Ranges in entry order: maker 0..100, acceptor 100..200, random 200..500, fourth 500..800.
+    // The pots add 2 and 6 to the pool, which fills at 8.
+    play(&mut deps, &env, MAKER, ACCEPTOR, 100);
+    play(&mut deps, &env, RANDOM_USER, PLAYER, 300);
+    let mut later = env.clone();
+    later.block.height += JACKPOT_ENTROPY_BLOCKS;
+    execute(&mut deps, &later, ADMIN, ExecuteMsg::DrawJackpot { tier: 0, seed: seed.clone() })
+        .unwrap();
+
+    let res = crate::contract::query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::JackpotDraws { tier: 0, start_after: None, limit: None },
+    )
+    .unwrap();
+    let draw = from_json::<JackpotDrawsResponse>(res).unwrap().draws.remove(0);
+    assert_eq!((draw.amount, draw.total_weight), (Uint128::new(8), Uint128::new(800)));
+    let mut hasher = Sha256::new();
+    hasher.update(seed.as_slice());
+    hasher.update(draw.entropy.as_slice());
+    let random: [u8; 32] = hasher.finalize().into();
+    let target = u128::from_be_bytes(random[..16].try_into().unwrap()) % 800;
+    let (expected, weight) = match target {
+        0..=99 => (MAKER, 100),
+        100..=199 => (ACCEPTOR, 100),
+        200..=499 => (RANDOM_USER, 300),
+        _ => (PLAYER, 300),
+    };
+    assert_eq!(draw.winner.as_str(), expected);
+    assert_eq!(draw.winner_weight, Uint128::new(weight));
+    assert_totals_consistent(&deps);
+
+    // The next cycle fills, but its seed is never revealed.
+    // Ranges: random 0..300, fourth 300..600, random 600..900, fourth 900..1200.
+    let next_seed = Binary::from(b"next seed".to_vec());
+    let commit = ExecuteMsg::CommitJackpotSeed {
+        tier: 0,
+        commitment: Binary::from(Sha256::digest(next_seed.as_slice()).to_vec()),
+    };
+    execute(&mut deps, &env, ADMIN, commit).unwrap();
+    play(&mut deps, &env, RANDOM_USER, PLAYER, 300);
+    play(&mut deps, &env, RANDOM_USER, PLAYER, 300);
+    let fallback = ExecuteMsg::FallbackDrawJackpot { tier: 0 };
+    let err = execute(&mut deps, &later, RANDOM_USER, fallback.clone()).unwrap_err();
+    assert!(matches!(err, ContractError::JackpotRevealPending { tier: 0, .. }));
+
+    // Bets after the fill go to the next cycle, so they cannot join this draw
+    let mut expired = env_at_time(env.block.time.seconds() + JACKPOT_REVEAL_SECS + 1);
+    expired.block.height = later.block.height;
+    play(&mut deps, &expired, MAKER, ACCEPTOR, 100);
+    let draw = ExecuteMsg::DrawJackpot { tier: 0, seed: next_seed };
+    let err = execute(&mut deps, &expired, ADMIN, draw).unwrap_err();
+    assert!(matches!(err, ContractError::JackpotRevealExpired { tier: 0, .. }));
+    let before = |who| query_vault_balance(&deps, &expired, who).available;
+    let balances = [before(RANDOM_USER), before(PLAYER)];
+    let res = execute(&mut deps, &expired, MAKER, fallback.clone()).unwrap();
+    let drawn = &events_of(&res, "coinflip_jackpot_drawn")[0];
+    assert_eq!((attr(drawn, "cycle"), attr(drawn, "fallback")), ("1", "true"));
+
+    // The winner follows from the fill's entropy alone
+    let res = crate::contract::query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::JackpotDraws { tier: 0, start_after: Some(0), limit: None },
+    )
+    .unwrap();
+    let draw = from_json::<JackpotDrawsResponse>(res).unwrap().draws.remove(0);
+    assert_eq!((draw.cycle, draw.amount), (1, Uint128::new(12)));
+    assert!(draw.seed.is_empty());
+    let mut hasher = Sha256::new();
+    hasher.update(b"coinflip_jackpot_fallback");
+    hasher.update(draw.entropy.as_slice());
+    let random: [u8; 32] = hasher.finalize().into();
+    let target = u128::from_be_bytes(random[..16].try_into().unwrap()) % 1200;
+    let (expected, index) = if (target / 300) % 2 == 0 { (RANDOM_USER, 0) } else { (PLAYER, 1) };
+    assert_eq!(draw.winner.as_str(), expected);
+    assert_eq!(draw.winner_weight, Uint128::new(600));
+    let after = query_vault_balance(&deps, &expired, expected).available;
+    assert_eq!(after, balances[index] + Uint128::new(12));
+
+    // Nothing is left to draw, and the current cycle is not funded until a seed is committed
+    let err = execute(&mut deps, &expired, MAKER, fallback).unwrap_err();
+    assert!(matches!(err, ContractError::JackpotNotFilled { tier: 0 }));
+    let res = crate::contract::query(deps.as_ref(), env.clone(), QueryMsg::JackpotPools {});
+    let pools = from_json::<JackpotPoolsResponse>(res.unwrap()).unwrap().pools;
+    assert_eq!((pools[0].cycle, pools[0].amount), (2, Uint128::zero()));
+    assert!(pools[0].pending_draw.is_none());
+    assert_totals_consistent(&deps);
+}
+
+// ============================================================
+// Rakeback
+// ============================================================
+
+#[test]
+fn test_rakeback_claim_and_sweep() {
+    use crate::msg::{ExecuteMsg, PlayerRakebackResponse, QueryMsg, RakebackEpochResponse};
+    use cosmwasm_std::testing::mock_info;
+    use cosmwasm_std::{from_json, Env};
+
+    const WEEK: u64 = 604_800;
+    let (mut deps, env) = setup_contract();
+    let execute = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, env: &Env, sender, msg| {
+        crate::contract::execute(deps.as_mut(), env.clone(), mock_info(sender, &[]), msg)
+    };
+
+    let update = |claim_epochs| ExecuteMsg::UpdateRakeback { bps: 1000, claim_epochs };
+    let err = execute(&mut deps, &env, ADMIN, update(0)).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidRakeback { .. }));
+    execute(&mut deps, &env, ADMIN, update(2)).unwrap();
+
+    // Pot of 2000: 10% of the 200 commission is reserved, 10 for each player
+    deposit(&mut deps, &env, MAKER, 5000).unwrap();
+    deposit(&mut deps, &env, ACCEPTOR, 5000).unwrap();
+    let secret = b"secret_32_bytes_exactly_here!!!!";
+    create_bet(&mut deps, &env, MAKER, 1000, compute_commitment(MAKER, &Side::Heads, secret))
+        .unwrap();
+    accept_bet(&mut deps, &env, ACCEPTOR, 1, Side::Tails).unwrap();
+    reveal_bet(&mut deps, &env, MAKER, 1, Side::Heads, Binary::from(secret.to_vec())).unwrap();
+    assert_eq!(query_vault_balance(&deps, &env, TREASURY).available, Uint128::new(180));
+    assert_totals_consistent(&deps);
+
+    let epoch = env.block.time.seconds() / WEEK;
+    let claim = ExecuteMsg::ClaimRakeback { epoch };
+    let err = execute(&mut deps, &env, MAKER, claim.clone()).unwrap_err();
+    assert!(matches!(err, ContractError::RakebackEpochNotOver { .. }));
+
+    let next_week = env_at_time(env.block.time.seconds() + WEEK);
+    let query = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, msg| {
+        crate::contract::query(deps.as_ref(), next_week.clone(), msg).unwrap()
+    };
+    let msg = QueryMsg::PlayerRakeback { address: MAKER.to_string(), epoch: Some(epoch) };
+    let rakeback: PlayerRakebackResponse = from_json(query(&deps, msg)).unwrap();
+    assert_eq!((rakeback.generated, rakeback.rebate), (Uint128::new(100), Uint128::new(10)));
+    assert!(rakeback.claimable);
+
+    let before = query_vault_balance(&deps, &env, MAKER).available;
+    execute(&mut deps, &next_week, MAKER, claim.clone()).unwrap();
+    assert_eq!(query_vault_balance(&deps, &env, MAKER).available, before + Uint128::new(10));
+    let err = execute(&mut deps, &next_week, MAKER, claim.clone()).unwrap_err();
+    assert!(matches!(err, ContractError::NoRakeback { .. }));
+    let sweep = ExecuteMsg::SweepRakeback { epoch };
+    let err = execute(&mut deps, &next_week, RANDOM_USER, sweep.clone()).unwrap_err();
+    assert!(matches!(err, ContractError::RakebackClaimWindowOpen { .. }));
+    assert_totals_consistent(&deps);
+
+    // After the window, the acceptor's unclaimed share goes back to the treasury
+    let closed = env_at_time(env.block.time.seconds() + 3 * WEEK);
+    let err = execute(&mut deps, &closed, ACCEPTOR, claim).unwrap_err();
+    assert!(matches!(err, ContractError::RakebackClaimExpired { .. }));
+    execute(&mut deps, &closed, RANDOM_USER, sweep.clone()).unwrap();
+    assert_eq!(query_vault_balance(&deps, &env, TREASURY).available, Uint128::new(190));
+    let err = execute(&mut deps, &closed, RANDOM_USER, sweep).unwrap_err();
+    assert!(matches!(err, ContractError::NoRakeback { .. }));
+
+    let reserve: RakebackEpochResponse =
+        from_json(query(&deps, QueryMsg::RakebackEpoch { epoch: Some(epoch) })).unwrap();
+    assert_eq!((reserve.reserved, reserve.claimed), (Uint128::new(20), Uint128::new(10)));
+    assert_eq!(reserve.claimable_until, epoch + 2);
+    assert!(reserve.swept);
+    assert_totals_consistent(&deps);
+}
+
+#[test]
+fn test_rakeback_sweep_takes_only_closed_epochs() {
+    use crate::msg::ExecuteMsg;
+    use cosmwasm_std::testing::mock_info;
+    use cosmwasm_std::Env;
+
+    const WEEK: u64 = 604_800;
+    let (mut deps, env) = setup_contract();
+    let execute = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, env: &Env, sender, msg| {
+        crate::contract::execute(deps.as_mut(), env.clone(), mock_info(sender, &[]), msg)
+    };
+    let secret = b"secret_32_bytes_exactly_here!!!!";
+    let play = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, env: &Env, bet_id| {
+        let commitment = compute_commitment(MAKER, &Side::Heads, secret);
+        create_bet(deps, env, MAKER, 1000, commitment).unwrap();
+        accept_bet(deps, env, ACCEPTOR, bet_id, Side::Tails).unwrap();
+        reveal_bet(deps, env, MAKER, bet_id, Side::Heads, Binary::from(secret.to_vec())).unwrap();
+    };
+    let treasury = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| {
+        query_vault_balance(deps, &env, TREASURY).available
+    };
+    let week = |n: u64| env_at_time(env.block.time.seconds() + n * WEEK);
+    let epoch = env.block.time.seconds() / WEEK;
+    let sweep = |epoch| ExecuteMsg::SweepRakeback { epoch };
+
+    // Claimable for one epoch after each epoch ends; 20 reserved per pot of 2000
+    let msg = ExecuteMsg::UpdateRakeback { bps: 1000, claim_epochs: 1 };
+    execute(&mut deps, &env, ADMIN, msg).unwrap();
+    deposit(&mut deps, &env, MAKER, 5000).unwrap();
+    deposit(&mut deps, &env, ACCEPTOR, 5000).unwrap();
+    play(&mut deps, &env, 1);
+    play(&mut deps, &week(1), 2);
+    let after_play = treasury(&deps);
+
+    // Two epochs on, the first closed unclaimed and is swept whole; the second is open
+    let err = execute(&mut deps, &week(1), RANDOM_USER, sweep(epoch)).unwrap_err();
+    assert!(matches!(err, ContractError::RakebackClaimWindowOpen { .. }));
+    let res = execute(&mut deps, &week(2), RANDOM_USER, sweep(epoch)).unwrap();
+    let swept = &events_of(&res, "coinflip_rakeback_swept")[0];
+    assert_eq!((attr(swept, "amount"), attr(swept, "treasury")), ("20", TREASURY));
+    assert_eq!(treasury(&deps), after_play + Uint128::new(20));
+    let err = execute(&mut deps, &week(2), RANDOM_USER, sweep(epoch + 1)).unwrap_err();
+    assert!(matches!(err, ContractError::RakebackClaimWindowOpen { .. }));
+    let err = execute(&mut deps, &week(2), MAKER, ExecuteMsg::ClaimRakeback { epoch });
+    assert!(matches!(err.unwrap_err(), ContractError::RakebackClaimExpired { .. }));
+    assert_totals_consistent(&deps);
+
+    // An epoch claimed in full sweeps nothing; one without games has no reserve
+    for player in [MAKER, ACCEPTOR] {
+        let msg = ExecuteMsg::ClaimRakeback { epoch: epoch + 1 };
+        execute(&mut deps, &week(2), player, msg).unwrap();
+    }
+    let res = execute(&mut deps, &week(3), RANDOM_USER, sweep(epoch + 1)).unwrap();
+    assert_eq!(attr(&events_of(&res, "coinflip_rakeback_swept")[0], "amount"), "0");
+    assert_eq!(treasury(&deps), after_play + Uint128::new(20));
+    let err = execute(&mut deps, &week(5), RANDOM_USER, sweep(epoch + 2)).unwrap_err();
+    assert!(matches!(err, ContractError::NoRakeback { .. }));
+    assert_totals_consistent(&deps);
+}
+
+// ============================================================
+// Loyalty
+// ============================================================
+
+#[test]
+fn test_loyalty_xp_and_fee_discounts() {
+    use crate::execute::loyalty::level_of;
+    use crate::msg::{ExecuteMsg, LoyaltyResponse, QueryMsg};
+    use cosmwasm_std::testing::mock_info;
+    use cosmwasm_std::{from_json, Env};
+
+    let (mut deps, env) = setup_contract();
+    let execute = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, env: &Env, sender, msg| {
+        crate::contract::execute(deps.as_mut(), env.clone(), mock_info(sender, &[]), msg)
+    };
+    let loyalty = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, address: &str| -> LoyaltyResponse {
+        let msg = QueryMsg::Loyalty { address: address.to_string() };
+        from_json(crate::contract::query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap()
+    };
+    let secret = b"secret_32_bytes_exactly_here!!!!";
+    let play = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, id: u64, amount: u128| {
+        let commitment = compute_commitment(MAKER, &Side::Heads, secret);
+        create_bet(deps, &env, MAKER, amount, commitment).unwrap();
+        accept_bet(deps, &env, ACCEPTOR, id, Side::Tails).unwrap();
+        reveal_bet(deps, &env, MAKER, id, Side::Heads, Binary::from(secret.to_vec())).unwrap()
+    };
+
+    assert_eq!((level_of(99), level_of(100), level_of(399), level_of(400)), (0, 1, 1, 2));
+    let update = |discount_bps| ExecuteMsg::UpdateLoyalty {
+        xp_unit: Uint128::new(100),
+        discount_bps,
+        discount_xp_cost: 15,
+    };
+    let err = execute(&mut deps, &env, MAKER, update(500)).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized));
+    let err = execute(&mut deps, &env, ADMIN, update(0)).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidLoyalty { .. }));
+    execute(&mut deps, &env, ADMIN, update(500)).unwrap();
+
+    // One XP per 100 wagered; the rest carries over to the next bet
+    deposit(&mut deps, &env, MAKER, 10_000).unwrap();
+    deposit(&mut deps, &env, ACCEPTOR, 10_000).unwrap();
+    play(&mut deps, 1, 1000);
+    let redeem = |bets| ExecuteMsg::RedeemFeeDiscount { bets };
+    let err = execute(&mut deps, &env, MAKER, redeem(1)).unwrap_err();
+    assert!(matches!(err, ContractError::InsufficientXp { need: 15, have: 10 }));
+    play(&mut deps, 2, 550);
+    assert_eq!(loyalty(&deps, ACCEPTOR).xp, 15);
+
+    let err = execute(&mut deps, &env, MAKER, redeem(0)).unwrap_err();
+    assert!(matches!(err, ContractError::ZeroDiscountBets));
+    execute(&mut deps, &env, MAKER, redeem(1)).unwrap();
+    let maker = loyalty(&deps, MAKER);
+    assert_eq!((maker.xp, maker.xp_spent, maker.xp_balance), (15, 15, 0));
+    assert_eq!((maker.level, maker.next_level_xp), (0, 100));
+    assert_eq!((maker.discount_bets, maker.discount_bps), (1, 500));
+
+    // Discounts keep their rate: another rate can only be redeemed once they are used
+    execute(&mut deps, &env, ADMIN, update(300)).unwrap();
+    let err = execute(&mut deps, &env, MAKER, redeem(1)).unwrap_err();
+    assert!(matches!(err, ContractError::FeeDiscountActive { bps: 500 }));
+
+    // The maker wins a pot of 2000 at 10% - 5% commission
+    let treasury = query_vault_balance(&deps, &env, TREASURY).available;
+    let res = play(&mut deps, 3, 1000);
+    assert!(res.events.iter().any(|e| e.ty == "coinflip_fee_discount_applied"));
+    let bet = query_bet(&deps, &env, 3);
+    assert_eq!(bet.commission_paid, Some(Uint128::new(100)));
+    assert_eq!(bet.payout_amount, Some(Uint128::new(1900)));
+    let after = query_vault_balance(&deps, &env, TREASURY).available;
+    assert_eq!(after, treasury + Uint128::new(100));
+    assert_eq!(loyalty(&deps, MAKER).discount_bets, 0);
+
+    // Used up: the next pot pays the full commission
+    play(&mut deps, 4, 1000);
+    assert_eq!(query_bet(&deps, &env, 4).commission_paid, Some(Uint128::new(200)));
+    assert_eq!(loyalty(&deps, MAKER).xp, 35);
+    assert_totals_consistent(&deps);
+}
+
+#[test]
+fn test_loyalty_levels_at_exact_boundaries() {
+    use crate::execute::loyalty::{level_of, level_xp};
+
+    assert_eq!(level_of(0), 0);
+    for level in [1, 2, 3, 7, 10, 99, 1_000, 65_535, 65_536, 1 << 20, 100_000_000] {
+        assert_eq!(level_of(level_xp(level)), level);
+        assert_eq!(level_of(level_xp(level) - 1), level - 1);
+        assert_eq!(level_of(level_xp(level + 1) - 1), level);
+    }
+
+    // The highest level reachable in u64 XP
+    let top = level_of(u64::MAX);
+    assert!(top * top <= u64::MAX / 100);
+    assert!((top + 1) * (top + 1) > u64::MAX / 100);
+}
+
+// ============================================================
+// Stake discounts
+// ============================================================
+
+#[test]
+fn test_stake_discounts_lower_winner_commission() {
+    use crate::msg::{EffectiveCommissionResponse, ExecuteMsg, QueryMsg};
+    use crate::state::StakeDiscount;
+    use cosmwasm_std::testing::mock_info;
+    use crate::msg::StakingQueryMsg;
+    use cosmwasm_std::{from_json, ContractResult, SystemError, SystemResult, WasmQuery};
+
+    let (mut deps, env) = setup_contract();
+    // launch-staking stand-in: MAKER has 5000 LAUNCH staked
+    deps.querier.update_wasm(|query| match query {
+        WasmQuery::Smart { contract_addr, msg } if contract_addr == "staking" => {
+            let response = match from_json(msg).unwrap() {
+                StakingQueryMsg::StakerInfo { address } if address == MAKER => {
+                    r#"{"staked":"5000","pending_rewards":"0","total_claimed":"0"}"#
+                }
+                StakingQueryMsg::StakerInfo { .. } => {
+                    r#"{"staked":"0","pending_rewards":"0","total_claimed":"0"}"#
+                }
+                _ => r#"{"total_staked":"5000"}"#,
+            };
+            SystemResult::Ok(ContractResult::Ok(Binary::from(response.as_bytes())))
+        }
+        _ => SystemResult::Err(SystemError::NoSuchContract {
+            addr: "unknown".to_string(),
+        }),
+    });
+    let tier = |min_stake, bps| StakeDiscount { min_stake: Uint128::new(min_stake), bps };
+    let mut update = |sender: &str, staking_contract: Option<&str>, discounts| {
+        let msg = ExecuteMsg::UpdateStakeDiscounts {
+            staking_contract: staking_contract.map(str::to_string),
+            discounts,
+        };
+        crate::contract::execute(deps.as_mut(), env.clone(), mock_info(sender, &[]), msg)
+    };
+
+    let discounts = vec![tier(1000, 200), tier(5000, 500)];
+    let err = update(MAKER, Some("staking"), discounts.clone()).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized));
+    for (staking, invalid) in [
+        (Some("staking"), vec![tier(5000, 500), tier(1000, 200)]),
+        (Some("staking"), vec![tier(1000, 0)]),
+        (None, discounts.clone()),
+        (Some("not_staking"), discounts.clone()),
+    ] {
+        let err = update(ADMIN, staking, invalid).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidStakeDiscounts { .. }));
+    }
+    update(ADMIN, Some("staking"), discounts).unwrap();
+
+    let effective = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, address: &str| {
+        let msg = QueryMsg::EffectiveCommission { address: address.to_string() };
+        let res = crate::contract::query(deps.as_ref(), env.clone(), msg).unwrap();
+        from_json::<EffectiveCommissionResponse>(res).unwrap()
+    };
+    let maker = effective(&deps, MAKER);
+    assert_eq!((maker.staked, maker.stake_discount_bps), (Uint128::new(5000), 500));
+    assert_eq!((maker.commission_bps, maker.effective_bps), (1000, 500));
+    assert_eq!(effective(&deps, ACCEPTOR).effective_bps, 1000);
+
+    // The maker wins a pot of 2000 at 10% - 5% and the rate is kept on the bet
+    deposit(&mut deps, &env, MAKER, 5000).unwrap();
+    deposit(&mut deps, &env, ACCEPTOR, 5000).unwrap();
+    let secret = b"secret_32_bytes_exactly_here!!!!";
+    create_bet(&mut deps, &env, MAKER, 1000, compute_commitment(MAKER, &Side::Heads, secret))
+        .unwrap();
+    accept_bet(&mut deps, &env, ACCEPTOR, 1, Side::Tails).unwrap();
+    let res =
+        reveal_bet(&mut deps, &env, MAKER, 1, Side::Heads, Binary::from(secret.to_vec())).unwrap();
+    assert!(res.events.iter().any(|e| e.ty == "coinflip_stake_discount_applied"));
+    let bet = query_bet(&deps, &env, 1);
+    assert_eq!(bet.commission_bps, Some(500));
+    assert_eq!(bet.commission_paid, Some(Uint128::new(100)));
+    assert_eq!(query_vault_balance(&deps, &env, TREASURY).available, Uint128::new(100));
+    assert_totals_consistent(&deps);
+}
+
+// ============================================================
+// Fee schedule
+// ============================================================
+
+#[test]
+fn test_fee_schedule_tiers_promo_and_floor() {
+    use crate::msg::{ExecuteMsg, QueryMsg, QuoteFeeResponse};
+    use crate::state::{FeePromo, FeeTier, Loyalty, LOYALTY};
+    use cosmwasm_std::testing::mock_info;
+    use cosmwasm_std::{from_json, Addr, Env};
+
+    let (mut deps, env) = setup_contract();
+    let now = env.block.time.seconds();
+    let execute = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, sender, msg| {
+        crate::contract::execute(deps.as_mut(), env.clone(), mock_info(sender, &[]), msg)
+    };
+    let tier = |min_amount, bps| FeeTier { min_amount: Uint128::new(min_amount), bps };
+    let promo = |start, end| Some(FeePromo { start, end, bps: 300 });
+    let schedule = |tiers, promo, min_commission_bps| ExecuteMsg::UpdateFeeSchedule {
+        tiers,
+        promo,
+        min_commission_bps,
+    };
+
+    let tiers = vec![tier(1000, 800), tier(5000, 500)];
+    let msg = schedule(tiers.clone(), promo(now + 1000, now + 2000), 300);
+    let err = execute(&mut deps, MAKER, msg.clone()).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized));
+    let err = execute(&mut deps, ADMIN, schedule(vec![tier(1000, 6000)], None, 0)).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidCommission { max_bps: 5000 }));
+    for invalid in [
+        schedule(vec![tier(5000, 500), tier(1000, 800)], None, 0),
+        schedule(tiers.clone(), promo(now + 1000, now + 1000), 0),
+        // The floor is above the 300 bps promo
+        schedule(tiers.clone(), promo(now + 1000, now + 2000), 400),
+    ] {
+        let err = execute(&mut deps, ADMIN, invalid).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidFeeSchedule { .. }));
+    }
+    execute(&mut deps, ADMIN, msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateConfig {
+        treasury: None,
+        commission_bps: Some(200),
+        min_bet: None,
+        reveal_timeout_secs: None,
+        max_open_per_user: None,
+        max_daily_amount_per_user: None,
+        bet_ttl_secs: None,
+    };
+    let err = execute(&mut deps, ADMIN, msg).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidFeeSchedule { .. }));
+
+    let quote = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, env: &Env, amount, player: Option<&str>| {
+        let msg = QueryMsg::QuoteFee {
+            amount: Uint128::new(amount),
+            player: player.map(str::to_string),
+        };
+        let res = crate::contract::query(deps.as_ref(), env.clone(), msg).unwrap();
+        from_json::<QuoteFeeResponse>(res).unwrap()
+    };
+    let rates = [100, 1000, 4999, 5000].map(|amount| quote(&deps, &env, amount, None));
+    let rates = rates.map(|quoted| quoted.effective_bps);
+    assert_eq!(rates, [1000, 800, 800, 500]);
+    let during_promo = quote(&deps, &env_at_time(now + 1500), 5000, None);
+    assert!(during_promo.promo_active);
+    assert_eq!((during_promo.effective_bps, during_promo.commission), (300, Uint128::new(300)));
+
+    // A 500 bps fee discount on an 800 bps bet stops at the 300 bps floor
+    let loyalty = Loyalty { discount_bets: 1, discount_bps: 500, ..Loyalty::default() };
+    LOYALTY.save(&mut deps.storage, &Addr::unchecked(MAKER), &loyalty).unwrap();
+    let quoted = quote(&deps, &env, 1000, Some(MAKER));
+    assert_eq!((quoted.schedule_bps, quoted.fee_discount_bps), (800, 500));
+    assert_eq!(quoted.effective_bps, 300);
+
+    deposit(&mut deps, &env, MAKER, 5000).unwrap();
+    deposit(&mut deps, &env, ACCEPTOR, 5000).unwrap();
+    let secret = b"secret_32_bytes_exactly_here!!!!";
+    create_bet(&mut deps, &env, MAKER, 1000, compute_commitment(MAKER, &Side::Heads, secret))
+        .unwrap();
+    accept_bet(&mut deps, &env, ACCEPTOR, 1, Side::Tails).unwrap();
+    reveal_bet(&mut deps, &env, MAKER, 1, Side::Heads, Binary::from(secret.to_vec())).unwrap();
+    let bet = query_bet(&deps, &env, 1);
+    assert_eq!((bet.commission_bps, bet.commission_paid), (Some(300), Some(Uint128::new(60))));
+    assert_totals_consistent(&deps);
+}
+
+// ============================================================
+// Promo vouchers
+// ============================================================
+
+#[test]
+fn test_promo_vouchers_discount_and_bonus() {
+    use crate::msg::{ExecuteMsg, PromoGrant, PromoVoucher, PromoVoucherResponse, QueryMsg};
+    use cosmwasm_std::testing::mock_info;
+    use cosmwasm_std::from_json;
+
+    let (mut deps, env) = setup_contract();
+    let now = env.block.time.seconds();
+    let (key, pubkey) = promo_key();
+    let execute = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, sender, msg| {
+        crate::contract::execute(deps.as_mut(), env.clone(), mock_info(sender, &[]), msg)
+    };
+    let create = |amount, side: &Side, voucher: Option<PromoVoucher>| ExecuteMsg::CreateBet {
+        amount: Uint128::new(amount),
+        commitment: compute_commitment(MAKER, side, b"secret_32_bytes_exactly_here!!!!"),
+        voucher,
+    };
+    let discount = |bps| PromoGrant::CommissionDiscount { bps };
+    let free = sign_voucher(&key, &env, "free", discount(10_000), now + 100, 2, None);
+
+    deposit(&mut deps, &env, MAKER, 5000).unwrap();
+    deposit(&mut deps, &env, ACCEPTOR, 5000).unwrap();
+    let err = execute(&mut deps, MAKER, create(1000, &Side::Heads, Some(free.clone())));
+    assert!(matches!(err.unwrap_err(), ContractError::PromosDisabled));
+
+    let msg = ExecuteMsg::UpdatePromoKey { pubkey: Some(Binary::from(vec![2u8; 20])) };
+    let err = execute(&mut deps, ADMIN, msg).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidPromoKey { len: 20 }));
+    let msg = ExecuteMsg::UpdatePromoKey { pubkey: Some(pubkey) };
+    let err = execute(&mut deps, MAKER, msg.clone()).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized));
+    execute(&mut deps, ADMIN, msg).unwrap();
+
+    // A voucher whose grant was changed after signing
+    let mut forged = free.clone();
+    forged.max_uses = 100;
+    let err = execute(&mut deps, MAKER, create(1000, &Side::Heads, Some(forged))).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidVoucher { .. }));
+    let expired = sign_voucher(&key, &env, "old", discount(500), now - 1, 1, None);
+    let err = execute(&mut deps, MAKER, create(1000, &Side::Heads, Some(expired))).unwrap_err();
+    assert!(matches!(err, ContractError::VoucherExpired { .. }));
+
+    // The maker wins a commission-free pot
+    let res = execute(&mut deps, MAKER, create(1000, &Side::Heads, Some(free.clone()))).unwrap();
+    assert_eq!(attr(&events_of(&res, "coinflip_promo_redeemed")[0], "discount_bps"), "10000");
+    accept_bet(&mut deps, &env, ACCEPTOR, 1, Side::Tails).unwrap();
+    let secret = Binary::from(b"secret_32_bytes_exactly_here!!!!".to_vec());
+    let res = reveal_bet(&mut deps, &env, MAKER, 1, Side::Heads, secret).unwrap();
+    let applied = &events_of(&res, "coinflip_promo_discount_applied")[0];
+    assert_eq!(attr(applied, "voucher_id"), "free");
+    let bet = query_bet(&deps, &env, 1);
+    assert_eq!((bet.commission_bps, bet.commission_paid), (Some(0), None));
+    assert_eq!(query_vault_balance(&deps, &env, MAKER).available, Uint128::new(6000));
+
+    let err = execute(&mut deps, MAKER, create(1000, &Side::Heads, Some(free))).unwrap_err();
+    assert!(matches!(err, ContractError::VoucherAlreadyRedeemed { .. }));
+    let query = |player: Option<&str>| {
+        let msg = QueryMsg::PromoVoucher {
+            id: "free".to_string(),
+            player: player.map(str::to_string),
+        };
+        let res = crate::contract::query(deps.as_ref(), env.clone(), msg).unwrap();
+        from_json::<PromoVoucherResponse>(res).unwrap()
+    };
+    let redeemed = query(Some(MAKER));
+    assert_eq!((redeemed.redemptions, redeemed.redeemed_on), (1, Some(1)));
+    assert_eq!(query(Some(ACCEPTOR)).redeemed_on, None);
+
+    // A bonus is paid from the treasury's vault balance
+    let bonus = PromoGrant::Bonus { amount: Uint128::new(200) };
+    let bonus = sign_voucher(&key, &env, "bonus", bonus, now + 100, 1, None);
+    create_bet(&mut deps, &env, MAKER, 1000, compute_commitment(MAKER, &Side::Heads, b"s2"))
+        .unwrap();
+    let accept = |voucher| ExecuteMsg::AcceptBet { bet_id: 2, guess: Side::Heads, voucher };
+    let err = execute(&mut deps, ACCEPTOR, accept(Some(bonus.clone()))).unwrap_err();
+    assert!(matches!(err, ContractError::PromoBonusUnfunded { .. }));
+    deposit(&mut deps, &env, TREASURY, 300).unwrap();
+    execute(&mut deps, ACCEPTOR, accept(Some(bonus.clone()))).unwrap();
+    assert_eq!(query_vault_balance(&deps, &env, TREASURY).available, Uint128::new(100));
+    // With no wagering multiple set, the bonus converts to `available` right away
+    assert_eq!(query_bet(&deps, &env, 2).acceptor_bonus, Uint128::zero());
+    assert_eq!(query_vault_balance(&deps, &env, ACCEPTOR).available, Uint128::new(3200));
+
+    let err = execute(&mut deps, RANDOM_USER, create(1000, &Side::Tails, Some(bonus))).unwrap_err();
+    assert!(matches!(err, ContractError::VoucherExhausted { .. }));
+    assert_totals_consistent(&deps);
+}
+
+#[test]
+fn test_promo_voucher_bound_to_player_and_refunded_on_cancel() {
+    use crate::msg::{ExecuteMsg, PromoGrant, PromoVoucher, PromoVoucherResponse, QueryMsg};
+    use cosmwasm_std::testing::mock_info;
+    use cosmwasm_std::from_json;
+
+    let (mut deps, env) = setup_contract();
+    let now = env.block.time.seconds();
+    let (key, pubkey) = promo_key();
+    let execute = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, sender, msg| {
+        crate::contract::execute(deps.as_mut(), env.clone(), mock_info(sender, &[]), msg)
+    };
+    let create = |voucher: Option<PromoVoucher>| ExecuteMsg::CreateBet {
+        amount: Uint128::new(1000),
+        commitment: compute_commitment(MAKER, &Side::Heads, b"secret_32_bytes_exactly_here!!!!"),
+        voucher,
+    };
+    let redemptions = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, player: &str| {
+        let msg = QueryMsg::PromoVoucher {
+            id: "vip".to_string(),
+            player: Some(player.to_string()),
+        };
+        let res = crate::contract::query(deps.as_ref(), env.clone(), msg).unwrap();
+        let res: PromoVoucherResponse = from_json(res).unwrap();
+        (res.redemptions, res.redeemed_on)
+    };
+    execute(&mut deps, ADMIN, ExecuteMsg::UpdatePromoKey { pubkey: Some(pubkey) }).unwrap();
+    deposit(&mut deps, &env, MAKER, 5000).unwrap();
+    deposit(&mut deps, &env, ACCEPTOR, 5000).unwrap();
+
+    let grant = PromoGrant::CommissionDiscount { bps: 10_000 };
+    let vip = sign_voucher(&key, &env, "vip", grant, now + 100, 1, Some(MAKER));
+
+    // The player cannot be swapped without breaking the signature
+    let mut swapped = vip.clone();
+    swapped.player = Some(ACCEPTOR.to_string());
+    let accept = |voucher| ExecuteMsg::AcceptBet { bet_id: 1, guess: Side::Tails, voucher };
+    execute(&mut deps, MAKER, create(None)).unwrap();
+    let err = execute(&mut deps, ACCEPTOR, accept(Some(swapped))).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidVoucher { .. }));
+
+    // Another player holding the voucher cannot redeem it
+    let err = execute(&mut deps, ACCEPTOR, accept(Some(vip.clone()))).unwrap_err();
+    assert!(matches!(err, ContractError::VoucherNotForPlayer { .. }));
+    assert_eq!(redemptions(&deps, ACCEPTOR), (0, None));
+
+    // Canceling the bet gives the use back
+    execute(&mut deps, MAKER, ExecuteMsg::CancelBet { bet_id: 1 }).unwrap();
+    execute(&mut deps, MAKER, create(Some(vip.clone()))).unwrap();
+    assert_eq!(redemptions(&deps, MAKER), (1, Some(2)));
+    let res = execute(&mut deps, MAKER, ExecuteMsg::CancelBet { bet_id: 2 }).unwrap();
+    let refunded = &events_of(&res, "coinflip_promo_refunded")[0];
+    assert_eq!(attr(refunded, "voucher_id"), "vip");
+    assert_eq!(attr(refunded, "redemptions"), "0");
+    assert_eq!(redemptions(&deps, MAKER), (0, None));
+
+    // ...and the voucher still works on the next bet
+    let res = execute(&mut deps, MAKER, create(Some(vip))).unwrap();
+    assert_eq!(attr(&events_of(&res, "coinflip_promo_redeemed")[0], "bet_id"), "3");
+    assert_eq!(redemptions(&deps, MAKER), (1, Some(3)));
+    assert_totals_consistent(&deps);
+}
+
+// ============================================================
+// Bonus balance
+// ============================================================
+
+#[test]
+fn test_bonus_balance_wagering() {
+    use crate::msg::{BonusResponse, ExecuteMsg, QueryMsg};
+    use cosmwasm_std::testing::mock_info;
+    use cosmwasm_std::from_json;
+
+    let (mut deps, env) = setup_contract();
+    let execute = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, sender, msg| {
+        crate::contract::execute(deps.as_mut(), env.clone(), mock_info(sender, &[]), msg)
+    };
+    let credit = |address: &str, amount| ExecuteMsg::CreditBonus {
+        address: address.to_string(),
+        amount: Uint128::new(amount),
+    };
+    let bonus_of = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, address: &str| {
+        let msg = QueryMsg::Bonus { address: address.to_string() };
+        let res = crate::contract::query(deps.as_ref(), env.clone(), msg).unwrap();
+        from_json::<BonusResponse>(res).unwrap()
+    };
+    let secret = b"secret_32_bytes_exactly_here!!!!";
+    let commitment = compute_commitment(MAKER, &Side::Heads, secret);
+    let reveal = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, bet_id| {
+        reveal_bet(deps, &env, MAKER, bet_id, Side::Heads, Binary::from(secret.to_vec()))
+    };
+
+    deposit(&mut deps, &env, TREASURY, 1000).unwrap();
+    deposit(&mut deps, &env, MAKER, 1000).unwrap();
+    deposit(&mut deps, &env, ACCEPTOR, 1000).unwrap();
+
+    let err = execute(&mut deps, ADMIN, ExecuteMsg::UpdateBonusWagering { multiple: 101 });
+    assert!(matches!(err.unwrap_err(), ContractError::InvalidWageringMultiple { max: 100 }));
+    let err = execute(&mut deps, MAKER, ExecuteMsg::UpdateBonusWagering { multiple: 3 });
+    assert!(matches!(err.unwrap_err(), ContractError::Unauthorized));
+    execute(&mut deps, ADMIN, ExecuteMsg::UpdateBonusWagering { multiple: 3 }).unwrap();
+    assert_eq!(query_config(&deps, &env).bonus_wagering_multiple, 3);
+
+    let err = execute(&mut deps, MAKER, credit(MAKER, 100)).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized));
+    let err = execute(&mut deps, ADMIN, credit(MAKER, 2000)).unwrap_err();
+    assert!(matches!(err, ContractError::InsufficientAvailableBalance { .. }));
+    execute(&mut deps, ADMIN, credit(MAKER, 100)).unwrap();
+    let bonus = bonus_of(&deps, MAKER);
+    assert_eq!((bonus.bonus, bonus.wagering_required), (Uint128::new(100), Uint128::new(300)));
+    assert_eq!(query_vault_balance(&deps, &env, TREASURY).available, Uint128::new(900));
+
+    // The bonus can be staked but not withdrawn
+    let err = withdraw(&mut deps, &env, MAKER, 1001).unwrap_err();
+    assert!(matches!(err, ContractError::InsufficientAvailableBalance { .. }));
+    create_bet(&mut deps, &env, MAKER, 1100, commitment.clone()).unwrap();
+    let balance = query_vault_balance(&deps, &env, MAKER);
+    assert_eq!((balance.available, balance.bonus), (Uint128::zero(), Uint128::zero()));
+    assert_eq!(query_bet(&deps, &env, 1).maker_bonus, Uint128::new(100));
+    assert_eq!(bonus_of(&deps, MAKER).bonus_locked, Uint128::new(100));
+    // A refund returns the bonus part to the bonus
+    cancel_bet(&mut deps, &env, MAKER, 1).unwrap();
+    let balance = query_vault_balance(&deps, &env, MAKER);
+    assert_eq!((balance.available, balance.bonus), (Uint128::new(1000), Uint128::new(100)));
+
+    // 100 of the 150 stake is bonus, so 2/3 of the 270 payout stays bonus
+    create_bet(&mut deps, &env, MAKER, 150, commitment.clone()).unwrap();
+    accept_bet(&mut deps, &env, ACCEPTOR, 2, Side::Tails).unwrap();
+    reveal(&mut deps, 2).unwrap();
+    let balance = query_vault_balance(&deps, &env, MAKER);
+    assert_eq!((balance.available, balance.bonus), (Uint128::new(1040), Uint128::new(180)));
+    let bonus = bonus_of(&deps, MAKER);
+    assert_eq!((bonus.wagered, bonus.wagering_remaining), (Uint128::new(150), Uint128::new(150)));
+
+    // Playing through the rest of the 300 converts the whole bonus
+    create_bet(&mut deps, &env, MAKER, 200, commitment.clone()).unwrap();
+    accept_bet(&mut deps, &env, ACCEPTOR, 3, Side::Tails).unwrap();
+    let res = reveal(&mut deps, 3).unwrap();
+    let converted = &events_of(&res, "coinflip_bonus_converted")[0];
+    assert_eq!(attr(converted, "amount"), "324");
+    let balance = query_vault_balance(&deps, &env, MAKER);
+    assert_eq!((balance.available, balance.bonus), (Uint128::new(1380), Uint128::zero()));
+    assert_eq!(bonus_of(&deps, MAKER).wagering_required, Uint128::zero());
+
+    // Losing the whole bonus drops the requirement
+    execute(&mut deps, ADMIN, credit(ACCEPTOR, 50)).unwrap();
+    create_bet(&mut deps, &env, MAKER, 100, commitment).unwrap();
+    accept_bet(&mut deps, &env, ACCEPTOR, 4, Side::Tails).unwrap();
+    reveal(&mut deps, 4).unwrap();
+    let bonus = bonus_of(&deps, ACCEPTOR);
+    assert_eq!((bonus.bonus, bonus.wagering_required), (Uint128::zero(), Uint128::zero()));
+    assert_totals_consistent(&deps);
+}
+
+#[test]
+fn test_bonus_stake_split_on_a_loss() {
+    use crate::msg::{BonusResponse, ExecuteMsg, QueryMsg};
+    use cosmwasm_std::from_json;
+    use cosmwasm_std::testing::mock_info;
+
+    let (mut deps, env) = setup_contract();
+    let credit = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, amount| {
+        let msg = ExecuteMsg::CreditBonus {
+            address: MAKER.to_string(),
+            amount: Uint128::new(amount),
+        };
+        crate::contract::execute(deps.as_mut(), env.clone(), mock_info(ADMIN, &[]), msg)
+    };
+    let bonus_of = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| {
+        let msg = QueryMsg::Bonus { address: MAKER.to_string() };
+        let res = crate::contract::query(deps.as_ref(), env.clone(), msg).unwrap();
+        from_json::<BonusResponse>(res).unwrap()
+    };
+    let secret = b"secret_32_bytes_exactly_here!!!!";
+
+    deposit(&mut deps, &env, TREASURY, 1000).unwrap();
+    deposit(&mut deps, &env, MAKER, 1000).unwrap();
+    deposit(&mut deps, &env, ACCEPTOR, 1000).unwrap();
+    let msg = ExecuteMsg::UpdateBonusWagering { multiple: 3 };
+    crate::contract::execute(deps.as_mut(), env.clone(), mock_info(ADMIN, &[]), msg).unwrap();
+    credit(&mut deps, 100).unwrap();
+
+    // The bonus is staked first: 100 of the 150 stake is bonus, 50 is cash
+    create_bet(&mut deps, &env, MAKER, 150, compute_commitment(MAKER, &Side::Heads, secret))
+        .unwrap();
+    assert_eq!(query_bet(&deps, &env, 1).maker_bonus, Uint128::new(100));
+    // Bonus credited while the stake is live stays apart from it
+    credit(&mut deps, 50).unwrap();
+    accept_bet(&mut deps, &env, ACCEPTOR, 1, Side::Heads).unwrap();
+    let res = reveal_bet(&mut deps, &env, MAKER, 1, Side::Heads, Binary::from(secret.to_vec()));
+    assert!(events_of(&res.unwrap(), "coinflip_bonus_converted").is_empty());
+
+    // The loss takes both parts of the stake; the whole stake counts as wagered
+    let balance = query_vault_balance(&deps, &env, MAKER);
+    assert_eq!((balance.available, balance.bonus), (Uint128::new(950), Uint128::new(50)));
+    let bonus = bonus_of(&deps);
+    assert_eq!(bonus.bonus_locked, Uint128::zero());
+    assert_eq!((bonus.wagering_required, bonus.wagered), (Uint128::new(450), Uint128::new(150)));
+    assert_eq!(bonus.wagering_remaining, Uint128::new(300));
+    // The acceptor staked cash only, so the whole payout of 270 is cash
+    let balance = query_vault_balance(&deps, &env, ACCEPTOR);
+    assert_eq!((balance.available, balance.bonus), (Uint128::new(1120), Uint128::zero()));
+    assert_totals_consistent(&deps);
+}
+
+#[test]
+fn test_bonus_release_errors_instead_of_panicking_on_drift() {
+    use crate::msg::ExecuteMsg;
+    use crate::state::BONUS_WAGERING;
+    use cosmwasm_std::testing::mock_info;
+    use cosmwasm_std::{Addr, StdError};
+
+    let (mut deps, env) = setup_contract();
+    deposit(&mut deps, &env, TREASURY, 1000).unwrap();
+    let credit = ExecuteMsg::CreditBonus {
+        address: MAKER.to_string(),
+        amount: Uint128::new(100),
+    };
+    for msg in [ExecuteMsg::UpdateBonusWagering { multiple: 3 }, credit] {
+        crate::contract::execute(deps.as_mut(), env.clone(), mock_info(ADMIN, &[]), msg).unwrap();
+    }
+    let commitment = compute_commitment(MAKER, &Side::Heads, b"secret_32_bytes_exactly_here!!!!");
+    create_bet(&mut deps, &env, MAKER, 100, commitment).unwrap();
+
+    // Drift: the wagering record no longer holds the bonus locked in the bet
+    let maker = Addr::unchecked(MAKER);
+    let mut wagering = BONUS_WAGERING.load(&deps.storage, &maker).unwrap();
+    wagering.locked = Uint128::new(40);
+    BONUS_WAGERING.save(&mut deps.storage, &maker, &wagering).unwrap();
+    let err = cancel_bet(&mut deps, &env, MAKER, 1).unwrap_err();
+    assert!(matches!(err, ContractError::Std(StdError::Overflow { .. })));
+}
+
+// ============================================================
+// Player limits
+// ============================================================
+
+#[test]
+fn test_player_limits_tighten_now_and_loosen_later() {
+    use crate::msg::{ExecuteMsg, PlayerLimitsResponse, QueryMsg};
+    use cosmwasm_std::testing::mock_info;
+    use cosmwasm_std::{from_json, Env};
+
+    let (mut deps, env) = setup_contract();
+    let now = env.block.time.seconds();
+    let set_limits = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>,
+                      env: &Env,
+                      daily_loss_limit: Option<u128>,
+                      max_bet: Option<u128>,
+                      cooldown_until: Option<u64>| {
+        let msg = ExecuteMsg::SetPlayerLimits {
+            daily_loss_limit: daily_loss_limit.map(Uint128::new),
+            max_bet: max_bet.map(Uint128::new),
+            cooldown_until,
+        };
+        crate::contract::execute(deps.as_mut(), env.clone(), mock_info(MAKER, &[]), msg)
+    };
+    let limits_of = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, env: &Env| {
+        let msg = QueryMsg::PlayerLimits { address: MAKER.to_string() };
+        let res = crate::contract::query(deps.as_ref(), env.clone(), msg).unwrap();
+        from_json::<PlayerLimitsResponse>(res).unwrap()
+    };
+    let secret = b"secret_32_bytes_exactly_here!!!!";
+    let commitment = compute_commitment(MAKER, &Side::Heads, secret);
+
+    deposit(&mut deps, &env, MAKER, 5000).unwrap();
+    deposit(&mut deps, &env, ACCEPTOR, 5000).unwrap();
+    set_limits(&mut deps, &env, Some(500), Some(300), None).unwrap();
+    let limits = limits_of(&deps, &env);
+    assert_eq!(limits.limits.max_bet, Some(Uint128::new(300)));
+    assert_eq!((limits.pending, limits.daily_loss_remaining), (None, Some(Uint128::new(500))));
+
+    let err = create_bet(&mut deps, &env, MAKER, 400, commitment.clone()).unwrap_err();
+    assert!(matches!(err, ContractError::AboveMaxBet { .. }));
+    create_bet(&mut deps, &env, MAKER, 300, commitment.clone()).unwrap();
+    accept_bet(&mut deps, &env, ACCEPTOR, 1, Side::Heads).unwrap();
+    reveal_bet(&mut deps, &env, MAKER, 1, Side::Heads, Binary::from(secret.to_vec())).unwrap();
+    let limits = limits_of(&deps, &env);
+    assert_eq!(limits.daily_loss, Uint128::new(300));
+    assert_eq!(limits.daily_loss_remaining, Some(Uint128::new(200)));
+
+    // Stakes in live bets count as lost
+    let err = create_bet(&mut deps, &env, MAKER, 250, commitment.clone()).unwrap_err();
+    assert!(matches!(err, ContractError::DailyLossLimit { .. }));
+    create_bet(&mut deps, &env, MAKER, 200, commitment.clone()).unwrap();
+    let err = create_bet(&mut deps, &env, MAKER, 10, commitment.clone()).unwrap_err();
+    assert!(matches!(err, ContractError::DailyLossLimit { .. }));
+
+    // A cooldown applies at once and blocks accepting the maker's bets
+    set_limits(&mut deps, &env, Some(500), Some(300), Some(now + 1000)).unwrap();
+    let err = accept_bet(&mut deps, &env, ACCEPTOR, 2, Side::Tails).unwrap_err();
+    assert!(matches!(err, ContractError::PlayerCooldown { until, .. } if until == now + 1000));
+    let later = env_at_time(now + 1001);
+    accept_bet(&mut deps, &later, ACCEPTOR, 2, Side::Tails).unwrap();
+
+    // Lifting the limits waits a day; dropping the expired cooldown does not count
+    set_limits(&mut deps, &later, None, None, None).unwrap();
+    let limits = limits_of(&deps, &later);
+    assert_eq!(limits.limits.daily_loss_limit, Some(Uint128::new(500)));
+    assert_eq!(limits.limits.cooldown_until, None);
+    let effective_at = limits.pending.unwrap().effective_at;
+    assert_eq!(effective_at, now + 1001 + 86_400);
+    let lifted = env_at_time(effective_at);
+    assert_eq!(limits_of(&deps, &lifted).limits.max_bet, None);
+    create_bet(&mut deps, &lifted, MAKER, 400, commitment).unwrap();
+    assert_totals_consistent(&deps);
+}
+
+#[test]
+fn test_player_limits_loosen_delay() {
+    use crate::execute::limits::LOOSEN_DELAY_SECS;
+    use crate::msg::{ExecuteMsg, PlayerLimitsResponse, QueryMsg};
+    use cosmwasm_std::testing::mock_info;
+    use cosmwasm_std::{from_json, Env};
+
+    let (mut deps, env) = setup_contract();
+    let now = env.block.time.seconds();
+    let set_limits = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>,
+                      env: &Env,
+                      daily_loss_limit: Option<u128>,
+                      max_bet: Option<u128>,
+                      cooldown_until: Option<u64>| {
+        let msg = ExecuteMsg::SetPlayerLimits {
+            daily_loss_limit: daily_loss_limit.map(Uint128::new),
+            max_bet: max_bet.map(Uint128::new),
+            cooldown_until,
+        };
+        crate::contract::execute(deps.as_mut(), env.clone(), mock_info(MAKER, &[]), msg)
+    };
+    let limits_of = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, env: &Env| {
+        let msg = QueryMsg::PlayerLimits { address: MAKER.to_string() };
+        let res = crate::contract::query(deps.as_ref(), env.clone(), msg).unwrap();
+        from_json::<PlayerLimitsResponse>(res).unwrap()
+    };
+    let commitment = compute_commitment(MAKER, &Side::Heads, b"secret_32_bytes_exactly_here!!!!");
+    deposit(&mut deps, &env, MAKER, 5000).unwrap();
+
+    // Raising the max bet waits; the new daily loss limit in the same call applies at once
+    set_limits(&mut deps, &env, None, Some(300), None).unwrap();
+    let res = set_limits(&mut deps, &env, Some(2000), Some(1000), None).unwrap();
+    let set = &events_of(&res, "coinflip_player_limits_set")[0];
+    let effective_at = now + LOOSEN_DELAY_SECS;
+    assert_eq!(attr(set, "pending_effective_at"), effective_at.to_string());
+    let limits = limits_of(&deps, &env);
+    assert_eq!(limits.limits.daily_loss_limit, Some(Uint128::new(2000)));
+    assert_eq!(limits.limits.max_bet, Some(Uint128::new(300)));
+    assert_eq!(limits.pending.unwrap().limits.max_bet, Some(Uint128::new(1000)));
+
+    let almost = env_at_time(effective_at - 1);
+    let err = create_bet(&mut deps, &almost, MAKER, 500, commitment.clone()).unwrap_err();
+    assert!(matches!(err, ContractError::AboveMaxBet { .. }));
+    let due = env_at_time(effective_at);
+    assert_eq!(limits_of(&deps, &due).limits.max_bet, Some(Uint128::new(1000)));
+    create_bet(&mut deps, &due, MAKER, 500, commitment.clone()).unwrap();
+
+    // A tighter setting replaces a waiting change, which then never applies
+    set_limits(&mut deps, &due, Some(2000), None, None).unwrap();
+    assert!(limits_of(&deps, &due).pending.is_some());
+    set_limits(&mut deps, &due, Some(2000), Some(200), None).unwrap();
+    assert!(limits_of(&deps, &due).pending.is_none());
+    let later = env_at_time(effective_at + 2 * LOOSEN_DELAY_SECS);
+    assert_eq!(limits_of(&deps, &later).limits.max_bet, Some(Uint128::new(200)));
+
+    // A running cooldown cannot be cut short at once
+    let until = effective_at + 10_000;
+    set_limits(&mut deps, &due, Some(2000), Some(200), Some(until)).unwrap();
+    set_limits(&mut deps, &due, Some(2000), Some(200), Some(effective_at + 10)).unwrap();
+    let shortly = env_at_time(effective_at + 100);
+    let err = create_bet(&mut deps, &shortly, MAKER, 100, commitment).unwrap_err();
+    assert!(matches!(err, ContractError::PlayerCooldown { until: u, .. } if u == until));
+    let limits = limits_of(&deps, &shortly);
+    assert_eq!(limits.pending.unwrap().effective_at, effective_at + LOOSEN_DELAY_SECS);
+}
+
+// ============================================================
+// Pair caps
+// ============================================================
+
+#[test]
+fn test_pair_caps_reject_and_non_qualifying() {
+    use crate::execute::rakeback::epoch_of;
+    use crate::msg::{ExecuteMsg, PairExposureResponse, QueryMsg, ReferralResponse};
+    use crate::state::{PairCapMode, PairCaps, RAKEBACK};
+    use cosmwasm_std::testing::mock_info;
+    use cosmwasm_std::{from_json, Addr, Env};
+
+    let (mut deps, env) = setup_contract();
+    let update_caps = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>,
+                       sender: &str,
+                       caps: Option<PairCaps>| {
+        let msg = ExecuteMsg::UpdatePairCaps { caps };
+        crate::contract::execute(deps.as_mut(), env.clone(), mock_info(sender, &[]), msg)
+    };
+    let exposure_of = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, env: &Env| {
+        let msg = QueryMsg::PairExposure {
+            address: MAKER.to_string(),
+            day: None,
+            start_after: None,
+            limit: None,
+        };
+        let res = crate::contract::query(deps.as_ref(), env.clone(), msg).unwrap();
+        from_json::<PairExposureResponse>(res).unwrap()
+    };
+    let referral_earned = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| {
+        let msg = QueryMsg::Referral { address: RANDOM_USER.to_string() };
+        let res = crate::contract::query(deps.as_ref(), env.clone(), msg).unwrap();
+        from_json::<ReferralResponse>(res).unwrap().total_earned
+    };
+    let rebate_of = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| {
+        let key = (epoch_of(env.block.time.seconds()), &Addr::unchecked(MAKER));
+        RAKEBACK.may_load(&deps.storage, key).unwrap().unwrap_or_default().rebate
+    };
+    let secret = b"secret_32_bytes_exactly_here!!!!";
+    let commitment = compute_commitment(MAKER, &Side::Heads, secret);
+
+    deposit(&mut deps, &env, MAKER, 5000).unwrap();
+    deposit(&mut deps, &env, ACCEPTOR, 5000).unwrap();
+    let admin = mock_info(ADMIN, &[]);
+    for player in [MAKER, ACCEPTOR] {
+        let msg = ExecuteMsg::SetReferrer { referrer: RANDOM_USER.to_string() };
+        crate::contract::execute(deps.as_mut(), env.clone(), mock_info(player, &[]), msg).unwrap();
+    }
+    let msg = ExecuteMsg::UpdateReferralBps { bps: vec![100] };
+    crate::contract::execute(deps.as_mut(), env.clone(), admin.clone(), msg).unwrap();
+    let msg = ExecuteMsg::UpdateRakeback { bps: 1000, claim_epochs: 4 };
+    crate::contract::execute(deps.as_mut(), env.clone(), admin, msg).unwrap();
+
+    let caps = PairCaps {
+        max_games: 1,
+        max_volume: Uint128::zero(),
+        mode: PairCapMode::Reject,
+    };
+    let err = update_caps(&mut deps, MAKER, Some(caps.clone())).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized));
+    let no_caps = PairCaps { max_games: 0, ..caps.clone() };
+    let err = update_caps(&mut deps, ADMIN, Some(no_caps)).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidPairCaps { .. }));
+    update_caps(&mut deps, ADMIN, Some(caps.clone())).unwrap();
+
+    // First game of the day between the pair counts as usual
+    create_bet(&mut deps, &env, MAKER, 1000, commitment.clone()).unwrap();
+    accept_bet(&mut deps, &env, ACCEPTOR, 1, Side::Heads).unwrap();
+    reveal_bet(&mut deps, &env, MAKER, 1, Side::Heads, Binary::from(secret.to_vec())).unwrap();
+    let earned = referral_earned(&deps);
+    let rebate = rebate_of(&deps);
+    assert!(!earned.is_zero() && !rebate.is_zero());
+
+    // In reject mode the second game is refused and not counted
+    create_bet(&mut deps, &env, MAKER, 1000, commitment).unwrap();
+    let err = accept_bet(&mut deps, &env, ACCEPTOR, 2, Side::Heads).unwrap_err();
+    assert!(matches!(err, ContractError::PairCapReached { .. }));
+    assert_eq!(exposure_of(&deps, &env).pairs[0].games, 1);
+
+    // Marked non-qualifying, it is played but left out of stats and rewards
+    let caps = PairCaps { mode: PairCapMode::NonQualifying, ..caps };
+    update_caps(&mut deps, ADMIN, Some(caps.clone())).unwrap();
+    let res = accept_bet(&mut deps, &env, ACCEPTOR, 2, Side::Heads).unwrap();
+    assert!(res.events.iter().any(|e| e.ty == "coinflip_bet_non_qualifying"));
+    reveal_bet(&mut deps, &env, MAKER, 2, Side::Heads, Binary::from(secret.to_vec())).unwrap();
+    assert!(query_bet(&deps, &env, 2).non_qualifying);
+    assert!(!query_bet(&deps, &env, 1).non_qualifying);
+    assert_eq!(query_player_stats(&deps, &env, MAKER).games, 1);
+    assert_eq!(query_player_stats(&deps, &env, ACCEPTOR).games, 1);
+    assert_eq!((referral_earned(&deps), rebate_of(&deps)), (earned, rebate));
+
+    let exposure = exposure_of(&deps, &env);
+    assert_eq!(exposure.caps, Some(caps));
+    assert_eq!(exposure.pairs.len(), 1);
+    assert_eq!(exposure.pairs[0].opponent, ACCEPTOR);
+    assert_eq!((exposure.pairs[0].games, exposure.pairs[0].volume), (2, Uint128::new(4000)));
+    assert_eq!(exposure.next_start_after, None);
+
+    // Pairs start over the next day
+    let tomorrow = env_at_time(env.block.time.seconds() + 86_400);
+    assert!(exposure_of(&deps, &tomorrow).pairs.is_empty());
+    assert_totals_consistent(&deps);
+
+    // Pruned, the non-qualifying bet stays out of the daily totals too
+    let archive = env_at_time(env.block.time.seconds() + 31 * 86_400);
+    prune_bets(&mut deps, &archive, 10, 10).unwrap();
+    let msg = QueryMsg::DailyStats { start_after: None, limit: None };
+    let res = crate::contract::query(deps.as_ref(), archive, msg).unwrap();
+    let days = from_json::<crate::msg::DailyStatsResponse>(res).unwrap().days;
+    assert_eq!((days.len(), days[0].bets, days[0].games), (1, 1, 1));
+    assert_eq!(days[0].volume, Uint128::new(2000));
+    assert_eq!(query_player_stats(&deps, &env, MAKER).games, 1);
+}
+
+#[test]
+fn test_pair_volume_cap_counts_both_roles_and_pairs_are_pruned() {
+    use crate::msg::{ExecuteMsg, PairExposureResponse, QueryMsg};
+    use crate::state::{PairCapMode, PairCaps};
+    use cosmwasm_std::testing::mock_info;
+    use cosmwasm_std::{from_json, Env};
+
+    let (mut deps, env) = setup_contract();
+    let day = env.block.time.seconds() / 86_400;
+    let exposure_of = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, address: &str, start_after| {
+        let msg = QueryMsg::PairExposure {
+            address: address.to_string(),
+            day: Some(day),
+            start_after,
+            limit: Some(1),
+        };
+        let res = crate::contract::query(deps.as_ref(), env.clone(), msg).unwrap();
+        from_json::<PairExposureResponse>(res).unwrap()
+    };
+    let prune = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, env: &Env| {
+        let msg = ExecuteMsg::PrunePairStats { limit: Some(3) };
+        let info = mock_info(RANDOM_USER, &[]);
+        let res = crate::contract::execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let value = |key| res.attributes.iter().find(|a| a.key == key).unwrap().value.clone();
+        (value("pruned"), value("completed") == "true")
+    };
+    let secret = b"secret_32_bytes_exactly_here!!!!";
+    let mut next_bet = 1;
+    let mut play = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, maker, acceptor| {
+        let commitment = compute_commitment(maker, &Side::Heads, secret);
+        create_bet(deps, &env, maker, 1000, commitment).unwrap();
+        accept_bet(deps, &env, acceptor, next_bet, Side::Heads).unwrap();
+        next_bet += 1;
+        query_bet(deps, &env, next_bet - 1).non_qualifying
+    };
+
+    for player in [MAKER, ACCEPTOR, RANDOM_USER] {
+        deposit(&mut deps, &env, player, 5000).unwrap();
+    }
+    let caps = PairCaps {
+        max_games: 0,
+        max_volume: Uint128::new(3000),
+        mode: PairCapMode::NonQualifying,
+    };
+    let msg = ExecuteMsg::UpdatePairCaps { caps: Some(caps) };
+    crate::contract::execute(deps.as_mut(), env.clone(), mock_info(ADMIN, &[]), msg).unwrap();
+
+    // The pair's volume adds up whichever of the two makes the bet; other pairs are apart
+    assert!(!play(&mut deps, MAKER, ACCEPTOR));
+    assert!(play(&mut deps, ACCEPTOR, MAKER));
+    assert!(!play(&mut deps, MAKER, RANDOM_USER));
+
+    let page = exposure_of(&deps, MAKER, None);
+    assert_eq!(page.pairs.len(), 1);
+    assert_eq!(page.pairs[0].opponent, ACCEPTOR);
+    assert_eq!((page.pairs[0].games, page.pairs[0].volume), (2, Uint128::new(4000)));
+    assert_eq!(page.next_start_after.as_deref(), Some(ACCEPTOR));
+    let page = exposure_of(&deps, MAKER, page.next_start_after);
+    assert_eq!(page.pairs[0].opponent, RANDOM_USER);
+    assert_eq!((page.pairs[0].games, page.pairs[0].volume), (1, Uint128::new(2000)));
+    let page = exposure_of(&deps, ACCEPTOR, None);
+    assert_eq!((page.pairs[0].opponent.as_str(), page.pairs[0].games), (MAKER, 2));
+
+    // Kept for 30 days, then pruned in batches: 2 pairs saved under both players
+    let kept = env_at_time(env.block.time.seconds() + 30 * 86_400);
+    assert_eq!(prune(&mut deps, &kept), ("0".to_string(), true));
+    assert_eq!(exposure_of(&deps, MAKER, None).pairs.len(), 1);
+    let expired = env_at_time(env.block.time.seconds() + 31 * 86_400);
+    assert_eq!(prune(&mut deps, &expired), ("3".to_string(), false));
+    assert_eq!(prune(&mut deps, &expired), ("1".to_string(), true));
+    for player in [MAKER, ACCEPTOR, RANDOM_USER] {
+        assert!(exposure_of(&deps, player, None).pairs.is_empty());
+    }
 }
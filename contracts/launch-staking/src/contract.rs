@@ -1,5 +1,5 @@
 use cosmwasm_std::{
-    entry_point, to_json_binary, BankMsg, Binary, Coin, CosmosMsg, Deps, DepsMut, Env,
+    entry_point, to_json_binary, BankMsg, Binary, Coin, CosmosMsg, Deps, DepsMut, Env, Event,
     MessageInfo, Response, StdResult, Uint128, WasmMsg,
 };
 use cw2::set_contract_version;
@@ -8,9 +8,9 @@ use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 use crate::error::ContractError;
 use crate::msg::{
     ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg, ReceiveMsg, StakerInfoResponse,
-    StateResponse,
+    StateResponse, SudoMsg,
 };
-use crate::state::{Config, GlobalState, StakerInfo, CONFIG, PRECISION, STAKERS, STATE};
+use crate::state::{Config, GlobalState, StakerInfo, CONFIG, PAUSED, PRECISION, STAKERS, STATE};
 
 const CONTRACT_NAME: &str = "crates.io:launch-staking";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -49,6 +49,12 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    // While paused, stakers can still take out their LAUNCH and rewards
+    let exit = matches!(msg, ExecuteMsg::Unstake { .. } | ExecuteMsg::Claim {});
+    if !exit && PAUSED.may_load(deps.storage)?.unwrap_or(false) {
+        return Err(ContractError::Paused);
+    }
+
     match msg {
         ExecuteMsg::Receive(cw20_msg) => execute_receive(deps, info, cw20_msg),
         ExecuteMsg::Unstake { amount } => execute_unstake(deps, info, amount),
@@ -283,6 +289,72 @@ fn execute_transfer_admin(
         .add_attribute("new_admin", new_admin))
 }
 
+// ─── Sudo ───────────────────────────────────────────────────────────────────
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn sudo(deps: DepsMut, _env: Env, msg: SudoMsg) -> Result<Response, ContractError> {
+    match msg {
+        SudoMsg::ReplaceAdmin { new_admin } => sudo_replace_admin(deps, new_admin),
+        SudoMsg::Pause {} => sudo_set_paused(deps, true),
+        SudoMsg::Unpause {} => sudo_set_paused(deps, false),
+        SudoMsg::UpdateConfig { launch_cw20 } => sudo_update_config(deps, launch_cw20),
+    }
+}
+
+/// Governance: replace the admin.
+fn sudo_replace_admin(deps: DepsMut, new_admin: String) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    let old_admin = config.admin;
+    config.admin = deps.api.addr_validate(&new_admin)?;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "sudo_replace_admin")
+        .add_event(
+            Event::new("sudo_admin_replaced")
+                .add_attribute("old_admin", old_admin)
+                .add_attribute("new_admin", config.admin),
+        ))
+}
+
+/// Governance: pause or resume all execute messages.
+fn sudo_set_paused(deps: DepsMut, paused: bool) -> Result<Response, ContractError> {
+    PAUSED.save(deps.storage, &paused)?;
+
+    let (action, event) = if paused {
+        ("sudo_pause", "sudo_paused")
+    } else {
+        ("sudo_unpause", "sudo_unpaused")
+    };
+    Ok(Response::new()
+        .add_attribute("action", action)
+        .add_event(Event::new(event)))
+}
+
+/// Governance: force config changes.
+/// Swapping the LAUNCH token with stake outstanding would strand it, so that is rejected.
+fn sudo_update_config(
+    deps: DepsMut,
+    launch_cw20: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    if let Some(addr) = launch_cw20 {
+        let state = STATE.load(deps.storage)?;
+        if !state.total_staked.is_zero() {
+            return Err(ContractError::StakeOutstanding);
+        }
+        config.launch_cw20 = deps.api.addr_validate(&addr)?;
+    }
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "sudo_update_config")
+        .add_event(
+            Event::new("sudo_config_updated").add_attribute("launch_cw20", config.launch_cw20),
+        ))
+}
+
 // ─── Internal helpers ───────────────────────────────────────────────────────
 
 /// Settle (accrue) pending rewards for a staker based on the current global reward_per_token.
@@ -351,6 +423,7 @@ fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     Ok(ConfigResponse {
         admin: config.admin,
         launch_cw20: config.launch_cw20,
+        paused: PAUSED.may_load(deps.storage)?.unwrap_or(false),
     })
 }
 
@@ -15,6 +15,12 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized,
 
+    #[error("Contract is paused by governance")]
+    Paused,
+
+    #[error("Cannot change the LAUNCH token while tokens are staked")]
+    StakeOutstanding,
+
     #[error("No funds sent")]
     NoFundsSent,
 
@@ -29,6 +29,22 @@ pub enum ExecuteMsg {
     TransferAdmin { new_admin: String },
 }
 
+/// Chain governance messages, handled by the `sudo` entry point without an admin check.
+#[cw_serde]
+pub enum SudoMsg {
+    /// Replace the admin (e.g. lost or compromised key)
+    ReplaceAdmin { new_admin: String },
+
+    /// Block every execute message except `Unstake` and `Claim` until `Unpause`
+    Pause {},
+
+    /// Resume normal operation after `Pause`
+    Unpause {},
+
+    /// Point the contract at a different LAUNCH token. Only allowed while nothing is staked.
+    UpdateConfig { launch_cw20: Option<String> },
+}
+
 /// Sub-message for CW20 Receive hook
 #[cw_serde]
 pub enum ReceiveMsg {
@@ -58,6 +74,8 @@ pub enum QueryMsg {
 pub struct ConfigResponse {
     pub admin: Addr,
     pub launch_cw20: Addr,
+    /// Set by governance through `SudoMsg::Pause`
+    pub paused: bool,
 }
 
 #[cw_serde]
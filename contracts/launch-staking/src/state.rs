@@ -68,3 +68,6 @@ pub const CONFIG: Item<Config> = Item::new("config");
 pub const STATE: Item<GlobalState> = Item::new("state");
 /// Per-address staker info. Key = staker address string.
 pub const STAKERS: Map<&str, StakerInfo> = Map::new("stakers");
+
+/// Governance pause flag (see `SudoMsg::Pause`); absent means not paused.
+pub const PAUSED: Item<bool> = Item::new("paused");
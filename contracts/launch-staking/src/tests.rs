@@ -1,11 +1,12 @@
 use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
 use cosmwasm_std::{coins, from_json, to_json_binary, Addr, Uint128};
 
-use crate::contract::{execute, instantiate, query};
+use crate::contract::{execute, instantiate, query, sudo};
 use crate::error::ContractError;
 use crate::msg::*;
 use crate::state::{CONFIG, STAKERS, STATE};
 use cw20::Cw20ReceiveMsg;
+use cw_multi_test::Executor;
 
 const ADMIN: &str = "admin";
 const LAUNCH_CW20: &str = "launch_cw20_addr";
@@ -519,6 +520,177 @@ fn transfer_admin_unauthorized() {
     assert!(matches!(err, ContractError::Unauthorized));
 }
 
+// ──────────────────────────────────────────────────────────────────────────────
+// Governance sudo
+// ──────────────────────────────────────────────────────────────────────────────
+
+fn setup_app() -> (cw_multi_test::App, Addr) {
+    let mut app = cw_multi_test::App::default();
+    let code = cw_multi_test::ContractWrapper::new(execute, instantiate, query).with_sudo(sudo);
+    let code_id = app.store_code(Box::new(code));
+    let msg = InstantiateMsg {
+        launch_cw20: LAUNCH_CW20.to_string(),
+    };
+    let staking = app
+        .instantiate_contract(code_id, Addr::unchecked(ADMIN), &msg, &[], "staking", None)
+        .unwrap();
+    (app, staking)
+}
+
+#[test]
+fn sudo_replace_admin() {
+    let (mut app, staking) = setup_app();
+
+    let res = app
+        .wasm_sudo(
+            staking.clone(),
+            &SudoMsg::ReplaceAdmin {
+                new_admin: "new_admin".to_string(),
+            },
+        )
+        .unwrap();
+    let event = res
+        .events
+        .iter()
+        .find(|e| e.ty == "wasm-sudo_admin_replaced")
+        .unwrap();
+    assert!(event.attributes.iter().any(|a| a.key == "old_admin" && a.value == ADMIN));
+    assert!(event.attributes.iter().any(|a| a.key == "new_admin" && a.value == "new_admin"));
+
+    let transfer = ExecuteMsg::TransferAdmin {
+        new_admin: USER1.to_string(),
+    };
+    let err = app
+        .execute_contract(Addr::unchecked(ADMIN), staking.clone(), &transfer, &[])
+        .unwrap_err();
+    assert!(matches!(err.downcast_ref::<ContractError>(), Some(ContractError::Unauthorized)));
+    app.execute_contract(Addr::unchecked("new_admin"), staking, &transfer, &[])
+        .unwrap();
+}
+
+#[test]
+fn sudo_pause_and_unpause() {
+    use cosmwasm_std::{Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdResult};
+
+    let mut app = cw_multi_test::App::new(|router, _, storage| {
+        router
+            .bank
+            .init_balance(storage, &Addr::unchecked(TREASURY), coins(1_000, "uaxm"))
+            .unwrap();
+    });
+
+    // Stand-in LAUNCH token that accepts the transfers `Unstake` sends back
+    let token_code = cw_multi_test::ContractWrapper::new(
+        |_: DepsMut, _: Env, _: MessageInfo, _: cw20::Cw20ExecuteMsg| -> StdResult<Response> {
+            Ok(Response::new())
+        },
+        |_: DepsMut, _: Env, _: MessageInfo, _: Empty| -> StdResult<Response> {
+            Ok(Response::new())
+        },
+        |_: Deps, _: Env, _: Empty| -> StdResult<Binary> { Ok(Binary::default()) },
+    );
+    let token_id = app.store_code(Box::new(token_code));
+    let token = app
+        .instantiate_contract(token_id, Addr::unchecked(ADMIN), &Empty {}, &[], "launch", None)
+        .unwrap();
+
+    let code = cw_multi_test::ContractWrapper::new(execute, instantiate, query).with_sudo(sudo);
+    let code_id = app.store_code(Box::new(code));
+    let msg = InstantiateMsg {
+        launch_cw20: token.to_string(),
+    };
+    let staking = app
+        .instantiate_contract(code_id, Addr::unchecked(ADMIN), &msg, &[], "staking", None)
+        .unwrap();
+
+    let stake = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: USER1.to_string(),
+        amount: Uint128::new(1_000),
+        msg: to_json_binary(&ReceiveMsg::Stake {}).unwrap(),
+    });
+    app.execute_contract(token.clone(), staking.clone(), &stake, &[]).unwrap();
+    let distribute = ExecuteMsg::Distribute {};
+    let rewards = coins(500, "uaxm");
+    app.execute_contract(Addr::unchecked(TREASURY), staking.clone(), &distribute, &rewards)
+        .unwrap();
+
+    let res = app.wasm_sudo(staking.clone(), &SudoMsg::Pause {}).unwrap();
+    assert!(res.events.iter().any(|e| e.ty == "wasm-sudo_paused"));
+
+    // New stakes and distributions are blocked
+    let err = app
+        .execute_contract(token.clone(), staking.clone(), &stake, &[])
+        .unwrap_err();
+    assert!(matches!(err.downcast_ref::<ContractError>(), Some(ContractError::Paused)));
+    let err = app
+        .execute_contract(Addr::unchecked(TREASURY), staking.clone(), &distribute, &rewards)
+        .unwrap_err();
+    assert!(matches!(err.downcast_ref::<ContractError>(), Some(ContractError::Paused)));
+
+    let config: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(staking.clone(), &QueryMsg::Config {})
+        .unwrap();
+    assert!(config.paused);
+
+    // Stakers can still take out their rewards and their stake
+    app.execute_contract(Addr::unchecked(USER1), staking.clone(), &ExecuteMsg::Claim {}, &[])
+        .unwrap();
+    let balance = app.wrap().query_balance(USER1, "uaxm").unwrap();
+    assert_eq!(balance.amount, Uint128::new(500));
+    let unstake = ExecuteMsg::Unstake {
+        amount: Uint128::new(1_000),
+    };
+    app.execute_contract(Addr::unchecked(USER1), staking.clone(), &unstake, &[])
+        .unwrap();
+    let staker: StakerInfoResponse = app
+        .wrap()
+        .query_wasm_smart(
+            staking.clone(),
+            &QueryMsg::StakerInfo {
+                address: USER1.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(staker.staked, Uint128::zero());
+
+    let res = app.wasm_sudo(staking.clone(), &SudoMsg::Unpause {}).unwrap();
+    assert!(res.events.iter().any(|e| e.ty == "wasm-sudo_unpaused"));
+
+    app.execute_contract(token, staking, &stake, &[]).unwrap();
+}
+
+#[test]
+fn sudo_update_config() {
+    let (mut app, staking) = setup_app();
+    let update = SudoMsg::UpdateConfig {
+        launch_cw20: Some("new_launch_cw20".to_string()),
+    };
+
+    let res = app.wasm_sudo(staking.clone(), &update).unwrap();
+    assert!(res.events.iter().any(|e| e.ty == "wasm-sudo_config_updated"));
+
+    let config: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(staking.clone(), &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.launch_cw20, Addr::unchecked("new_launch_cw20"));
+
+    // Once something is staked the token can no longer change
+    let stake = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: USER1.to_string(),
+        amount: Uint128::new(1_000),
+        msg: to_json_binary(&ReceiveMsg::Stake {}).unwrap(),
+    });
+    app.execute_contract(Addr::unchecked("new_launch_cw20"), staking.clone(), &stake, &[])
+        .unwrap();
+    let err = app.wasm_sudo(staking, &update).unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<ContractError>(),
+        Some(ContractError::StakeOutstanding)
+    ));
+}
+
 // ──────────────────────────────────────────────────────────────────────────────
 // Queries
 // ──────────────────────────────────────────────────────────────────────────────
@@ -122,6 +122,23 @@ async function main() {
   log(`  Tx hash: ${migrateResult.transactionHash}`);
   log(`  Gas used: ${migrateResult.gasUsed}`);
 
+  if (resetState) {
+    // State reset runs in batches — drain it before the contract accepts other messages
+    let resetDone = false;
+    while (!resetDone) {
+      const res = await client.execute(
+        account.address,
+        CONTRACT_ADDR,
+        { continue_migration: { limit: 200 } },
+        "auto",
+      );
+      const attrs = res.events.flatMap((e) => e.attributes);
+      resetDone = attrs.some((a) => a.key === "completed" && a.value === "true");
+      const cleared = attrs.find((a) => a.key === "cleared_entries")?.value;
+      log(`  ContinueMigration: ${cleared} entries cleared${resetDone ? " (done)" : ""}`);
+    }
+  }
+
   // 6. Verify config after migration
   log("--- Config AFTER migration ---");
   const configAfter = await client.queryContractSmart(CONTRACT_ADDR, { config: {} });
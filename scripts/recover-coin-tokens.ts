@@ -132,6 +132,21 @@ async function main() {
     );
     log(`  Migrated! Tx: ${result.transactionHash}`);
     log(`  Gas used: ${result.gasUsed}`);
+
+    // State reset runs in batches — drain it before the contract accepts other messages
+    let resetDone = false;
+    while (!resetDone) {
+      const res = await client.execute(
+        account.address,
+        OLD_COINFLIP,
+        { continue_migration: { limit: 200 } },
+        "auto",
+      );
+      const attrs = res.events.flatMap((e) => e.attributes);
+      resetDone = attrs.some((a) => a.key === "completed" && a.value === "true");
+      const cleared = attrs.find((a) => a.key === "cleared_entries")?.value;
+      log(`  ContinueMigration: ${cleared} entries cleared${resetDone ? " (done)" : ""}`);
+    }
   } else {
     log("\n--- Step 1: SKIPPED (--skip-migrate) ---");
   }